@@ -0,0 +1,16 @@
+CREATE TABLE chats (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL UNIQUE,
+    title TEXT NOT NULL DEFAULT '',
+    description TEXT
+);
+
+CREATE TABLE users (
+    id SERIAL PRIMARY KEY,
+    username TEXT NOT NULL DEFAULT '',
+    account_id BIGINT NOT NULL UNIQUE,
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    name TEXT NOT NULL
+);
+
+CREATE INDEX idx_users_chat_id ON users (chat_id);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS gambles;
+DROP TABLE IF EXISTS user_stats;
@@ -0,0 +1,20 @@
+CREATE TABLE user_stats (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL UNIQUE REFERENCES users (id) ON DELETE CASCADE,
+    balance INTEGER NOT NULL DEFAULT 1000,
+    daily_limit INTEGER NOT NULL DEFAULT 100,
+    daily_used INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE gambles (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    message_id INTEGER NOT NULL,
+    gamble_type TEXT NOT NULL,
+    bet INTEGER NOT NULL,
+    change INTEGER NOT NULL,
+    is_win BOOLEAN NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_gambles_user_id_created_at ON gambles (user_id, created_at);
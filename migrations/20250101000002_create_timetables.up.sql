@@ -0,0 +1,17 @@
+CREATE TABLE timetables (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL UNIQUE REFERENCES chats (chat_id) ON DELETE CASCADE
+);
+
+CREATE TABLE timetable_entries (
+    id SERIAL PRIMARY KEY,
+    timetable_id INTEGER NOT NULL REFERENCES timetables (id) ON DELETE CASCADE,
+    week INTEGER NOT NULL CHECK (week IN (0, 1)),
+    day INTEGER NOT NULL CHECK (day BETWEEN 0 AND 6),
+    class_name TEXT NOT NULL,
+    class_type TEXT NOT NULL,
+    class_time TIME NOT NULL,
+    link TEXT
+);
+
+CREATE INDEX idx_timetable_entries_lookup ON timetable_entries (timetable_id, week, day, class_time);
@@ -0,0 +1,26 @@
+CREATE TABLE queues (
+    id SERIAL PRIMARY KEY,
+    title TEXT NOT NULL,
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    message_id INTEGER NOT NULL,
+    is_mixed BOOLEAN,
+    is_priority BOOLEAN NOT NULL DEFAULT FALSE,
+    is_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_queues_chat_id_message_id ON queues (chat_id, message_id) WHERE is_deleted = FALSE;
+
+CREATE TABLE queue_users (
+    id SERIAL PRIMARY KEY,
+    queue_id INTEGER NOT NULL REFERENCES queues (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    priority INTEGER,
+    is_frozen BOOLEAN DEFAULT FALSE,
+    UNIQUE (queue_id, user_id),
+    -- positions are rewritten row by row inside transactions, so the check runs at commit
+    CONSTRAINT queue_users_queue_id_position_key UNIQUE (queue_id, position) DEFERRABLE INITIALLY DEFERRED
+);
+
+CREATE INDEX idx_queue_users_user_id ON queue_users (user_id);
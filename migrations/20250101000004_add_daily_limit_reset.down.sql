@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS daily_resets;
+
+ALTER TABLE user_stats
+    DROP COLUMN IF EXISTS active_streak,
+    DROP COLUMN IF EXISTS messages_today;
+
+ALTER TABLE chats DROP COLUMN IF EXISTS timezone;
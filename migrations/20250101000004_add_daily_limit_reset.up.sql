@@ -0,0 +1,11 @@
+ALTER TABLE chats ADD COLUMN timezone TEXT NOT NULL DEFAULT 'Europe/Kyiv';
+
+ALTER TABLE user_stats
+    ADD COLUMN messages_today INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN active_streak INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE daily_resets (
+    chat_id BIGINT PRIMARY KEY REFERENCES chats (chat_id) ON DELETE CASCADE,
+    last_reset_date DATE NOT NULL,
+    last_run_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+ALTER TABLE user_stats DROP COLUMN IF EXISTS free_spins;
+
+ALTER TABLE gambles DROP COLUMN IF EXISTS outcome;
@@ -0,0 +1,3 @@
+ALTER TABLE gambles ADD COLUMN outcome TEXT;
+
+ALTER TABLE user_stats ADD COLUMN free_spins INTEGER NOT NULL DEFAULT 0;
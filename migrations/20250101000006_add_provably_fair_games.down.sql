@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS game_seeds;
+
+ALTER TABLE gambles
+    DROP COLUMN IF EXISTS client_seed,
+    DROP COLUMN IF EXISTS server_seed;
@@ -0,0 +1,10 @@
+ALTER TABLE gambles
+    ADD COLUMN server_seed TEXT,
+    ADD COLUMN client_seed TEXT;
+
+-- the committed seed for the next API game of each user; consumed when a game is settled
+CREATE TABLE game_seeds (
+    user_id INTEGER PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    server_seed TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
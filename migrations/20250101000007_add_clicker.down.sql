@@ -0,0 +1 @@
+ALTER TABLE user_stats DROP COLUMN IF EXISTS clicks;
@@ -0,0 +1 @@
+ALTER TABLE user_stats ADD COLUMN clicks BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS chat_cleanup_delays (
+    chat_id BIGINT NOT NULL REFERENCES chats(chat_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    delay_secs INTEGER NOT NULL CHECK (delay_secs >= 0),
+    PRIMARY KEY (chat_id, kind)
+);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS chat_holidays;
+
+ALTER TABLE chats DROP COLUMN IF EXISTS semester_start;
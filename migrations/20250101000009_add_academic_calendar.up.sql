@@ -0,0 +1,12 @@
+ALTER TABLE chats ADD COLUMN semester_start DATE;
+
+CREATE TABLE chat_holidays (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    starts_on DATE NOT NULL,
+    ends_on DATE NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    CHECK (ends_on >= starts_on)
+);
+
+CREATE INDEX chat_holidays_chat_id_idx ON chat_holidays (chat_id);
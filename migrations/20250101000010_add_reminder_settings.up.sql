@@ -0,0 +1,22 @@
+CREATE TABLE reminder_settings (
+    chat_id BIGINT PRIMARY KEY REFERENCES chats (chat_id) ON DELETE CASCADE,
+    offsets INTEGER[] NOT NULL DEFAULT '{3}',
+    muted_class_types TEXT[] NOT NULL DEFAULT '{}',
+    digest_time TIME
+);
+
+CREATE TABLE sent_reminders (
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    entry_id INTEGER NOT NULL REFERENCES timetable_entries (id) ON DELETE CASCADE,
+    class_date DATE NOT NULL,
+    offset_minutes INTEGER NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chat_id, entry_id, class_date, offset_minutes)
+);
+
+CREATE TABLE sent_digests (
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    digest_date DATE NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chat_id, digest_date)
+);
@@ -0,0 +1 @@
+ALTER TABLE chats ADD COLUMN calendar_token TEXT UNIQUE;
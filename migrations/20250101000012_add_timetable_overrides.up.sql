@@ -0,0 +1,12 @@
+CREATE TABLE timetable_overrides (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    entry_id INTEGER NOT NULL REFERENCES timetable_entries (id) ON DELETE CASCADE,
+    -- Date the lesson would normally take place on.
+    class_date DATE NOT NULL,
+    -- Chat-local date and time the lesson is moved to; NULL when it is cancelled.
+    moved_to TIMESTAMP,
+    UNIQUE (entry_id, class_date)
+);
+
+CREATE INDEX timetable_overrides_chat_id_idx ON timetable_overrides (chat_id);
@@ -0,0 +1,6 @@
+CREATE TABLE timetable_sources (
+    chat_id BIGINT PRIMARY KEY REFERENCES chats (chat_id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    group_name TEXT NOT NULL,
+    synced_at TIMESTAMP
+);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS sent_deadline_reminders;
+DROP TABLE IF EXISTS deadline_settings;
+DROP TABLE IF EXISTS deadline_completions;
+DROP TABLE IF EXISTS deadlines;
@@ -0,0 +1,31 @@
+CREATE TABLE deadlines (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    class_name TEXT,
+    -- chat-local, like the rest of the timetable
+    due_at TIMESTAMP NOT NULL,
+    created_by BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_deadlines_chat_id_due_at ON deadlines (chat_id, due_at);
+
+CREATE TABLE deadline_completions (
+    deadline_id INTEGER NOT NULL REFERENCES deadlines (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    completed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (deadline_id, user_id)
+);
+
+CREATE TABLE deadline_settings (
+    chat_id BIGINT PRIMARY KEY REFERENCES chats (chat_id) ON DELETE CASCADE,
+    offsets INTEGER[] NOT NULL DEFAULT '{1440,180}'
+);
+
+CREATE TABLE sent_deadline_reminders (
+    deadline_id INTEGER NOT NULL REFERENCES deadlines (id) ON DELETE CASCADE,
+    offset_minutes INTEGER NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (deadline_id, offset_minutes)
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS quiet_hours;
+DROP TABLE IF EXISTS subscriptions;
@@ -0,0 +1,15 @@
+CREATE TABLE subscriptions (
+    account_id BIGINT NOT NULL,
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, chat_id, kind)
+);
+
+CREATE INDEX idx_subscriptions_chat_id_kind ON subscriptions (chat_id, kind);
+
+CREATE TABLE quiet_hours (
+    account_id BIGINT PRIMARY KEY,
+    starts_at TIME NOT NULL,
+    ends_at TIME NOT NULL
+);
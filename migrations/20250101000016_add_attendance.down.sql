@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS attendance;
+DROP TABLE IF EXISTS lesson_occurrences;
+ALTER TABLE reminder_settings DROP COLUMN IF EXISTS attendance;
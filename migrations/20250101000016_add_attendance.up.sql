@@ -0,0 +1,22 @@
+ALTER TABLE reminder_settings ADD COLUMN attendance BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Lessons that were held with a check-in button. The class name is kept so the
+-- history survives changes to the timetable.
+CREATE TABLE lesson_occurrences (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL REFERENCES chats (chat_id) ON DELETE CASCADE,
+    entry_id INTEGER REFERENCES timetable_entries (id) ON DELETE SET NULL,
+    class_name TEXT NOT NULL,
+    class_date DATE NOT NULL,
+    class_time TIME NOT NULL,
+    UNIQUE (chat_id, entry_id, class_date)
+);
+
+CREATE INDEX idx_lesson_occurrences_chat_id_class_date ON lesson_occurrences (chat_id, class_date);
+
+CREATE TABLE attendance (
+    occurrence_id INTEGER NOT NULL REFERENCES lesson_occurrences (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    checked_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (occurrence_id, user_id)
+);
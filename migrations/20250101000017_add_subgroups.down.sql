@@ -0,0 +1,11 @@
+DELETE FROM timetable_sources WHERE subgroup <> '';
+ALTER TABLE timetable_sources DROP CONSTRAINT timetable_sources_pkey;
+ALTER TABLE timetable_sources DROP COLUMN subgroup;
+ALTER TABLE timetable_sources ADD PRIMARY KEY (chat_id);
+
+DROP TABLE IF EXISTS subgroup_members;
+
+DELETE FROM timetables WHERE name <> '';
+ALTER TABLE timetables DROP CONSTRAINT timetables_chat_id_name_key;
+ALTER TABLE timetables DROP COLUMN name;
+ALTER TABLE timetables ADD CONSTRAINT timetables_chat_id_key UNIQUE (chat_id);
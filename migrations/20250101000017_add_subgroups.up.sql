@@ -0,0 +1,16 @@
+-- The timetable shared by the whole chat keeps an empty name; subgroups get
+-- their own named timetables.
+ALTER TABLE timetables DROP CONSTRAINT timetables_chat_id_key;
+ALTER TABLE timetables ADD COLUMN name TEXT NOT NULL DEFAULT '';
+ALTER TABLE timetables ADD CONSTRAINT timetables_chat_id_name_key UNIQUE (chat_id, name);
+
+CREATE TABLE subgroup_members (
+    user_id INTEGER PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    timetable_id INTEGER NOT NULL REFERENCES timetables (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_subgroup_members_timetable_id ON subgroup_members (timetable_id);
+
+ALTER TABLE timetable_sources DROP CONSTRAINT timetable_sources_pkey;
+ALTER TABLE timetable_sources ADD COLUMN subgroup TEXT NOT NULL DEFAULT '';
+ALTER TABLE timetable_sources ADD PRIMARY KEY (chat_id, subgroup);
@@ -0,0 +1,13 @@
+-- Changes admins make to a queue. Names are resolved through users, the
+-- details keep the positions as they were at the time.
+CREATE TABLE queue_audit_log (
+    id SERIAL PRIMARY KEY,
+    queue_id INTEGER NOT NULL REFERENCES queues (id) ON DELETE CASCADE,
+    actor_id INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    action TEXT NOT NULL,
+    target_id INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    details TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_queue_audit_log_queue_id ON queue_audit_log (queue_id, created_at);
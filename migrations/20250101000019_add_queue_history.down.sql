@@ -0,0 +1,2 @@
+ALTER TABLE queue_audit_log DROP COLUMN IF EXISTS undone_at;
+ALTER TABLE queue_audit_log DROP COLUMN IF EXISTS snapshot;
@@ -0,0 +1,4 @@
+-- The audit log becomes the history of every queue change. The order before
+-- the change is kept so the latest changes can be undone.
+ALTER TABLE queue_audit_log ADD COLUMN snapshot JSONB;
+ALTER TABLE queue_audit_log ADD COLUMN undone_at TIMESTAMP;
@@ -0,0 +1,7 @@
+ALTER TABLE queues DROP COLUMN IF EXISTS is_archived;
+ALTER TABLE queues DROP COLUMN IF EXISTS is_closed;
+ALTER TABLE queues DROP COLUMN IF EXISTS archive_at;
+ALTER TABLE queues DROP COLUMN IF EXISTS entry_id;
+ALTER TABLE queues DROP COLUMN IF EXISTS max_users;
+ALTER TABLE queues DROP COLUMN IF EXISTS closes_at;
+ALTER TABLE queues DROP COLUMN IF EXISTS opens_at;
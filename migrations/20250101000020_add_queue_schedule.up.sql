@@ -0,0 +1,9 @@
+-- Times are chat-local, like deadlines. A queue tied to a lesson is archived
+-- once the lesson is over.
+ALTER TABLE queues ADD COLUMN opens_at TIMESTAMP;
+ALTER TABLE queues ADD COLUMN closes_at TIMESTAMP;
+ALTER TABLE queues ADD COLUMN max_users INTEGER;
+ALTER TABLE queues ADD COLUMN entry_id INTEGER REFERENCES timetable_entries (id) ON DELETE SET NULL;
+ALTER TABLE queues ADD COLUMN archive_at TIMESTAMP;
+ALTER TABLE queues ADD COLUMN is_closed BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE queues ADD COLUMN is_archived BOOLEAN NOT NULL DEFAULT FALSE;
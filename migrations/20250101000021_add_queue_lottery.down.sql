@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_queues_lottery_closes_at;
+ALTER TABLE queues DROP COLUMN IF EXISTS lottery_closes_at;
+ALTER TABLE queues DROP COLUMN IF EXISTS lottery_seed;
@@ -0,0 +1,7 @@
+-- Members who join a lottery queue before `lottery_closes_at` (chat-local) are
+-- ordered by a draw seeded with `lottery_seed`; only its hash is shown until then.
+ALTER TABLE queues ADD COLUMN lottery_seed TEXT;
+ALTER TABLE queues ADD COLUMN lottery_closes_at TIMESTAMP;
+
+CREATE INDEX idx_queues_lottery_closes_at ON queues (lottery_closes_at)
+    WHERE lottery_closes_at IS NOT NULL;
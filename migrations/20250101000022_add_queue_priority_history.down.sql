@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_queues_chat_id_subject;
+DROP TABLE IF EXISTS queue_priority_settings;
+ALTER TABLE queue_users DROP COLUMN IF EXISTS last_defended_at;
+ALTER TABLE queue_users DROP COLUMN IF EXISTS defences;
+ALTER TABLE queue_users DROP COLUMN IF EXISTS weight;
+ALTER TABLE queues DROP COLUMN IF EXISTS subject;
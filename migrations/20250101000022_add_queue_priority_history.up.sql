@@ -0,0 +1,19 @@
+-- Priority queues of a chat with the same subject share their history: members
+-- who defended recently go after those who haven't.
+ALTER TABLE queues ADD COLUMN subject TEXT;
+
+-- What a member was last ordered by, so the queue can explain their place.
+ALTER TABLE queue_users ADD COLUMN weight DOUBLE PRECISION;
+ALTER TABLE queue_users ADD COLUMN defences INTEGER;
+ALTER TABLE queue_users ADD COLUMN last_defended_at TIMESTAMP;
+
+CREATE TABLE queue_priority_settings (
+    chat_id BIGINT PRIMARY KEY REFERENCES chats (chat_id) ON DELETE CASCADE,
+    -- how far back defences count
+    window_days INTEGER NOT NULL DEFAULT 90,
+    -- a defence counts half as much after this many days; 0 keeps full weight
+    half_life_days INTEGER NOT NULL DEFAULT 30
+);
+
+CREATE INDEX idx_queues_chat_id_subject ON queues (chat_id, subject)
+    WHERE subject IS NOT NULL;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS clicker_batches;
@@ -0,0 +1,6 @@
+-- Click batches already credited, so a batch flushed again after Redis failed
+-- to drop it is skipped.
+CREATE TABLE clicker_batches (
+    id TEXT PRIMARY KEY,
+    flushed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,194 @@
+use std::{env, sync::Arc};
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use teloxide::types::UserId;
+
+use crate::{
+    models::user::UserModel, repositories::user_repository::get_user_by_account_id, state::AppState,
+};
+
+use super::error::ApiError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const AUTH_SCHEME: &str = "tma ";
+const DEFAULT_MAX_AGE_SECS: i64 = 24 * 60 * 60;
+
+/// Caller of the API, authenticated with the `initData` string the Telegram
+/// WebApp receives on launch. Clients send it as `Authorization: tma <initData>`.
+pub struct AuthUser(pub UserModel);
+
+#[derive(Debug, PartialEq)]
+pub struct InitData {
+    pub user_id: UserId,
+    pub auth_date: i64,
+}
+
+#[derive(Deserialize)]
+struct WebAppUser {
+    id: u64,
+}
+
+impl FromRequestParts<Arc<AppState>> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let init_data = parts
+            .headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix(AUTH_SCHEME))
+            .ok_or_else(|| ApiError::unauthorized("Missing Telegram init data"))?;
+
+        let bot_token = env::var("TELOXIDE_TOKEN").map_err(|e| anyhow::anyhow!(e))?;
+        let max_age = env::var("WEBAPP_AUTH_MAX_AGE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_AGE_SECS);
+
+        let init_data = validate_init_data(
+            init_data,
+            &bot_token,
+            chrono::Utc::now().timestamp(),
+            max_age,
+        )
+        .map_err(|err| {
+            tracing::debug!("Rejected init data: {:?}", err);
+            ApiError::unauthorized("Invalid Telegram init data")
+        })?;
+
+        let user = get_user_by_account_id(state, init_data.user_id)
+            .await
+            .map_err(|_| ApiError::forbidden("User is not registered in any chat"))?;
+
+        Ok(AuthUser(user))
+    }
+}
+
+/// Checks the `hash` field of WebApp init data as described in
+/// https://core.telegram.org/bots/webapps#validating-data-received-via-the-mini-app
+pub fn validate_init_data(
+    init_data: &str,
+    bot_token: &str,
+    now: i64,
+    max_age: i64,
+) -> anyhow::Result<InitData> {
+    let mut fields: Vec<(String, String)> = serde_urlencoded::from_str(init_data)?;
+
+    let hash_position = fields
+        .iter()
+        .position(|(key, _)| key == "hash")
+        .ok_or_else(|| anyhow::anyhow!("Init data has no hash"))?;
+    let (_, hash) = fields.remove(hash_position);
+    let hash = hex::decode(hash)?;
+
+    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let data_check_string = fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut secret = HmacSha256::new_from_slice(b"WebAppData")?;
+    secret.update(bot_token.as_bytes());
+    let secret = secret.finalize().into_bytes();
+
+    let mut mac = HmacSha256::new_from_slice(&secret)?;
+    mac.update(data_check_string.as_bytes());
+    mac.verify_slice(&hash)
+        .map_err(|_| anyhow::anyhow!("Init data hash mismatch"))?;
+
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Init data has no {}", name))
+    };
+
+    let auth_date: i64 = field("auth_date")?.parse()?;
+    if now - auth_date > max_age {
+        anyhow::bail!("Init data is stale");
+    }
+
+    let user: WebAppUser = serde_json::from_str(field("user")?)?;
+
+    Ok(InitData {
+        user_id: UserId(user.id),
+        auth_date,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOT_TOKEN: &str = "123456:TEST-TOKEN";
+
+    fn sign(fields: &[(&str, &str)]) -> String {
+        let mut sorted = fields.to_vec();
+        sorted.sort_by_key(|(a, _)| *a);
+        let data_check_string = sorted
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut secret = HmacSha256::new_from_slice(b"WebAppData").unwrap();
+        secret.update(BOT_TOKEN.as_bytes());
+        let secret = secret.finalize().into_bytes();
+        let mut mac = HmacSha256::new_from_slice(&secret).unwrap();
+        mac.update(data_check_string.as_bytes());
+        let hash = hex::encode(mac.finalize().into_bytes());
+
+        let mut all = fields.to_vec();
+        all.push(("hash", &hash));
+        serde_urlencoded::to_string(all).unwrap()
+    }
+
+    #[test]
+    fn test_valid_init_data() {
+        let init_data = sign(&[
+            ("auth_date", "1700000000"),
+            ("query_id", "AAH"),
+            ("user", r#"{"id":42,"first_name":"Test"}"#),
+        ]);
+
+        let result = validate_init_data(&init_data, BOT_TOKEN, 1700000100, 3600).unwrap();
+        assert_eq!(
+            result,
+            InitData {
+                user_id: UserId(42),
+                auth_date: 1700000000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_tampered_init_data() {
+        let init_data = sign(&[
+            ("auth_date", "1700000000"),
+            ("user", r#"{"id":42,"first_name":"Test"}"#),
+        ]);
+        let tampered = init_data.replace("42", "43");
+
+        assert!(validate_init_data(&tampered, BOT_TOKEN, 1700000100, 3600).is_err());
+        assert!(validate_init_data(&init_data, "other:token", 1700000100, 3600).is_err());
+    }
+
+    #[test]
+    fn test_stale_init_data() {
+        let init_data = sign(&[
+            ("auth_date", "1700000000"),
+            ("user", r#"{"id":42,"first_name":"Test"}"#),
+        ]);
+
+        assert!(validate_init_data(&init_data, BOT_TOKEN, 1700003601, 3600).is_err());
+    }
+}
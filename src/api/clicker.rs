@@ -1,20 +1,118 @@
-use axum::Json;
-use serde::Serialize;
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use teloxide::types::ChatId;
+
+use crate::{
+    bot::utils::time::get_local_time,
+    config::clicker::ClickerConfig,
+    redis::RedisCache,
+    repositories::{chat_repository::get_chat_timezone, stats_repository::credit_clicks},
+    state::AppState,
+};
+
+use super::{
+    auth::AuthUser,
+    error::{ApiError, ApiResult},
+};
 
 #[derive(Serialize)]
 pub struct ClickResponse {
     status: String,
-    available: i32,
+    accepted: i64,
+    available: i64,
 }
 
-#[derive(Serialize)]
-pub struct UserId {
-    id: i32,
+#[derive(Deserialize)]
+pub struct ClickRequest {
+    #[serde(default = "default_clicks")]
+    clicks: i64,
 }
 
-pub async fn click() -> Json<ClickResponse> {
-    Json(ClickResponse {
-        available: 100,
-        status: "success".to_string(),
-    })
+fn default_clicks() -> i64 {
+    1
+}
+
+/// Counts clicks in Redis; [`flush_clicks`] later converts them into balance.
+pub async fn click(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Json(request): Json<ClickRequest>,
+) -> ApiResult<ClickResponse> {
+    let config = ClickerConfig::from_env();
+
+    if request.clicks <= 0 || request.clicks > config.max_clicks_per_second {
+        return Err(ApiError::bad_request(format!(
+            "clicks must be between 1 and {}",
+            config.max_clicks_per_second
+        )));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let burst = state
+        .redis
+        .count_click_burst(user.id, now, request.clicks)?;
+    if burst > config.max_clicks_per_second {
+        tracing::debug!("Rejected {} clicks/s from user {}", burst, user.id);
+        return Err(ApiError::too_many_requests("Clicking too fast"));
+    }
+
+    let timezone = get_chat_timezone(&state.db, ChatId(user.chat_id)).await?;
+    let local_date = get_local_time(&timezone).date_naive();
+    let (accepted, clicked_today) = state.redis.add_daily_clicks(
+        user.id,
+        local_date,
+        request.clicks,
+        config.daily_click_cap,
+    )?;
+
+    if accepted > 0 {
+        state.redis.add_pending_clicks(user.id, accepted)?;
+    }
+
+    let status = if accepted < request.clicks {
+        "limit_reached"
+    } else {
+        "success"
+    };
+
+    Ok(Json(ClickResponse {
+        status: status.to_string(),
+        accepted,
+        available: config.daily_click_cap - clicked_today,
+    }))
+}
+
+pub async fn flush_clicks(state: Arc<AppState>) {
+    let (batch_id, pending) = match state.redis.take_pending_clicks() {
+        Ok(batch) => batch,
+        Err(err) => {
+            tracing::error!("Failed to take pending clicks: {:?}", err);
+            return;
+        }
+    };
+    if pending.is_empty() {
+        return;
+    }
+
+    let config = ClickerConfig::from_env();
+    let credited = match credit_clicks(&state.db, &batch_id, &pending, config.clicks_per_coin).await
+    {
+        Ok(credited) => credited,
+        Err(err) => {
+            tracing::error!("Failed to flush clicks: {:?}", err);
+            return;
+        }
+    };
+    if !credited {
+        tracing::warn!("Click batch {} was already credited", batch_id);
+    }
+
+    if let Err(err) = state.redis.clear_flushed_clicks() {
+        tracing::error!("Failed to clear flushed clicks: {:?}", err);
+        return;
+    }
+
+    tracing::debug!("Flushed clicks of {} users", pending.len());
 }
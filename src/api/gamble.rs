@@ -1,37 +1,238 @@
-use axum::Json;
-use serde::Serialize;
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use teloxide::types::UserId;
+
+use crate::{
+    models::{
+        gamble::{FairGambleDto, GambleType},
+        user::UserModel,
+    },
+    repositories::{
+        gamble_repository::{get_or_create_server_seed, settle_fair_gamble, SettleError},
+        stats_repository::get_user_stats,
+    },
+    state::AppState,
+};
+
+use super::{
+    auth::AuthUser,
+    error::{ApiError, ApiResult},
+    games::{
+        fairness::{generate_seed, hash_seed, FairRng, Fairness},
+        roulette::{self, Colour, RouletteBet},
+        slots,
+    },
+};
 
 #[derive(Serialize)]
-pub struct GambleResult {
+pub struct GambleResult<T> {
     id: i32,
     gamble_type: String,
     bet: i32,
     change: i32,
     is_win: bool,
+    balance: i32,
+    details: T,
+    fairness: Fairness,
 }
 
 #[derive(Serialize)]
-pub struct Slot {}
+pub struct Slot {
+    reels: Vec<String>,
+    multiplier: u32,
+}
 
 #[derive(Serialize)]
-pub struct Roulette {}
-
-pub async fn slots() -> Json<GambleResult> {
-    Json(GambleResult {
-        id: 1,
-        gamble_type: "slots".to_string(),
-        bet: 10,
-        change: 20,
-        is_win: true,
-    })
-}
-
-pub async fn roulette() -> Json<GambleResult> {
-    Json(GambleResult {
-        id: 1,
-        gamble_type: "routette".to_string(),
-        bet: 10,
-        change: 20,
-        is_win: true,
-    })
+pub struct Roulette {
+    number: u8,
+    colour: Colour,
+    multiplier: u32,
+}
+
+#[derive(Serialize)]
+pub struct SeedCommitment {
+    server_seed_hash: String,
+}
+
+#[derive(Deserialize)]
+pub struct SlotsRequest {
+    bet: i32,
+    client_seed: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RouletteRequest {
+    bet: i32,
+    client_seed: Option<String>,
+    bet_type: RouletteBet,
+}
+
+/// Hash of the server seed the next game will use, so players can note it before betting.
+pub async fn game_seed(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+) -> ApiResult<SeedCommitment> {
+    let server_seed = get_or_create_server_seed(&state.db, user.id, &generate_seed()).await?;
+
+    Ok(Json(SeedCommitment {
+        server_seed_hash: hash_seed(&server_seed),
+    }))
+}
+
+pub async fn slots(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Json(request): Json<SlotsRequest>,
+) -> ApiResult<GambleResult<Slot>> {
+    play(
+        &state,
+        &user,
+        request.bet,
+        request.client_seed,
+        GambleType::Slots,
+        slots::MAX_MULTIPLIER,
+        |rng| {
+            let spin = slots::spin(rng);
+            (
+                spin.multiplier,
+                Slot {
+                    reels: spin.symbols.iter().map(|s| s.to_string()).collect(),
+                    multiplier: spin.multiplier,
+                },
+            )
+        },
+    )
+    .await
+}
+
+pub async fn roulette(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Json(request): Json<RouletteRequest>,
+) -> ApiResult<GambleResult<Roulette>> {
+    match request.bet_type {
+        RouletteBet::Number(number) if number > roulette::MAX_NUMBER => {
+            return Err(ApiError::bad_request("Number must be between 0 and 36"));
+        }
+        RouletteBet::Colour(Colour::Green) => {
+            return Err(ApiError::bad_request("Bet on zero with a number bet"));
+        }
+        _ => {}
+    }
+
+    play(
+        &state,
+        &user,
+        request.bet,
+        request.client_seed,
+        GambleType::Roulette,
+        roulette::MAX_MULTIPLIER,
+        |rng| {
+            let number = roulette::spin(rng);
+            let multiplier = roulette::payout_multiplier(request.bet_type, number);
+            (
+                multiplier,
+                Roulette {
+                    number,
+                    colour: roulette::colour_of(number),
+                    multiplier,
+                },
+            )
+        },
+    )
+    .await
+}
+
+/// What a bet paid back `multiplier` times adds to the balance.
+fn payout_change(bet: i32, multiplier: u32) -> Result<i32, ApiError> {
+    let change = bet as i64 * multiplier as i64 - bet as i64;
+    i32::try_from(change).map_err(|_| ApiError::bad_request("Bet is too large"))
+}
+
+/// Draws the outcome from the user's committed seed, settles it and reveals the seed.
+/// Bets whose best payout would not fit the balance are refused before the draw,
+/// so a refusal says nothing about the outcome.
+async fn play<T: Serialize>(
+    state: &AppState,
+    user: &UserModel,
+    bet: i32,
+    client_seed: Option<String>,
+    gamble_type: GambleType,
+    max_multiplier: u32,
+    draw: impl FnOnce(&mut FairRng) -> (u32, T),
+) -> ApiResult<GambleResult<T>> {
+    if bet <= 0 {
+        return Err(ApiError::bad_request("Bet must be positive"));
+    }
+
+    let user_stats = get_user_stats(&state.db, UserId(user.account_id as u64)).await?;
+    if user_stats.balance < bet {
+        return Err(ApiError::bad_request("Недостатньо коштів"));
+    }
+    let best_change = payout_change(bet, max_multiplier)?;
+    if user_stats.balance.checked_add(best_change).is_none() {
+        return Err(ApiError::bad_request("Bet is too large"));
+    }
+
+    let client_seed = client_seed.unwrap_or_else(generate_seed);
+    let server_seed = get_or_create_server_seed(&state.db, user.id, &generate_seed()).await?;
+
+    let mut rng = FairRng::new(&server_seed, &client_seed);
+    let (multiplier, details) = draw(&mut rng);
+    let change = payout_change(bet, multiplier)?;
+
+    let (gamble, balance) = settle_fair_gamble(
+        &state.db,
+        FairGambleDto {
+            user_id: user.id,
+            bet,
+            change,
+            is_win: change > 0,
+            gamble_type,
+            outcome: serde_json::to_string(&details).map_err(anyhow::Error::from)?,
+            server_seed: server_seed.clone(),
+            client_seed: client_seed.clone(),
+        },
+    )
+    .await
+    .map_err(|err| match err.downcast_ref::<SettleError>() {
+        Some(SettleError::InsufficientFunds) => ApiError::bad_request("Недостатньо коштів"),
+        Some(SettleError::SeedAlreadyUsed) => {
+            ApiError::conflict("Server seed was already used, request a new one")
+        }
+        Some(SettleError::FreeSpinUsed) | None => ApiError::from(err),
+    })?;
+
+    let next_server_seed = get_or_create_server_seed(&state.db, user.id, &generate_seed()).await?;
+
+    Ok(Json(GambleResult {
+        id: gamble.id,
+        gamble_type: gamble.gamble_type,
+        bet: gamble.bet,
+        change: gamble.change,
+        is_win: gamble.is_win,
+        balance,
+        details,
+        fairness: Fairness {
+            server_seed_hash: hash_seed(&server_seed),
+            server_seed,
+            client_seed,
+            next_server_seed_hash: hash_seed(&next_server_seed),
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payout_change() {
+        assert_eq!(payout_change(10, 36).ok(), Some(350));
+        assert_eq!(payout_change(10, 0).ok(), Some(-10));
+        assert_eq!(payout_change(70_000_000, 36).ok(), None);
+        assert_eq!(payout_change(i32::MAX, 1).ok(), Some(0));
+    }
 }
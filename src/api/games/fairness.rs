@@ -0,0 +1,98 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Everything a player needs to recompute a game result after it was played:
+/// `sha256(server_seed)` must equal the hash handed out before the game, and
+/// replaying [`FairRng`] with both seeds must yield the same outcome.
+#[derive(Serialize, Debug, Clone)]
+pub struct Fairness {
+    pub server_seed: String,
+    pub server_seed_hash: String,
+    pub client_seed: String,
+    pub next_server_seed_hash: String,
+}
+
+pub fn generate_seed() -> String {
+    let mut seed = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut seed);
+    hex::encode(seed)
+}
+
+pub fn hash_seed(seed: &str) -> String {
+    hex::encode(Sha256::digest(seed.as_bytes()))
+}
+
+/// Deterministic number stream: block `n` is `HMAC-SHA256(server_seed, "{client_seed}:{n}")`,
+/// consumed four big-endian bytes at a time.
+pub struct FairRng {
+    mac: HmacSha256,
+    client_seed: String,
+    block: u32,
+    buffer: Vec<u8>,
+}
+
+impl FairRng {
+    pub fn new(server_seed: &str, client_seed: &str) -> Self {
+        Self {
+            mac: HmacSha256::new_from_slice(server_seed.as_bytes())
+                .expect("HMAC accepts keys of any length"),
+            client_seed: client_seed.to_string(),
+            block: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.buffer.len() < 4 {
+            let mut mac = self.mac.clone();
+            mac.update(format!("{}:{}", self.client_seed, self.block).as_bytes());
+            self.buffer = mac.finalize().into_bytes().to_vec();
+            self.block += 1;
+        }
+        let bytes: Vec<u8> = self.buffer.drain(..4).collect();
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    /// Uniform number in `0..bound`, rejecting the biased tail of the `u32` range.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        let zone = u32::MAX - u32::MAX % bound;
+        loop {
+            let value = self.next_u32();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seeds_give_same_stream() {
+        let mut a = FairRng::new("server", "client");
+        let mut b = FairRng::new("server", "client");
+        let mut c = FairRng::new("server", "other");
+
+        let a_values: Vec<u32> = (0..20).map(|_| a.below(37)).collect();
+        let b_values: Vec<u32> = (0..20).map(|_| b.below(37)).collect();
+        let c_values: Vec<u32> = (0..20).map(|_| c.below(37)).collect();
+
+        assert_eq!(a_values, b_values);
+        assert_ne!(a_values, c_values);
+        assert!(a_values.iter().all(|value| *value < 37));
+    }
+
+    #[test]
+    fn test_hash_seed() {
+        assert_eq!(
+            hash_seed("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
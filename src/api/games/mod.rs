@@ -0,0 +1,3 @@
+pub mod fairness;
+pub mod roulette;
+pub mod slots;
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use super::fairness::FairRng;
+
+const RED_NUMBERS: [u8; 18] = [
+    1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36,
+];
+pub const MAX_NUMBER: u8 = 36;
+/// Paid for a straight bet on the number that comes up.
+pub const MAX_MULTIPLIER: u32 = 36;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Colour {
+    Red,
+    Black,
+    Green,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+/// A single-zero (European) roulette bet, e.g. `{"kind": "number", "value": 17}`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum RouletteBet {
+    Number(u8),
+    Colour(Colour),
+    Parity(Parity),
+}
+
+pub fn colour_of(number: u8) -> Colour {
+    if number == 0 {
+        Colour::Green
+    } else if RED_NUMBERS.contains(&number) {
+        Colour::Red
+    } else {
+        Colour::Black
+    }
+}
+
+pub fn spin(rng: &mut FairRng) -> u8 {
+    rng.below(MAX_NUMBER as u32 + 1) as u8
+}
+
+/// How many times the stake is paid back; zero loses every outside bet.
+pub fn payout_multiplier(bet: RouletteBet, number: u8) -> u32 {
+    match bet {
+        RouletteBet::Number(chosen) if chosen == number => MAX_MULTIPLIER,
+        RouletteBet::Colour(colour) if colour != Colour::Green && colour == colour_of(number) => 2,
+        RouletteBet::Parity(Parity::Even) if number != 0 && number.is_multiple_of(2) => 2,
+        RouletteBet::Parity(Parity::Odd) if !number.is_multiple_of(2) => 2,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payouts() {
+        assert_eq!(payout_multiplier(RouletteBet::Number(17), 17), 36);
+        assert_eq!(payout_multiplier(RouletteBet::Number(17), 18), 0);
+        assert_eq!(payout_multiplier(RouletteBet::Colour(Colour::Red), 1), 2);
+        assert_eq!(payout_multiplier(RouletteBet::Colour(Colour::Black), 1), 0);
+        assert_eq!(payout_multiplier(RouletteBet::Colour(Colour::Green), 0), 0);
+        assert_eq!(payout_multiplier(RouletteBet::Parity(Parity::Even), 0), 0);
+        assert_eq!(payout_multiplier(RouletteBet::Parity(Parity::Even), 2), 2);
+        assert_eq!(payout_multiplier(RouletteBet::Parity(Parity::Odd), 3), 2);
+    }
+
+    #[test]
+    fn test_bet_format() {
+        let bet: RouletteBet = serde_json::from_str(r#"{"kind":"colour","value":"red"}"#).unwrap();
+        assert_eq!(bet, RouletteBet::Colour(Colour::Red));
+        let bet: RouletteBet = serde_json::from_str(r#"{"kind":"number","value":0}"#).unwrap();
+        assert_eq!(bet, RouletteBet::Number(0));
+    }
+}
@@ -0,0 +1,80 @@
+use super::fairness::FairRng;
+
+/// Every reel uses the same strip; repeated symbols are more likely to stop on the line.
+pub const REEL: &[&str] = &[
+    "🍒", "🍒", "🍒", "🍒", "🍋", "🍋", "🍋", "🍊", "🍊", "🍊", "🔔", "🔔", "⭐", "⭐", "7️⃣", "💎",
+];
+pub const REEL_COUNT: usize = 3;
+/// The best line of [`PAYTABLE`].
+pub const MAX_MULTIPLIER: u32 = 200;
+
+/// `(symbol, how many on the line, payout multiplier)`; the best matching line pays.
+pub const PAYTABLE: &[(&str, usize, u32)] = &[
+    ("💎", 3, 200),
+    ("7️⃣", 3, 100),
+    ("⭐", 3, 40),
+    ("🔔", 3, 30),
+    ("🍊", 3, 15),
+    ("🍋", 3, 12),
+    ("🍒", 3, 10),
+    ("🍒", 2, 3),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotSpin {
+    pub symbols: Vec<&'static str>,
+    pub multiplier: u32,
+}
+
+pub fn spin(rng: &mut FairRng) -> SlotSpin {
+    let symbols: Vec<&'static str> = (0..REEL_COUNT)
+        .map(|_| REEL[rng.below(REEL.len() as u32) as usize])
+        .collect();
+    let multiplier = payout_multiplier(&symbols);
+    SlotSpin {
+        symbols,
+        multiplier,
+    }
+}
+
+pub fn payout_multiplier(symbols: &[&str]) -> u32 {
+    PAYTABLE
+        .iter()
+        .filter(|(symbol, count, _)| symbols.iter().filter(|s| *s == symbol).count() >= *count)
+        .map(|(_, _, multiplier)| *multiplier)
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paytable() {
+        assert_eq!(payout_multiplier(&["💎", "💎", "💎"]), 200);
+        assert_eq!(payout_multiplier(&["🍒", "🍒", "🍒"]), 10);
+        assert_eq!(payout_multiplier(&["🍒", "🔔", "🍒"]), 3);
+        assert_eq!(payout_multiplier(&["🍒", "🔔", "🍋"]), 0);
+        assert_eq!(
+            PAYTABLE.iter().map(|(_, _, multiplier)| *multiplier).max(),
+            Some(MAX_MULTIPLIER)
+        );
+    }
+
+    #[test]
+    fn test_return_to_player_is_below_one() {
+        let mut total = 0u64;
+        let mut combinations = 0u64;
+        for a in REEL {
+            for b in REEL {
+                for c in REEL {
+                    total += payout_multiplier(&[a, b, c]) as u64;
+                    combinations += 1;
+                }
+            }
+        }
+        let rtp = total as f64 / combinations as f64;
+        assert!((0.9..1.0).contains(&rtp), "RTP {} is out of range", rtp);
+    }
+}
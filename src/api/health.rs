@@ -1,12 +1,18 @@
-use axum::Json;
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+
+use crate::state::{AppState, EventStatsSnapshot};
 
 #[derive(serde::Serialize)]
 pub struct Health {
     status: String,
+    events: EventStatsSnapshot,
 }
 
-pub async fn check_health() -> Json<Health> {
+pub async fn check_health(State(state): State<Arc<AppState>>) -> Json<Health> {
     Json(Health {
         status: "ok".to_string(),
+        events: state.events.stats.snapshot(),
     })
 }
@@ -3,22 +3,33 @@ use clicker::*;
 use gamble::*;
 use health::*;
 use stats::*;
+use timetable::*;
 
 use crate::state::State;
 
+pub mod auth;
 pub mod clicker;
+pub mod error;
 pub mod gamble;
+pub mod games;
 pub mod health;
 pub mod stats;
+pub mod timetable;
 
 pub async fn start(state: State) {
     tracing::info!("Starting API server");
     let app = Router::new()
         .route("/", axum::routing::get(check_health))
-        .route("/slots", axum::routing::get(slots))
-        .route("/routette", axum::routing::get(roulette))
+        .route("/slots", axum::routing::post(slots))
+        .route("/roulette", axum::routing::post(roulette))
+        // misspelled path kept for clients that still call it
+        .route("/routette", axum::routing::post(roulette))
+        .route("/games/seed", axum::routing::get(game_seed))
         .route("/stats", axum::routing::get(stats))
-        .route("/clicker", axum::routing::get(click))
+        .route("/users/{id}/stats", axum::routing::get(user_stats))
+        .route("/users/{id}/gambles", axum::routing::get(gamble_history))
+        .route("/clicker", axum::routing::post(click))
+        .route("/timetable/{file_name}", axum::routing::get(timetable_feed))
         .with_state(state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
@@ -1,5 +1,25 @@
-use axum::Json;
-use serde::Serialize;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use teloxide::types::{ChatId, UserId};
+
+use crate::{
+    models::stats::{FullStats, GambleModel},
+    repositories::{
+        gamble_repository::get_gambles_by_account_id,
+        stats_repository::{get_full_me, get_group_stats},
+    },
+    state::AppState,
+};
+
+use super::error::{ApiError, ApiResult};
+
+const DEFAULT_LIMIT: i32 = 20;
+const MAX_LIMIT: i32 = 100;
 
 #[derive(Serialize)]
 pub struct User {
@@ -10,28 +30,107 @@ pub struct User {
 
 #[derive(Serialize)]
 pub struct Stats {
+    group_name: String,
+    total: i32,
+    pagination: Pagination,
     users: Vec<User>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct Pagination {
+    #[serde(default)]
     offset: i32,
+    #[serde(default = "default_limit")]
     limit: i32,
 }
 
-pub async fn stats() -> Json<Stats> {
-    Json(Stats {
-        users: vec![
-            User {
-                id: 1,
-                username: "user1".to_string(),
-                balance: 100,
-            },
-            User {
-                id: 2,
-                username: "user2".to_string(),
-                balance: 200,
-            },
-        ],
-    })
+fn default_limit() -> i32 {
+    DEFAULT_LIMIT
+}
+
+impl Pagination {
+    fn validate(self) -> Result<Self, ApiError> {
+        if self.offset < 0 || self.limit <= 0 {
+            return Err(ApiError::bad_request(
+                "offset must be non-negative and limit must be positive",
+            ));
+        }
+        Ok(Self {
+            offset: self.offset,
+            limit: self.limit.min(MAX_LIMIT),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ChatQuery {
+    chat_id: i64,
+}
+
+#[derive(Serialize)]
+pub struct GambleHistory {
+    pagination: Pagination,
+    gambles: Vec<GambleModel>,
+}
+
+pub async fn stats(
+    State(state): State<Arc<AppState>>,
+    Query(chat): Query<ChatQuery>,
+    Query(pagination): Query<Pagination>,
+) -> ApiResult<Stats> {
+    let pagination = pagination.validate()?;
+    let group_stats = get_group_stats(&state.db, ChatId(chat.chat_id))
+        .await?
+        .ok_or_else(|| ApiError::not_found("Chat not found"))?;
+
+    let total = group_stats.stats.len() as i32;
+    let users = group_stats
+        .stats
+        .into_iter()
+        .skip(pagination.offset as usize)
+        .take(pagination.limit as usize)
+        .map(|stat| User {
+            id: stat.user_id,
+            username: stat.username,
+            balance: stat.balance,
+        })
+        .collect();
+
+    Ok(Json(Stats {
+        group_name: group_stats.group_name,
+        total,
+        pagination,
+        users,
+    }))
+}
+
+pub async fn user_stats(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<u64>,
+) -> ApiResult<FullStats> {
+    let stats = get_full_me(&state.db, UserId(account_id))
+        .await?
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    Ok(Json(stats))
+}
+
+pub async fn gamble_history(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<u64>,
+    Query(pagination): Query<Pagination>,
+) -> ApiResult<GambleHistory> {
+    let pagination = pagination.validate()?;
+    let gambles = get_gambles_by_account_id(
+        &state.db,
+        UserId(account_id),
+        pagination.offset as i64,
+        pagination.limit as i64,
+    )
+    .await?;
+
+    Ok(Json(GambleHistory {
+        pagination,
+        gambles,
+    }))
 }
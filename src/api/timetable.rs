@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+};
+use teloxide::types::ChatId;
+
+use crate::{
+    bot::timetable::export::render_ics,
+    repositories::{
+        chat_repository::{get_calendar, get_chat_by_calendar_token},
+        timetable_repository::get_full_timetable,
+    },
+    state::AppState,
+};
+
+use super::error::ApiError;
+
+/// Subscribable iCalendar feed of a chat's timetable, e.g. `/timetable/<token>.ics`.
+pub async fn timetable_feed(
+    State(state): State<Arc<AppState>>,
+    Path(file_name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let token = file_name
+        .strip_suffix(".ics")
+        .ok_or_else(|| ApiError::not_found("Calendar not found"))?;
+    let chat = get_chat_by_calendar_token(&state.db, token)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Calendar not found"))?;
+
+    let chat_id = ChatId(chat.chat_id);
+    let entries = get_full_timetable(&state.db, chat_id).await?;
+    let calendar = get_calendar(&state.db, chat_id).await?;
+    let ics = render_ics(&calendar, &entries, &chat.title, chrono::Utc::now());
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        ics,
+    ))
+}
@@ -0,0 +1,86 @@
+use teloxide::{
+    payloads::SendDocumentSetters,
+    prelude::Requester,
+    types::{InputFile, Message},
+    Bot,
+};
+
+use crate::{
+    bot::{
+        handler::HandlerResult,
+        ui,
+        utils::{params::get_param, permissions::is_privileged},
+    },
+    delete_message,
+    models::cleanup::CleanupKind,
+    repositories::{
+        attendance_repository::get_attendance, chat_repository::get_calendar,
+        reminder_repository::set_attendance,
+    },
+    state::State,
+};
+
+use super::attendance_csv;
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg, CleanupKind::Timetable);
+    Ok(())
+}
+
+/// `/attendance` shows everyone's attendance per subject for the semester.
+/// Admins can turn the check-in button on or off and export the table as CSV.
+pub async fn attendance(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+
+    let param = get_param::<String>(&msg).ok();
+    if param.is_some() && !is_privileged(&bot, &msg).await? {
+        let text = "Тільки адміністратори можуть це змінювати";
+        return reply(&bot, &msg, &state, text.to_string()).await;
+    }
+
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    match param.as_deref() {
+        None => {
+            let attendance =
+                get_attendance(&state.db, msg.chat.id, calendar.semester_start).await?;
+            reply(
+                &bot,
+                &msg,
+                &state,
+                ui::attendance_ui::attendance_view(&attendance, calendar.semester_start),
+            )
+            .await
+        }
+        Some("on") | Some("off") => {
+            let enabled = param.as_deref() == Some("on");
+            set_attendance(&state.db, msg.chat.id, enabled).await?;
+            let text = if enabled {
+                "Нагадування про пари матимуть кнопку «Я тут ✋»"
+            } else {
+                "Відмітку присутності вимкнено"
+            };
+            reply(&bot, &msg, &state, text.to_string()).await
+        }
+        Some("csv") => {
+            let attendance =
+                get_attendance(&state.db, msg.chat.id, calendar.semester_start).await?;
+            if attendance.is_empty() {
+                let text = "Відміток присутності ще немає";
+                return reply(&bot, &msg, &state, text.to_string()).await;
+            }
+            let file_name = format!("attendance-{}.csv", calendar.today().format("%Y-%m-%d"));
+            bot.send_document(
+                msg.chat.id,
+                InputFile::memory(attendance_csv(&attendance).into_bytes()).file_name(file_name),
+            )
+            .caption("Відвідування за семестр")
+            .await?;
+            Ok(())
+        }
+        Some(_) => {
+            let usage = "Використання: /attendance [on|off|csv]";
+            reply(&bot, &msg, &state, usage.to_string()).await
+        }
+    }
+}
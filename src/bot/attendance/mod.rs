@@ -0,0 +1,88 @@
+pub mod commands;
+
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardButtonKind, InlineKeyboardMarkup};
+
+use crate::models::attendance::AttendanceModel;
+
+pub fn check_in_callback(occurrence_id: i32) -> String {
+    format!("check-in_{}", occurrence_id)
+}
+
+pub fn check_in_button(occurrence_id: i32, attended: i64) -> InlineKeyboardButton {
+    InlineKeyboardButton::callback(
+        format!("Я тут ✋ · {}", attended),
+        check_in_callback(occurrence_id),
+    )
+}
+
+/// Copy of the reminder's keyboard with the check-in count updated, keeping the
+/// link button as it was.
+pub fn update_check_in_markup(
+    markup: &InlineKeyboardMarkup,
+    occurrence_id: i32,
+    attended: i64,
+) -> InlineKeyboardMarkup {
+    let callback = check_in_callback(occurrence_id);
+    let rows = markup
+        .inline_keyboard
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|button| match &button.kind {
+                    InlineKeyboardButtonKind::CallbackData(data) if *data == callback => {
+                        check_in_button(occurrence_id, attended)
+                    }
+                    _ => button.clone(),
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    InlineKeyboardMarkup::new(rows)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One row per member and subject, for reports to the faculty.
+pub fn attendance_csv(attendance: &[AttendanceModel]) -> String {
+    let mut csv = "name,username,subject,attended,held,percentage\n".to_string();
+    for row in attendance {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{:.1}\n",
+            csv_field(&row.name),
+            csv_field(&row.username),
+            csv_field(&row.class_name),
+            row.attended,
+            row.held,
+            row.percentage()
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attendance_csv_escapes_fields() {
+        let attendance = vec![AttendanceModel {
+            user_id: 1,
+            name: "Петренко, Іван".to_string(),
+            username: "ivan".to_string(),
+            class_name: "Бази \"даних\"".to_string(),
+            held: 3,
+            attended: 2,
+        }];
+
+        assert_eq!(
+            attendance_csv(&attendance),
+            "name,username,subject,attended,held,percentage\n\"Петренко, Іван\",ivan,\"Бази \"\"даних\"\"\",2,3,66.7\n"
+        );
+    }
+}
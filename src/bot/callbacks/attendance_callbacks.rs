@@ -0,0 +1,69 @@
+use teloxide::{
+    payloads::{AnswerCallbackQuerySetters, EditMessageReplyMarkupSetters},
+    prelude::Requester,
+    types::CallbackQuery,
+    Bot,
+};
+
+use crate::{
+    bot::{attendance::update_check_in_markup, handler::HandlerResult},
+    repositories::{
+        attendance_repository::{check_in as insert_check_in, count_attendance, get_occurrence},
+        chat_repository::get_calendar,
+        user_repository::get_user_by_account_id,
+    },
+    state::State,
+};
+
+/// Checks the user in to the lesson. Check-ins close at the end of the day of
+/// the lesson.
+pub async fn check_in(
+    bot: Bot,
+    state: State,
+    occurrence_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    let message = query.message.as_ref().unwrap();
+    let chat_id = message.chat().id;
+    let occurrence = get_occurrence(&state.db, occurrence_id)
+        .await?
+        .filter(|occurrence| occurrence.chat_id == chat_id.0);
+    let Some(occurrence) = occurrence else {
+        bot.answer_callback_query(query.id.clone()).await?;
+        return Ok(());
+    };
+
+    let calendar = get_calendar(&state.db, chat_id).await?;
+    if calendar.today() > occurrence.class_date {
+        bot.answer_callback_query(query.id.clone())
+            .text("Відмітку для цієї пари вже закрито")
+            .await?;
+        return Ok(());
+    }
+
+    let user = get_user_by_account_id(&state, query.from.id).await?;
+    if !insert_check_in(&state.db, occurrence_id, user.id).await? {
+        bot.answer_callback_query(query.id.clone())
+            .text("Ви вже відмітились")
+            .await?;
+        return Ok(());
+    }
+    bot.answer_callback_query(query.id.clone())
+        .text(format!(
+            "Присутність на «{}» відмічено ✋",
+            occurrence.class_name
+        ))
+        .await?;
+
+    let markup = message
+        .regular_message()
+        .and_then(|message| message.reply_markup());
+    if let Some(markup) = markup {
+        let attended = count_attendance(&state.db, occurrence_id).await?;
+        bot.edit_message_reply_markup(chat_id, message.id())
+            .reply_markup(update_check_in_markup(markup, occurrence_id, attended))
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use teloxide::{
+    payloads::{AnswerCallbackQuerySetters, EditMessageReplyMarkupSetters, EditMessageTextSetters},
+    prelude::Requester,
+    types::CallbackQuery,
+    Bot,
+};
+
+use crate::{
+    bot::{
+        deadlines::DeadlineMarkupExt, handler::HandlerResult, ui,
+        utils::reply_markup_builder::ReplyMarkupBuilder,
+    },
+    repositories::{
+        chat_repository::get_calendar,
+        deadline_repository::{
+            count_completions, get_deadline, get_upcoming_deadlines, toggle_completion,
+        },
+        user_repository::get_user_by_account_id,
+    },
+    state::State,
+};
+
+/// Toggles the user's mark and tells them the result. Returns `false` if the
+/// button does not belong to a deadline of this chat.
+async fn toggle(
+    bot: &Bot,
+    state: &State,
+    deadline_id: i32,
+    query: &CallbackQuery,
+) -> anyhow::Result<bool> {
+    let chat_id = query.message.as_ref().unwrap().chat().id;
+    match get_deadline(&state.db, deadline_id).await? {
+        Some(deadline) if deadline.chat_id == chat_id.0 => {}
+        _ => {
+            bot.answer_callback_query(query.id.clone())
+                .text("Дедлайн вже видалено")
+                .await?;
+            return Ok(false);
+        }
+    }
+
+    let user = get_user_by_account_id(state, query.from.id).await?;
+    let text = if toggle_completion(&state.db, deadline_id, user.id).await? {
+        "Позначено як виконане ✅"
+    } else {
+        "Позначку знято"
+    };
+    bot.answer_callback_query(query.id.clone())
+        .text(text)
+        .await?;
+    Ok(true)
+}
+
+pub async fn done_deadline(
+    bot: Bot,
+    state: State,
+    deadline_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    if !toggle(&bot, &state, deadline_id, &query).await? {
+        return Ok(());
+    }
+
+    let message = query.message.as_ref().unwrap();
+    let calendar = get_calendar(&state.db, message.chat().id).await?;
+    let now = calendar.now().naive_local();
+    let deadlines = get_upcoming_deadlines(&state.db, message.chat().id, now).await?;
+    bot.edit_message_text(
+        message.chat().id,
+        message.id(),
+        ui::deadline_ui::deadlines_view(&deadlines, now),
+    )
+    .reply_markup(ReplyMarkupBuilder::deadlines_markup(&deadlines))
+    .await?;
+
+    Ok(())
+}
+
+pub async fn done_deadline_reminder(
+    bot: Bot,
+    state: State,
+    deadline_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    if !toggle(&bot, &state, deadline_id, &query).await? {
+        return Ok(());
+    }
+
+    let message = query.message.as_ref().unwrap();
+    let completed = count_completions(&state.db, deadline_id).await?;
+    bot.edit_message_reply_markup(message.chat().id, message.id())
+        .reply_markup(ReplyMarkupBuilder::deadline_reminder_markup(
+            deadline_id,
+            completed,
+        ))
+        .await?;
+
+    Ok(())
+}
@@ -1,15 +1,21 @@
+use std::sync::Arc;
+
 use teloxide::{
     prelude::Requester,
     types::{CallbackQuery, MessageId, UserId},
     Bot,
 };
 
-use crate::state::State;
+use crate::{config::state::TimetableEdit, redis::dialogue::RedisDialogueStorage, state::State};
 
 use super::handler::HandlerResult;
+use queue_callbacks::MemberAction;
 
+pub mod attendance_callbacks;
+pub mod deadline_callbacks;
 pub mod queue_callbacks;
 pub mod stats_callbacks;
+pub mod timetable_callbacks;
 
 pub enum Callback {
     ShowFullStats(MessageId, UserId),
@@ -21,6 +27,21 @@ pub enum Callback {
     FreezeQueue(i32),
     SkipQueue(i32),
     DoneQueue(i32),
+    UndoQueue(i32),
+    QueueAdmin(i32),
+    CloseQueueAdmin(i32),
+    QueueMember(i32),
+    QueueMemberAction(i32, MemberAction),
+    TimetableEditor,
+    EditEntry(i32),
+    StartTimetableEdit(TimetableEdit),
+    DeleteEntry(i32),
+    ConfirmDeleteEntry(i32),
+    RestoreLesson(i32),
+    CancelTimetableEdit,
+    DoneDeadline(i32),
+    DoneDeadlineReminder(i32),
+    CheckIn(i32),
 }
 
 impl Callback {
@@ -67,12 +88,86 @@ impl Callback {
                 let queue_id = queue_id.parse().ok()?;
                 Some(Callback::DoneQueue(queue_id))
             }
+            ["queue-undo", queue_id] => {
+                let queue_id = queue_id.parse().ok()?;
+                Some(Callback::UndoQueue(queue_id))
+            }
+            ["queue-admin", queue_id] => {
+                let queue_id = queue_id.parse().ok()?;
+                Some(Callback::QueueAdmin(queue_id))
+            }
+            ["queue-admin-close", queue_id] => {
+                let queue_id = queue_id.parse().ok()?;
+                Some(Callback::CloseQueueAdmin(queue_id))
+            }
+            ["queue-member", queue_user_id] => {
+                let queue_user_id = queue_user_id.parse().ok()?;
+                Some(Callback::QueueMember(queue_user_id))
+            }
+            [action @ ("queue-top" | "queue-up" | "queue-down" | "queue-kick"), queue_user_id] => {
+                let queue_user_id = queue_user_id.parse().ok()?;
+                let action = match *action {
+                    "queue-top" => MemberAction::Top,
+                    "queue-up" => MemberAction::Up,
+                    "queue-down" => MemberAction::Down,
+                    _ => MemberAction::Kick,
+                };
+                Some(Callback::QueueMemberAction(queue_user_id, action))
+            }
+            ["timetable-editor"] => Some(Callback::TimetableEditor),
+            ["edit-entry", entry_id] => {
+                let entry_id = entry_id.parse().ok()?;
+                Some(Callback::EditEntry(entry_id))
+            }
+            ["add-entry"] => Some(Callback::StartTimetableEdit(TimetableEdit::AddEntry)),
+            [action @ ("rename-entry" | "retime-entry" | "link-entry" | "cancel-lesson"
+            | "move-lesson"), entry_id] => {
+                let entry_id = entry_id.parse().ok()?;
+                let edit = match *action {
+                    "rename-entry" => TimetableEdit::Rename { entry_id },
+                    "retime-entry" => TimetableEdit::Retime { entry_id },
+                    "link-entry" => TimetableEdit::Link { entry_id },
+                    "cancel-lesson" => TimetableEdit::Cancel { entry_id },
+                    _ => TimetableEdit::Move { entry_id },
+                };
+                Some(Callback::StartTimetableEdit(edit))
+            }
+            ["delete-entry", entry_id] => {
+                let entry_id = entry_id.parse().ok()?;
+                Some(Callback::DeleteEntry(entry_id))
+            }
+            ["confirm-delete-entry", entry_id] => {
+                let entry_id = entry_id.parse().ok()?;
+                Some(Callback::ConfirmDeleteEntry(entry_id))
+            }
+            ["restore-lesson", override_id] => {
+                let override_id = override_id.parse().ok()?;
+                Some(Callback::RestoreLesson(override_id))
+            }
+            ["cancel-timetable-edit"] => Some(Callback::CancelTimetableEdit),
+            ["done-deadline", deadline_id] => {
+                let deadline_id = deadline_id.parse().ok()?;
+                Some(Callback::DoneDeadline(deadline_id))
+            }
+            ["done-deadline-reminder", deadline_id] => {
+                let deadline_id = deadline_id.parse().ok()?;
+                Some(Callback::DoneDeadlineReminder(deadline_id))
+            }
+            ["check-in", occurrence_id] => {
+                let occurrence_id = occurrence_id.parse().ok()?;
+                Some(Callback::CheckIn(occurrence_id))
+            }
             _ => None,
         }
     }
 }
 
-pub async fn handle_callback(bot: Bot, state: State, q: CallbackQuery) -> HandlerResult {
+pub async fn handle_callback(
+    bot: Bot,
+    state: State,
+    storage: Arc<RedisDialogueStorage>,
+    q: CallbackQuery,
+) -> HandlerResult {
     if q.data.is_none() {
         bot.answer_callback_query(q.id).await?;
         return Ok(());
@@ -105,6 +200,51 @@ pub async fn handle_callback(bot: Bot, state: State, q: CallbackQuery) -> Handle
         Some(Callback::DoneQueue(queue_id)) => {
             queue_callbacks::done_queue(bot, state, queue_id, q).await?;
         }
+        Some(Callback::UndoQueue(queue_id)) => {
+            queue_callbacks::undo_queue(bot, state, queue_id, q).await?;
+        }
+        Some(Callback::QueueAdmin(queue_id)) => {
+            queue_callbacks::show_queue_admin(bot, state, queue_id, q).await?;
+        }
+        Some(Callback::CloseQueueAdmin(queue_id)) => {
+            queue_callbacks::close_queue_admin(bot, state, queue_id, q).await?;
+        }
+        Some(Callback::QueueMember(queue_user_id)) => {
+            queue_callbacks::show_queue_member(bot, state, queue_user_id, q).await?;
+        }
+        Some(Callback::QueueMemberAction(queue_user_id, action)) => {
+            queue_callbacks::queue_member_action(bot, state, queue_user_id, action, q).await?;
+        }
+        Some(Callback::TimetableEditor) => {
+            timetable_callbacks::show_editor(bot, state, q).await?;
+        }
+        Some(Callback::EditEntry(entry_id)) => {
+            timetable_callbacks::show_entry(bot, state, entry_id, q).await?;
+        }
+        Some(Callback::StartTimetableEdit(edit)) => {
+            timetable_callbacks::start_edit(bot, state, storage, edit, q).await?;
+        }
+        Some(Callback::DeleteEntry(entry_id)) => {
+            timetable_callbacks::delete_entry_prompt(bot, entry_id, q).await?;
+        }
+        Some(Callback::ConfirmDeleteEntry(entry_id)) => {
+            timetable_callbacks::confirm_delete_entry(bot, state, entry_id, q).await?;
+        }
+        Some(Callback::RestoreLesson(override_id)) => {
+            timetable_callbacks::restore_lesson(bot, state, override_id, q).await?;
+        }
+        Some(Callback::CancelTimetableEdit) => {
+            timetable_callbacks::cancel_edit(bot, storage, q).await?;
+        }
+        Some(Callback::DoneDeadline(deadline_id)) => {
+            deadline_callbacks::done_deadline(bot, state, deadline_id, q).await?;
+        }
+        Some(Callback::DoneDeadlineReminder(deadline_id)) => {
+            deadline_callbacks::done_deadline_reminder(bot, state, deadline_id, q).await?;
+        }
+        Some(Callback::CheckIn(occurrence_id)) => {
+            attendance_callbacks::check_in(bot, state, occurrence_id, q).await?;
+        }
         None => {
             bot.answer_callback_query(q.id).await?;
         }
@@ -1,22 +1,45 @@
 use teloxide::{
     dispatching::dialogue::GetChatId,
-    payloads::SendMessageSetters,
+    payloads::{AnswerCallbackQuerySetters, EditMessageReplyMarkupSetters, SendMessageSetters},
     prelude::Requester,
     types::{CallbackQuery, ChatId, MessageId, ReplyParameters},
     Bot,
 };
 
 use crate::{
-    bot::{handler::HandlerResult, queues::QueueMessages, ui},
+    bot::{
+        handler::HandlerResult,
+        queues::{
+            first_in_queue, notify_next, queue_markup, schedule::join_rejection, QueueMarkupExt,
+            QueueMessages, UNDO_WINDOW_MINUTES,
+        },
+        subscriptions::notify_subscriber,
+        ui,
+        utils::{permissions::is_user_privileged, reply_markup_builder::ReplyMarkupBuilder},
+    },
     delete_message,
+    models::{queue::QueueAction, subscription::SubscriptionKind},
     repositories::{
         self,
-        queue_repository::{add_user_to_queue, get_queue_by_id, get_users},
+        chat_repository::get_calendar,
+        queue_repository::{
+            add_user_to_queue, get_queue_by_id, get_queue_member, get_undoable_event, get_users,
+            kick_queue_user, move_queue_user, order_by_priority, set_queue_message,
+            swap_queue_users, undo_queue_event,
+        },
         user_repository::get_user_by_account_id,
     },
     state::State,
 };
 
+/// What the admin panel can do with a queue member.
+pub enum MemberAction {
+    Top,
+    Up,
+    Down,
+    Kick,
+}
+
 pub async fn join_queue(
     bot: Bot,
     state: State,
@@ -25,11 +48,22 @@ pub async fn join_queue(
 ) -> HandlerResult {
     let stored_user = get_user_by_account_id(&state, query.from.id).await?;
 
+    let queue = get_queue_by_id(&state.db, queue_id).await?;
+    let users = get_users(&state.db, queue_id).await?;
+    let calendar = get_calendar(&state.db, ChatId(queue.chat_id)).await?;
+    if let Some(text) = join_rejection(&queue, users.len(), calendar.now().naive_local()) {
+        bot.answer_callback_query(query.id).text(text).await?;
+        return Ok(());
+    }
+
     if let Err(err) = add_user_to_queue(&state.db, queue_id, stored_user.id, None).await {
         tracing::error!("Failed to join queue: {:?}", err);
         bot.answer_callback_query(query.id).await?;
         return Ok(());
     };
+    if queue.is_priority {
+        order_by_priority(&state.db, queue_id).await?;
+    }
 
     let queue = get_queue_by_id(&state.db, queue_id).await?;
     let users = get_users(&state.db, queue_id).await?;
@@ -48,12 +82,14 @@ pub async fn leave_queue(
     query: CallbackQuery,
 ) -> HandlerResult {
     let stored_user = get_user_by_account_id(&state, query.from.id).await?;
+    let previous_first = first_in_queue(&state, queue_id).await?;
     repositories::queue_repository::remove_user_from_queue(&state.db, queue_id, stored_user.id)
         .await?;
 
     let queue = get_queue_by_id(&state.db, queue_id).await?;
     let users = get_users(&state.db, queue_id).await?;
 
+    notify_next(&state, &queue, previous_first, &users).await?;
     bot.edit_queue(queue, users).await;
 
     Ok(())
@@ -74,12 +110,19 @@ pub async fn delete_queue(
     }
 
     let queue = get_queue_by_id(&state.db, queue_id).await?;
+    let actor = get_user_by_account_id(&state, query.from.id).await?;
 
-    repositories::queue_repository::delete_queue(&state.db, queue.id).await?;
+    repositories::queue_repository::delete_queue(&state.db, queue.id, actor.id).await?;
 
     bot.delete_message(ChatId(queue.chat_id), MessageId(queue.message_id))
         .await?;
 
+    let new_msg = bot
+        .send_message(ChatId(queue.chat_id), ui::queue_ui::deleted(&queue))
+        .reply_markup(ReplyMarkupBuilder::deleted_queue_markup(queue.id))
+        .await?;
+    delete_message!(state, new_msg);
+
     Ok(())
 }
 
@@ -105,6 +148,15 @@ pub async fn notify_queue(
 
     delete_message!(state, new_msg);
 
+    notify_subscriber(
+        &state,
+        ChatId(queue.chat_id),
+        SubscriptionKind::Queue,
+        users[0].account_id,
+        &ui::subscription_ui::queue_notification(&queue),
+    )
+    .await?;
+
     Ok(())
 }
 
@@ -122,12 +174,23 @@ pub async fn shuffle_queue(
         return Ok(());
     }
 
+    let queue = get_queue_by_id(&state.db, queue_id).await?;
+    if queue.lottery_closes_at.is_some() {
+        bot.answer_callback_query(query.id)
+            .text("Чергу перемішає жеребкування")
+            .await?;
+        return Ok(());
+    }
+
     tracing::debug!("Shuffling queue with id: {}", queue_id);
-    repositories::queue_repository::shuffle_queue(&state.db, queue_id).await?;
+    let actor = get_user_by_account_id(&state, query.from.id).await?;
+    let previous_first = first_in_queue(&state, queue_id).await?;
+    repositories::queue_repository::shuffle_queue(&state.db, queue_id, actor.id).await?;
 
     let queue = get_queue_by_id(&state.db, queue_id).await?;
     let users = get_users(&state.db, queue_id).await?;
 
+    notify_next(&state, &queue, previous_first, &users).await?;
     bot.edit_queue(queue, users).await;
 
     Ok(())
@@ -144,11 +207,13 @@ pub async fn freeze_queue(
     let user_id = query.from.id;
     let user_who_clicked = get_user_by_account_id(&state, user_id).await?;
 
+    let previous_first = first_in_queue(&state, queue_id).await?;
     repositories::queue_repository::freeze_user(&state.db, queue_id, user_who_clicked.id).await?;
 
     let queue = get_queue_by_id(&state.db, queue_id).await?;
     let users = get_users(&state.db, queue_id).await?;
 
+    notify_next(&state, &queue, previous_first, &users).await?;
     bot.edit_queue(queue, users).await;
 
     Ok(())
@@ -165,6 +230,7 @@ pub async fn skip_queue(
     let user_id = query.from.id;
     let user_who_clicked = get_user_by_account_id(&state, user_id).await?;
 
+    let previous_first = first_in_queue(&state, queue_id).await?;
     repositories::queue_repository::skip_priority_queue(
         &state.db,
         queue_id,
@@ -172,10 +238,12 @@ pub async fn skip_queue(
         false,
     )
     .await?;
+    order_by_priority(&state.db, queue_id).await?;
 
     let queue = get_queue_by_id(&state.db, queue_id).await?;
     let users = get_users(&state.db, queue_id).await?;
 
+    notify_next(&state, &queue, previous_first, &users).await?;
     bot.edit_queue(queue, users).await;
 
     Ok(())
@@ -192,6 +260,7 @@ pub async fn done_queue(
     let user_id = query.from.id;
     let user_who_clicked = get_user_by_account_id(&state, user_id).await?;
 
+    let previous_first = first_in_queue(&state, queue_id).await?;
     repositories::queue_repository::skip_priority_queue(
         &state.db,
         queue_id,
@@ -199,10 +268,221 @@ pub async fn done_queue(
         true,
     )
     .await?;
+    order_by_priority(&state.db, queue_id).await?;
+
+    let queue = get_queue_by_id(&state.db, queue_id).await?;
+    let users = get_users(&state.db, queue_id).await?;
+
+    notify_next(&state, &queue, previous_first, &users).await?;
+    bot.edit_queue(queue, users).await;
+
+    Ok(())
+}
+
+/// Answers the press of a non-admin and returns `false`.
+async fn check_privileged(bot: &Bot, query: &CallbackQuery) -> anyhow::Result<bool> {
+    let chat_member = bot
+        .get_chat_member(query.chat_id().unwrap(), query.from.id)
+        .await?;
+    if !chat_member.is_privileged() {
+        bot.answer_callback_query(query.id.clone())
+            .text("Тільки адміністратори можуть керувати чергою")
+            .await?;
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Shows the members of the queue as buttons for an admin to pick one.
+pub async fn show_queue_admin(
+    bot: Bot,
+    state: State,
+    queue_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    if !check_privileged(&bot, &query).await? {
+        return Ok(());
+    }
 
     let queue = get_queue_by_id(&state.db, queue_id).await?;
     let users = get_users(&state.db, queue_id).await?;
+    bot.answer_callback_query(query.id).await?;
+    bot.edit_message_reply_markup(ChatId(queue.chat_id), MessageId(queue.message_id))
+        .reply_markup(ReplyMarkupBuilder::queue_admin_markup(queue.id, &users))
+        .await?;
+
+    Ok(())
+}
 
+pub async fn close_queue_admin(
+    bot: Bot,
+    state: State,
+    queue_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    if !check_privileged(&bot, &query).await? {
+        return Ok(());
+    }
+
+    let queue = get_queue_by_id(&state.db, queue_id).await?;
+    bot.answer_callback_query(query.id).await?;
+    bot.edit_message_reply_markup(ChatId(queue.chat_id), MessageId(queue.message_id))
+        .reply_markup(queue_markup(&queue))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn show_queue_member(
+    bot: Bot,
+    state: State,
+    queue_user_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    if !check_privileged(&bot, &query).await? {
+        return Ok(());
+    }
+
+    let chat_id = query.chat_id().unwrap();
+    let member = get_queue_member(&state.db, queue_user_id).await?;
+    let Some(member) = member else {
+        bot.answer_callback_query(query.id)
+            .text("Цього учасника вже немає в черзі")
+            .await?;
+        return Ok(());
+    };
+    let queue = get_queue_by_id(&state.db, member.queue_id).await?;
+    if queue.chat_id != chat_id.0 {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    bot.answer_callback_query(query.id)
+        .text(format!("{}: місце {}", member.name, member.position))
+        .await?;
+    bot.edit_message_reply_markup(chat_id, MessageId(queue.message_id))
+        .reply_markup(ReplyMarkupBuilder::queue_member_markup(&member))
+        .await?;
+
+    Ok(())
+}
+
+/// Moves or kicks a member from the admin panel. The panel stays open on the
+/// member, or goes back to the list after a kick.
+pub async fn queue_member_action(
+    bot: Bot,
+    state: State,
+    queue_user_id: i32,
+    action: MemberAction,
+    query: CallbackQuery,
+) -> HandlerResult {
+    if !check_privileged(&bot, &query).await? {
+        return Ok(());
+    }
+
+    let chat_id = query.chat_id().unwrap();
+    let member = get_queue_member(&state.db, queue_user_id).await?;
+    let Some(member) = member else {
+        bot.answer_callback_query(query.id)
+            .text("Цього учасника вже немає в черзі")
+            .await?;
+        return Ok(());
+    };
+    let queue = get_queue_by_id(&state.db, member.queue_id).await?;
+    if queue.chat_id != chat_id.0 {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    }
+
+    let actor = get_user_by_account_id(&state, query.from.id).await?;
+    let previous_first = first_in_queue(&state, queue.id).await?;
+    let position = member.position;
+    let changed = match action {
+        MemberAction::Top => move_queue_user(&state.db, queue.id, actor.id, position, 1)
+            .await?
+            .is_some(),
+        MemberAction::Up => {
+            swap_queue_users(&state.db, queue.id, actor.id, position, position - 1).await?
+        }
+        MemberAction::Down => {
+            swap_queue_users(&state.db, queue.id, actor.id, position, position + 1).await?
+        }
+        MemberAction::Kick => kick_queue_user(&state.db, queue.id, actor.id, position)
+            .await?
+            .is_some(),
+    };
+    bot.answer_callback_query(query.id).await?;
+    if !changed {
+        return Ok(());
+    }
+
+    let users = get_users(&state.db, queue.id).await?;
+    let markup = match get_queue_member(&state.db, queue_user_id).await? {
+        Some(member) => ReplyMarkupBuilder::queue_member_markup(&member),
+        None => ReplyMarkupBuilder::queue_admin_markup(queue.id, &users),
+    };
+    notify_next(&state, &queue, previous_first, &users).await?;
+    bot.edit_queue_with_markup(queue, users, markup).await;
+
+    Ok(())
+}
+
+/// Reverts the last change of the queue. Only its author or an admin can undo
+/// it, and only for [`UNDO_WINDOW_MINUTES`].
+pub async fn undo_queue(
+    bot: Bot,
+    state: State,
+    queue_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    let chat_id = query.chat_id().unwrap();
+    let event = get_undoable_event(&state.db, queue_id, UNDO_WINDOW_MINUTES)
+        .await?
+        .filter(|event| event.chat_id == chat_id.0);
+    let Some(event) = event else {
+        bot.answer_callback_query(query.id)
+            .text("Немає змін, які можна скасувати")
+            .await?;
+        return Ok(());
+    };
+
+    let user = get_user_by_account_id(&state, query.from.id).await?;
+    if event.actor_id != Some(user.id) && !is_user_privileged(&bot, chat_id, query.from.id).await? {
+        bot.answer_callback_query(query.id)
+            .text("Скасувати зміну може лише її автор або адміністратор")
+            .await?;
+        return Ok(());
+    }
+
+    let previous_first = first_in_queue(&state, queue_id).await?;
+    if !undo_queue_event(&state.db, &event).await? {
+        bot.answer_callback_query(query.id)
+            .text("Черга вже змінилась, спробуйте ще раз")
+            .await?;
+        return Ok(());
+    }
+    bot.answer_callback_query(query.id.clone())
+        .text(format!(
+            "Скасовано: {}",
+            ui::queue_ui::action_label(event.action)
+        ))
+        .await?;
+
+    let mut queue = get_queue_by_id(&state.db, queue_id).await?;
+    let users = get_users(&state.db, queue_id).await?;
+    if event.action == QueueAction::Delete {
+        // The message of the queue went away with it, so it is sent again.
+        let queue_msg = bot
+            .send_message(chat_id, ui::queue_ui::title(&queue.title))
+            .await?;
+        set_queue_message(&state.db, queue.id, queue_msg.id).await?;
+        queue.message_id = queue_msg.id.0;
+        if let Some(message) = query.message.as_ref() {
+            bot.delete_message(chat_id, message.id()).await?;
+        }
+    } else {
+        notify_next(&state, &queue, previous_first, &users).await?;
+    }
     bot.edit_queue(queue, users).await;
 
     Ok(())
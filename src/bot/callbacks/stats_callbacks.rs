@@ -1,3 +1,4 @@
+use anyhow::Context;
 use teloxide::{
     payloads::EditMessageTextSetters,
     prelude::Requester,
@@ -21,7 +22,9 @@ pub async fn show_full_stats(
         bot.answer_callback_query(query.id).await?;
         return Ok(());
     }
-    let stats = get_full_me(&state.db, user_id).await?;
+    let stats = get_full_me(&state.db, user_id)
+        .await?
+        .context(format!("User stats not found for account_id: {}", user_id))?;
     let res = crate::bot::ui::stats_ui::full_stats(stats);
     let chat_id = query.message.as_ref().unwrap().chat().id;
     bot.edit_message_text(chat_id, message_id, res)
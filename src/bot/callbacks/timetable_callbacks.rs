@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use teloxide::{
+    payloads::{AnswerCallbackQuerySetters, EditMessageTextSetters, SendMessageSetters},
+    prelude::Requester,
+    types::{CallbackQuery, ChatId, MessageId, ParseMode},
+    Bot,
+};
+
+use crate::{
+    bot::{
+        handler::HandlerResult,
+        timetable::editor::{prompt, TimetableMarkupExt},
+        ui,
+        utils::{permissions::is_user_privileged, reply_markup_builder::ReplyMarkupBuilder},
+    },
+    config::state::{BotDialogue, StateMachine, TimetableEdit},
+    delete_message,
+    models::cleanup::CleanupKind,
+    redis::{dialogue::RedisDialogueStorage, RedisCache},
+    repositories::{
+        chat_repository::get_calendar,
+        timetable_repository::{
+            delete_entry, get_chat_entry, get_full_timetable, get_upcoming_entry_overrides,
+            remove_override,
+        },
+    },
+    state::State,
+};
+
+/// Chat and message of the editor the button belongs to, if the user may edit
+/// the timetable. Otherwise the query is answered with an explanation.
+async fn editor_message(
+    bot: &Bot,
+    query: &CallbackQuery,
+) -> anyhow::Result<Option<(ChatId, MessageId)>> {
+    let Some(message) = query.message.as_ref() else {
+        bot.answer_callback_query(query.id.clone()).await?;
+        return Ok(None);
+    };
+    let chat_id = message.chat().id;
+    if !is_user_privileged(bot, chat_id, query.from.id).await? {
+        bot.answer_callback_query(query.id.clone())
+            .text("Тільки адміністратори можуть редагувати розклад")
+            .show_alert(true)
+            .await?;
+        return Ok(None);
+    }
+    Ok(Some((chat_id, message.id())))
+}
+
+async fn render_editor(
+    bot: &Bot,
+    state: &State,
+    chat_id: ChatId,
+    message_id: MessageId,
+) -> anyhow::Result<()> {
+    let entries = get_full_timetable(&state.db, chat_id).await?;
+    bot.edit_message_text(
+        chat_id,
+        message_id,
+        ui::timetable_ui::edit_view(entries.clone()),
+    )
+    .parse_mode(ParseMode::MarkdownV2)
+    .reply_markup(ReplyMarkupBuilder::timetable_editor_markup(&entries))
+    .await?;
+    Ok(())
+}
+
+async fn render_entry(
+    bot: &Bot,
+    state: &State,
+    chat_id: ChatId,
+    message_id: MessageId,
+    entry_id: i32,
+) -> anyhow::Result<()> {
+    let Some(entry) = get_chat_entry(&state.db, chat_id, entry_id).await? else {
+        return render_editor(bot, state, chat_id, message_id).await;
+    };
+    let calendar = get_calendar(&state.db, chat_id).await?;
+    let overrides = get_upcoming_entry_overrides(&state.db, entry_id, calendar.today()).await?;
+    bot.edit_message_text(
+        chat_id,
+        message_id,
+        ui::timetable_ui::entry_editor_view(&entry, &overrides),
+    )
+    .reply_markup(ReplyMarkupBuilder::entry_editor_markup(
+        entry.id, &overrides,
+    ))
+    .await?;
+    Ok(())
+}
+
+pub async fn show_editor(bot: Bot, state: State, query: CallbackQuery) -> HandlerResult {
+    let Some((chat_id, message_id)) = editor_message(&bot, &query).await? else {
+        return Ok(());
+    };
+    render_editor(&bot, &state, chat_id, message_id).await?;
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+pub async fn show_entry(
+    bot: Bot,
+    state: State,
+    entry_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    let Some((chat_id, message_id)) = editor_message(&bot, &query).await? else {
+        return Ok(());
+    };
+    render_entry(&bot, &state, chat_id, message_id, entry_id).await?;
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+/// Asks the user for the details of the edit and waits for their reply.
+pub async fn start_edit(
+    bot: Bot,
+    state: State,
+    storage: Arc<RedisDialogueStorage>,
+    edit: TimetableEdit,
+    query: CallbackQuery,
+) -> HandlerResult {
+    let Some((chat_id, _)) = editor_message(&bot, &query).await? else {
+        return Ok(());
+    };
+
+    BotDialogue::new(storage, chat_id)
+        .update(StateMachine::ReceiveTimetableEdit {
+            edit: edit.clone(),
+            user_id: query.from.id,
+        })
+        .await?;
+
+    let new_msg = bot
+        .send_message(chat_id, prompt(&edit))
+        .reply_markup(ReplyMarkupBuilder::cancel_timetable_edit_markup())
+        .await?;
+    delete_message!(state, new_msg, CleanupKind::Timetable);
+
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+pub async fn cancel_edit(
+    bot: Bot,
+    storage: Arc<RedisDialogueStorage>,
+    query: CallbackQuery,
+) -> HandlerResult {
+    let Some((chat_id, message_id)) = editor_message(&bot, &query).await? else {
+        return Ok(());
+    };
+
+    BotDialogue::new(storage, chat_id).exit().await?;
+    if let Err(err) = bot.delete_message(chat_id, message_id).await {
+        tracing::error!("Failed to delete message: {:?}", err);
+    }
+
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+pub async fn delete_entry_prompt(bot: Bot, entry_id: i32, query: CallbackQuery) -> HandlerResult {
+    let Some((chat_id, message_id)) = editor_message(&bot, &query).await? else {
+        return Ok(());
+    };
+
+    bot.edit_message_text(chat_id, message_id, "Видалити пару з розкладу назавжди?")
+        .reply_markup(ReplyMarkupBuilder::confirm_delete_entry_markup(entry_id))
+        .await?;
+
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+pub async fn confirm_delete_entry(
+    bot: Bot,
+    state: State,
+    entry_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    let Some((chat_id, message_id)) = editor_message(&bot, &query).await? else {
+        return Ok(());
+    };
+
+    if get_chat_entry(&state.db, chat_id, entry_id)
+        .await?
+        .is_some()
+    {
+        delete_entry(&state.db, entry_id).await?;
+        state.redis.clear_timetable_entries(chat_id)?;
+    }
+    render_editor(&bot, &state, chat_id, message_id).await?;
+
+    bot.answer_callback_query(query.id)
+        .text("Пару видалено")
+        .await?;
+    Ok(())
+}
+
+pub async fn restore_lesson(
+    bot: Bot,
+    state: State,
+    override_id: i32,
+    query: CallbackQuery,
+) -> HandlerResult {
+    let Some((chat_id, message_id)) = editor_message(&bot, &query).await? else {
+        return Ok(());
+    };
+
+    let Some(removed) = remove_override(&state.db, chat_id, override_id).await? else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+    render_entry(&bot, &state, chat_id, message_id, removed.entry_id).await?;
+
+    bot.answer_callback_query(query.id)
+        .text(format!(
+            "Пара {} відбудеться за розкладом",
+            removed.class_date.format("%Y-%m-%d")
+        ))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,322 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::Requester,
+    types::{Message, UserId},
+    Bot,
+};
+
+use crate::{
+    bot::{
+        handler::HandlerResult,
+        ui,
+        utils::{permissions::is_privileged, reply_markup_builder::ReplyMarkupBuilder},
+    },
+    delete_message,
+    models::cleanup::CleanupKind,
+    repositories::{
+        chat_repository::get_calendar,
+        deadline_repository::{
+            add_deadline, get_deadline, get_deadline_settings, get_upcoming_deadlines,
+            remove_deadline, set_deadline_offsets,
+        },
+        timetable_repository::get_full_timetable,
+    },
+    state::State,
+};
+
+use super::DeadlineMarkupExt;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const MAX_DEADLINE_OFFSET: i32 = 14 * 1440;
+const MAX_DEADLINE_OFFSETS: usize = 5;
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg, CleanupKind::General);
+    Ok(())
+}
+
+fn command_text(msg: &Message) -> &str {
+    msg.text()
+        .and_then(|text| text.split_once(char::is_whitespace))
+        .map(|(_, rest)| rest.trim())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewDeadline {
+    pub due_at: NaiveDateTime,
+    pub title: String,
+    pub class_name: Option<String>,
+}
+
+/// `<YYYY-MM-DD> [HH:MM] <task> [| <class>]`. Tasks without a time are due at
+/// the end of the day.
+pub fn parse_deadline(text: &str) -> Option<NewDeadline> {
+    let (task, class_name) = match text.split_once('|') {
+        Some((task, class_name)) => (task, Some(class_name.trim().to_string())),
+        None => (text, None),
+    };
+    let mut parts = task.split_whitespace().peekable();
+    let date = NaiveDate::parse_from_str(parts.next()?, DATE_FORMAT).ok()?;
+    let time = match parts
+        .peek()
+        .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M").ok())
+    {
+        Some(time) => {
+            parts.next();
+            time
+        }
+        None => NaiveTime::from_hms_opt(23, 59, 0)?,
+    };
+    let title = parts.collect::<Vec<_>>().join(" ");
+    if title.is_empty() || class_name.as_deref() == Some("") {
+        return None;
+    }
+
+    Some(NewDeadline {
+        due_at: date.and_time(time),
+        title,
+        class_name,
+    })
+}
+
+/// Finds the timetable class the user meant: an exact match, or the only class
+/// whose name contains `query`, ignoring case.
+pub fn match_class_name(class_names: &[String], query: &str) -> Option<String> {
+    let query = query.to_lowercase();
+    if let Some(exact) = class_names.iter().find(|name| name.to_lowercase() == query) {
+        return Some(exact.clone());
+    }
+    let mut matches = class_names
+        .iter()
+        .filter(|name| name.to_lowercase().contains(&query));
+    match (matches.next(), matches.next()) {
+        (Some(name), None) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// Parses reminder offsets like `1d`, `3h` or `30m` into minutes. `off`
+/// disables reminders.
+pub fn parse_deadline_offsets(params: &[&str]) -> Option<Vec<i32>> {
+    if params == ["off"] {
+        return Some(Vec::new());
+    }
+    if params.is_empty() || params.len() > MAX_DEADLINE_OFFSETS {
+        return None;
+    }
+    let mut offsets = params
+        .iter()
+        .map(|param| {
+            let (value, multiplier) = match param.char_indices().last()? {
+                (index, 'd') => (&param[..index], 1440),
+                (index, 'h') => (&param[..index], 60),
+                (index, 'm') => (&param[..index], 1),
+                _ => (*param, 1),
+            };
+            value.parse::<i32>().ok()?.checked_mul(multiplier)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if offsets
+        .iter()
+        .any(|offset| !(1..=MAX_DEADLINE_OFFSET).contains(offset))
+    {
+        return None;
+    }
+    offsets.sort_unstable_by(|a, b| b.cmp(a));
+    offsets.dedup();
+    Some(offsets)
+}
+
+pub async fn deadline(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::General);
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+
+    let Some(new_deadline) = parse_deadline(command_text(&msg)) else {
+        let usage = "Використання: /deadline <РРРР-ММ-ДД> [ГГ:ХХ] <завдання> [| <пара>], наприклад:\n/deadline 2025-10-20 23:59 Лабораторна 3 | Бази даних";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    if new_deadline.due_at <= calendar.now().naive_local() {
+        let text = "Дедлайн має бути в майбутньому";
+        return reply(&bot, &msg, &state, text.to_string()).await;
+    }
+
+    let class_name = match &new_deadline.class_name {
+        Some(query) => {
+            let mut class_names: Vec<String> = get_full_timetable(&state.db, msg.chat.id)
+                .await?
+                .into_iter()
+                .map(|entry| entry.class_name)
+                .collect();
+            class_names.sort();
+            class_names.dedup();
+            match match_class_name(&class_names, query) {
+                Some(class_name) => Some(class_name),
+                None => {
+                    let text = format!("Пару \"{}\" не знайдено в розкладі", query);
+                    return reply(&bot, &msg, &state, text).await;
+                }
+            }
+        }
+        None => None,
+    };
+
+    let deadline = add_deadline(
+        &state.db,
+        msg.chat.id,
+        &new_deadline.title,
+        class_name.as_deref(),
+        new_deadline.due_at,
+        user.id,
+    )
+    .await?;
+
+    reply(
+        &bot,
+        &msg,
+        &state,
+        ui::deadline_ui::deadline_added_view(&deadline),
+    )
+    .await
+}
+
+pub async fn deadlines(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::General);
+
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    let now = calendar.now().naive_local();
+    let deadlines = get_upcoming_deadlines(&state.db, msg.chat.id, now).await?;
+
+    let new_msg = bot
+        .send_message(
+            msg.chat.id,
+            ui::deadline_ui::deadlines_view(&deadlines, now),
+        )
+        .reply_markup(ReplyMarkupBuilder::deadlines_markup(&deadlines))
+        .await?;
+    delete_message!(state, new_msg, CleanupKind::General);
+
+    Ok(())
+}
+
+/// Removes a deadline. Only its author and chat admins may do so.
+pub async fn remove(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::General);
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+
+    let Ok(deadline_id) = command_text(&msg).trim_start_matches('#').parse::<i32>() else {
+        let usage = "Використання: /remove_deadline <номер>";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let deadline = get_deadline(&state.db, deadline_id)
+        .await?
+        .filter(|deadline| deadline.chat_id == msg.chat.id.0);
+    let Some(deadline) = deadline else {
+        let text = format!("Дедлайн #{} не знайдено", deadline_id);
+        return reply(&bot, &msg, &state, text).await;
+    };
+
+    if UserId(deadline.created_by as u64) != user.id && !is_privileged(&bot, &msg).await? {
+        let text = "Видалити дедлайн може лише його автор або адміністратор";
+        return reply(&bot, &msg, &state, text.to_string()).await;
+    }
+
+    remove_deadline(&state.db, msg.chat.id, deadline_id).await?;
+    let text = format!("Дедлайн #{} видалено", deadline_id);
+    reply(&bot, &msg, &state, text).await
+}
+
+pub async fn reminders(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::General);
+
+    let params: Vec<&str> = command_text(&msg).split_whitespace().collect();
+    if !params.is_empty() {
+        if !is_privileged(&bot, &msg).await? {
+            let text = "Тільки адміністратори можуть це змінювати";
+            return reply(&bot, &msg, &state, text.to_string()).await;
+        }
+        let Some(offsets) = parse_deadline_offsets(&params) else {
+            let usage = format!(
+                "Використання: /deadline_reminders <1d|3h|30m> ... (до {} значень, не більше 14 днів) або /deadline_reminders off",
+                MAX_DEADLINE_OFFSETS
+            );
+            return reply(&bot, &msg, &state, usage).await;
+        };
+        set_deadline_offsets(&state.db, msg.chat.id, &offsets).await?;
+    }
+
+    let settings = get_deadline_settings(&state.db, msg.chat.id).await?;
+    reply(
+        &bot,
+        &msg,
+        &state,
+        ui::deadline_ui::deadline_settings_view(&settings),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deadline() {
+        let deadline = parse_deadline("2025-10-20 Лабораторна 3 | бази").unwrap();
+        assert_eq!(
+            deadline.due_at,
+            NaiveDate::from_ymd_opt(2025, 10, 20)
+                .unwrap()
+                .and_hms_opt(23, 59, 0)
+                .unwrap()
+        );
+        assert_eq!(deadline.title, "Лабораторна 3");
+        assert_eq!(deadline.class_name.as_deref(), Some("бази"));
+
+        let deadline = parse_deadline("2025-10-20 08:30 Есе").unwrap();
+        assert_eq!(deadline.due_at.format("%H:%M").to_string(), "08:30");
+        assert_eq!(deadline.class_name, None);
+
+        assert!(parse_deadline("20.10 Есе").is_none());
+        assert!(parse_deadline("2025-10-20 08:30").is_none());
+        assert!(parse_deadline("2025-10-20 Есе |").is_none());
+    }
+
+    #[test]
+    fn test_match_class_name() {
+        let names = vec![
+            "Бази даних".to_string(),
+            "Бази знань".to_string(),
+            "Фізика".to_string(),
+        ];
+        assert_eq!(
+            match_class_name(&names, "фізика"),
+            Some("Фізика".to_string())
+        );
+        assert_eq!(
+            match_class_name(&names, "даних"),
+            Some("Бази даних".to_string())
+        );
+        assert_eq!(match_class_name(&names, "бази"), None);
+    }
+
+    #[test]
+    fn test_parse_deadline_offsets() {
+        assert_eq!(
+            parse_deadline_offsets(&["3h", "1d", "30m", "90"]),
+            Some(vec![1440, 180, 90, 30])
+        );
+        assert_eq!(parse_deadline_offsets(&["off"]), Some(Vec::new()));
+        assert!(parse_deadline_offsets(&["15d"]).is_none());
+        assert!(parse_deadline_offsets(&["0"]).is_none());
+        assert!(parse_deadline_offsets(&["soon"]).is_none());
+    }
+}
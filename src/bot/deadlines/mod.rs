@@ -0,0 +1,36 @@
+pub mod commands;
+pub mod reminders;
+
+use teloxide::types::InlineKeyboardMarkup;
+
+use crate::models::deadline::DeadlineModel;
+
+use super::utils::reply_markup_builder::ReplyMarkupBuilder;
+
+const BUTTON_TITLE_LENGTH: usize = 24;
+
+pub trait DeadlineMarkupExt {
+    fn deadlines_markup(deadlines: &[(DeadlineModel, i64)]) -> InlineKeyboardMarkup;
+    fn deadline_reminder_markup(deadline_id: i32, completed: i64) -> InlineKeyboardMarkup;
+}
+
+impl DeadlineMarkupExt for ReplyMarkupBuilder {
+    fn deadlines_markup(deadlines: &[(DeadlineModel, i64)]) -> InlineKeyboardMarkup {
+        let mut markup = ReplyMarkupBuilder::new();
+        for (deadline, completed) in deadlines {
+            let title: String = deadline.title.chars().take(BUTTON_TITLE_LENGTH).collect();
+            let label = format!("✅ #{} {} · {}", deadline.id, title, completed);
+            markup = markup.single_button(&label, format!("done-deadline_{}", deadline.id));
+        }
+        markup.build()
+    }
+
+    fn deadline_reminder_markup(deadline_id: i32, completed: i64) -> InlineKeyboardMarkup {
+        ReplyMarkupBuilder::new()
+            .single_button(
+                &format!("Виконано ✅ · {}", completed),
+                format!("done-deadline-reminder_{}", deadline_id),
+            )
+            .build()
+    }
+}
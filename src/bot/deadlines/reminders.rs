@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDateTime, Timelike};
+use teloxide::types::ChatId;
+
+use crate::{
+    models::deadline::{DeadlineModel, DeadlineSettingsModel},
+    repositories::{
+        chat_repository::get_calendar,
+        deadline_repository::{
+            claim_deadline_reminder, get_deadline_settings, get_pending_deadlines, prune_deadlines,
+        },
+    },
+    state::{Event, State},
+};
+
+/// A reminder missed by up to this many minutes (e.g. a skipped cron tick) is still sent.
+const CATCH_UP_MINUTES: i64 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DueDeadlineReminder {
+    pub deadline_id: i32,
+    pub offset_minutes: i32,
+}
+
+/// Reminders whose time has come at `now` (chat-local). Reminders that would
+/// have fired before the deadline was added are not sent.
+pub fn due_deadline_reminders(
+    now: NaiveDateTime,
+    deadlines: &[DeadlineModel],
+    settings: &DeadlineSettingsModel,
+) -> Vec<DueDeadlineReminder> {
+    let mut due = Vec::new();
+    for deadline in deadlines {
+        for offset in settings.offsets.iter() {
+            let remind_at = deadline.due_at - Duration::minutes(*offset as i64);
+            if remind_at <= now
+                && now < deadline.due_at
+                && now < remind_at + Duration::minutes(CATCH_UP_MINUTES)
+            {
+                due.push(DueDeadlineReminder {
+                    deadline_id: deadline.id,
+                    offset_minutes: *offset,
+                });
+            }
+        }
+    }
+    due
+}
+
+pub async fn deadline_reminders(state: State) {
+    let deadlines = match get_pending_deadlines(&state.db).await {
+        Ok(deadlines) => deadlines,
+        Err(err) => {
+            tracing::error!("Failed to get pending deadlines: {:?}", err);
+            return;
+        }
+    };
+
+    let mut by_chat: BTreeMap<i64, Vec<DeadlineModel>> = BTreeMap::new();
+    for deadline in deadlines {
+        by_chat.entry(deadline.chat_id).or_default().push(deadline);
+    }
+    for (chat_id, deadlines) in by_chat {
+        if let Err(err) = remind_chat(&state, ChatId(chat_id), &deadlines).await {
+            tracing::error!(
+                "Failed to send deadline reminders to chat {}: {:?}",
+                chat_id,
+                err
+            );
+        }
+    }
+}
+
+async fn remind_chat(
+    state: &State,
+    chat_id: ChatId,
+    deadlines: &[DeadlineModel],
+) -> anyhow::Result<()> {
+    let calendar = get_calendar(&state.db, chat_id).await?;
+    let settings = get_deadline_settings(&state.db, chat_id).await?;
+
+    let now = calendar.now().naive_local();
+    let now = now.with_second(0).unwrap_or(now);
+
+    for reminder in due_deadline_reminders(now, deadlines, &settings) {
+        let claimed =
+            claim_deadline_reminder(&state.db, reminder.deadline_id, reminder.offset_minutes)
+                .await?;
+        if claimed {
+            state.events.send(Event::DeadlineReminder {
+                chat_id,
+                deadline_id: reminder.deadline_id,
+                offset_minutes: reminder.offset_minutes,
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn prune_past_deadlines(state: State) {
+    if let Err(err) = prune_deadlines(&state.db).await {
+        tracing::error!("Failed to prune past deadlines: {:?}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn deadline(id: i32, due_at: NaiveDateTime) -> DeadlineModel {
+        DeadlineModel {
+            id,
+            chat_id: 1,
+            title: "Лабораторна 3".to_string(),
+            class_name: None,
+            due_at,
+            created_by: 1,
+            created_at: at(1, 12, 0),
+        }
+    }
+
+    #[test]
+    fn test_due_deadline_reminders() {
+        let settings = DeadlineSettingsModel::default_for(1);
+        let deadlines = vec![deadline(1, at(20, 23, 59)), deadline(2, at(21, 2, 59))];
+
+        // A day before the first deadline.
+        assert_eq!(
+            due_deadline_reminders(at(19, 23, 59), &deadlines, &settings),
+            vec![DueDeadlineReminder {
+                deadline_id: 1,
+                offset_minutes: 1440,
+            }]
+        );
+        // Three hours before the second one, which is due after midnight.
+        assert_eq!(
+            due_deadline_reminders(at(20, 23, 59), &deadlines, &settings),
+            vec![DueDeadlineReminder {
+                deadline_id: 2,
+                offset_minutes: 180,
+            }]
+        );
+        // Missed by more than the catch-up window.
+        assert!(due_deadline_reminders(at(20, 0, 5), &deadlines, &settings).is_empty());
+        // Past deadlines get no reminders.
+        assert!(due_deadline_reminders(at(22, 0, 0), &deadlines, &settings).is_empty());
+    }
+}
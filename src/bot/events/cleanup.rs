@@ -2,32 +2,74 @@ use std::{env, sync::Arc, time::Duration};
 
 use teloxide::{prelude::Requester, Bot};
 
-use crate::state::Event;
+use crate::{
+    redis::RedisCache,
+    repositories::chat_repository::get_cleanup_delay,
+    state::{Event, State},
+};
 
-pub async fn delete_message(bot: Arc<Bot>, event: Event) -> anyhow::Result<()> {
+const DEFAULT_CLEANUP_INTERVAL: i64 = 60;
+const WORKER_TICK: Duration = Duration::from_secs(1);
+const DELETIONS_PER_TICK: isize = 50;
+
+/// Queues the message for deletion after the chat's delay for its kind.
+pub async fn delete_message(state: State, event: Event) -> anyhow::Result<()> {
     let Event::DeleteMessage {
         chat_id,
         message_id,
+        kind,
     } = event
     else {
         return Ok(());
     };
-    tokio::spawn(async move {
-        let interval = env::var("MESSAGE_CLEANUP_INTERVAL")
-            .unwrap_or_else(|_| {
-                tracing::warn!("Environment variable MESSAGE_CLEANUP_INTERVAL is not set, using default value of 60 seconds");
-                "60".to_string()
-            })
-            .parse::<u64>()
-            .unwrap_or_else(|_| {
-                tracing::warn!("Environment variable MESSAGE_CLEANUP_INTERVAL is not a number, using default value of 60 seconds");
-                60
-            });
-        tokio::time::sleep(Duration::from_secs(interval)).await;
-        bot.delete_message(chat_id, message_id).await.map_err(|e| {
-            tracing::error!("Failed to delete message: {:?}", e);
-        })
-    });
+
+    let delay = match get_cleanup_delay(&state.db, chat_id, kind).await {
+        Ok(Some(delay)) => delay as i64,
+        Ok(None) => default_cleanup_interval(),
+        Err(err) => {
+            tracing::error!("Failed to get cleanup delay: {:?}", err);
+            default_cleanup_interval()
+        }
+    };
+
+    let due_at = chrono::Utc::now().timestamp() + delay;
+    state.redis.schedule_deletion(chat_id, message_id, due_at)?;
 
     Ok(())
 }
+
+fn default_cleanup_interval() -> i64 {
+    env::var("MESSAGE_CLEANUP_INTERVAL")
+        .unwrap_or_else(|_| {
+            tracing::warn!("Environment variable MESSAGE_CLEANUP_INTERVAL is not set, using default value of {} seconds", DEFAULT_CLEANUP_INTERVAL);
+            DEFAULT_CLEANUP_INTERVAL.to_string()
+        })
+        .parse::<i64>()
+        .unwrap_or_else(|_| {
+            tracing::warn!("Environment variable MESSAGE_CLEANUP_INTERVAL is not a number, using default value of {} seconds", DEFAULT_CLEANUP_INTERVAL);
+            DEFAULT_CLEANUP_INTERVAL
+        })
+}
+
+/// Deletes messages as they become due, including those left over from before a restart.
+pub async fn deletion_worker(bot: Arc<Bot>, state: State) {
+    let mut interval = tokio::time::interval(WORKER_TICK);
+    loop {
+        interval.tick().await;
+
+        let now = chrono::Utc::now().timestamp();
+        let due = match state.redis.take_due_deletions(now, DELETIONS_PER_TICK) {
+            Ok(due) => due,
+            Err(err) => {
+                tracing::error!("Failed to take due deletions: {:?}", err);
+                continue;
+            }
+        };
+
+        for (chat_id, message_id) in due {
+            if let Err(err) = bot.delete_message(chat_id, message_id).await {
+                tracing::error!("Failed to delete message: {:?}", err);
+            }
+        }
+    }
+}
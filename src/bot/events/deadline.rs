@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use teloxide::{payloads::SendMessageSetters, prelude::Requester, Bot};
+
+use crate::{
+    bot::{
+        deadlines::DeadlineMarkupExt, events::Delivered, subscriptions::notify_subscribers, ui,
+        utils::reply_markup_builder::ReplyMarkupBuilder,
+    },
+    models::subscription::SubscriptionKind,
+    repositories::deadline_repository::{count_completions, get_deadline},
+    state::{Event, State},
+};
+
+/// Reminder about an upcoming deadline; it stays in the chat so the button can
+/// be used until the task is due.
+pub async fn remind(bot: Arc<Bot>, state: State, event: Event) -> anyhow::Result<()> {
+    let Event::DeadlineReminder {
+        chat_id,
+        deadline_id,
+        offset_minutes,
+    } = event
+    else {
+        return Ok(());
+    };
+    let Some(deadline) = get_deadline(&state.db, deadline_id).await? else {
+        return Ok(());
+    };
+    let completed = count_completions(&state.db, deadline_id).await?;
+    let text = ui::deadline_ui::deadline_reminder_view(&deadline, offset_minutes);
+
+    bot.send_message(chat_id, text.clone())
+        .reply_markup(ReplyMarkupBuilder::deadline_reminder_markup(
+            deadline_id,
+            completed,
+        ))
+        .await?;
+
+    notify_subscribers(&state, chat_id, SubscriptionKind::Deadlines, &text)
+        .await
+        .context(Delivered)?;
+
+    Ok(())
+}
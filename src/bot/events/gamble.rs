@@ -9,6 +9,7 @@ use teloxide::{
 
 use crate::{
     bot::ui,
+    models::gamble::GambleType,
     repositories,
     state::{Event, State},
 };
@@ -24,7 +25,9 @@ pub async fn show_gamble_result(bot: Arc<Bot>, state: State, event: Event) -> an
     }
     let gamble = gamble.unwrap();
 
-    let content = if gamble.is_win {
+    let content = if GambleType::from(gamble.gamble_type.as_str()) == GambleType::Wheel {
+        ui::stats_ui::wheel_result(&gamble)
+    } else if gamble.is_win {
         ui::stats_ui::generate_win_message(gamble.bet, gamble.bet + gamble.change)
     } else {
         ui::stats_ui::generate_lose_message(gamble.bet, gamble.bet + gamble.change)
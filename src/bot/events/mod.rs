@@ -1,31 +1,129 @@
-use std::sync::Arc;
+use std::{fmt, sync::Arc, time::Duration};
 
-use crate::state::{Event, State};
+use crate::{
+    redis::RedisCache,
+    state::{Event, State},
+};
+use serde::Serialize;
 use teloxide::Bot;
 
 pub mod cleanup;
+pub mod deadline;
 pub mod gamble;
 pub mod notification;
+pub mod private;
+pub mod queue;
+
+const MAX_ATTEMPTS: u32 = 4;
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+struct DeadLetter<'a> {
+    event: &'a Event,
+    error: String,
+    attempts: u32,
+    failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Context for errors raised after a handler has posted to the chat. Running the
+/// handler again would post twice, so such events are not retried.
+#[derive(Debug)]
+pub struct Delivered;
+
+impl fmt::Display for Delivered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed after the message was delivered")
+    }
+}
 
 pub async fn event_loop(bot: Bot, state: State) -> anyhow::Result<()> {
     let bot = Arc::new(bot);
-    let mut receiver = state.sender.subscribe();
+    let Some(mut receiver) = state.events.take_receiver() else {
+        anyhow::bail!("Event loop is already running");
+    };
     tracing::info!("Starting event loop");
-    while let Ok(event) = receiver.recv().await {
-        let bot = bot.clone();
-        let state = state.clone();
-        match event {
-            Event::Exit => break,
-            Event::DeleteMessage { .. } => {
-                cleanup::delete_message(bot, event).await?;
-            }
-            Event::NotifyTimetable { .. } => {
-                notification::notify(bot, state, event).await?;
+    tokio::spawn(cleanup::deletion_worker(bot.clone(), state.clone()));
+    while let Some(event) = receiver.recv().await {
+        if let Event::Exit = event {
+            state.events.stats.record_handled();
+            break;
+        }
+        tokio::spawn(handle_with_retry(bot.clone(), state.clone(), event));
+    }
+    Ok(())
+}
+
+/// Runs the handler of the event, retrying with exponential backoff. Events that
+/// keep failing, or fail after [`Delivered`], are pushed to the dead-letter list
+/// in Redis for inspection.
+async fn handle_with_retry(bot: Arc<Bot>, state: State, event: Event) {
+    let mut attempt = 1;
+    loop {
+        let result = handle(bot.clone(), state.clone(), event.clone()).await;
+        let err = match result {
+            Ok(()) => {
+                state.events.stats.record_handled();
+                return;
             }
-            Event::GambleResult { .. } => {
-                gamble::show_gamble_result(bot, state, event).await?;
+            Err(err) => err,
+        };
+
+        if attempt >= MAX_ATTEMPTS || err.downcast_ref::<Delivered>().is_some() {
+            tracing::error!(
+                "Event {:?} failed on attempt {}, moving it to dead letters: {:?}",
+                event,
+                attempt,
+                err
+            );
+            state.events.stats.record_dead_letter();
+            let dead_letter = DeadLetter {
+                event: &event,
+                error: format!("{:?}", err),
+                attempts: attempt,
+                failed_at: chrono::Utc::now(),
+            };
+            let stored = serde_json::to_string(&dead_letter)
+                .map_err(anyhow::Error::from)
+                .and_then(|entry| state.redis.push_dead_letter(&entry));
+            if let Err(err) = stored {
+                tracing::error!("Failed to store dead letter: {:?}", err);
             }
+            return;
         }
+
+        let backoff = BASE_BACKOFF * 2u32.pow(attempt - 1);
+        tracing::warn!(
+            "Event {:?} failed (attempt {}), retrying in {:?}: {:?}",
+            event,
+            attempt,
+            backoff,
+            err
+        );
+        state.events.stats.record_retry();
+        tokio::time::sleep(backoff).await;
+        attempt += 1;
+    }
+}
+
+async fn handle(bot: Arc<Bot>, state: State, event: Event) -> anyhow::Result<()> {
+    match event {
+        Event::DeleteMessage { .. } => cleanup::delete_message(state, event).await,
+        Event::NotifyTimetable { .. } => notification::notify(bot, state, event).await,
+        Event::TimetableDigest { .. } => notification::digest(bot, state, event).await,
+        Event::TimetableChanged { .. } => notification::timetable_changed(bot, event).await,
+        Event::DeadlineReminder { .. } => deadline::remind(bot, state, event).await,
+        Event::PrivateNotification { .. } => private::send(bot, state, event).await,
+        Event::GambleResult { .. } => gamble::show_gamble_result(bot, state, event).await,
+        Event::RefreshQueue { .. } => queue::refresh(bot, state, event).await,
+        Event::Exit => Ok(()),
+    }
+}
+
+pub async fn report_event_stats(state: State) {
+    let stats = state.events.stats.snapshot();
+    if stats.lag > 0 || stats.dead_lettered > 0 || stats.dropped > 0 {
+        tracing::warn!("Event bus: {:?}", stats);
+    } else {
+        tracing::info!("Event bus: {:?}", stats);
     }
-    Ok(())
 }
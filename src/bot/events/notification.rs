@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use anyhow::Context;
 use reqwest::Url;
 use teloxide::{
     payloads::SendMessageSetters,
@@ -9,22 +10,66 @@ use teloxide::{
 };
 
 use crate::{
-    bot::ui,
+    bot::{
+        attendance::check_in_button,
+        events::Delivered,
+        subscriptions::{notify_subscriber, notify_subscribers},
+        ui,
+    },
     delete_message,
-    repositories::timetable_repository::get_entry_by_id,
+    models::{
+        cleanup::CleanupKind, subscription::SubscriptionKind, timetable::TimetableEntryModel,
+    },
+    repositories::{
+        attendance_repository::{count_attendance, record_occurrence},
+        reminder_repository::get_reminder_settings,
+        subgroup_repository::get_subgroup_members,
+        timetable_repository::{get_entry_by_id, get_today_timetable},
+    },
     state::{Event, State},
 };
 
 pub async fn notify(bot: Arc<Bot>, state: State, event: Event) -> anyhow::Result<()> {
-    let Event::NotifyTimetable { chat_id, entry_id } = event else {
+    let Event::NotifyTimetable {
+        chat_id,
+        entry_id,
+        class_date,
+        class_time,
+    } = event
+    else {
         return Ok(());
     };
-    let entry = get_entry_by_id(&state.db, entry_id).await?;
+    let entry = get_entry_by_id(&state.db, entry_id)
+        .await?
+        .map(|entry| TimetableEntryModel {
+            class_time,
+            ..entry
+        });
     let res = ui::timetable_ui::entry_view(entry.clone());
 
     let bot_username = bot.get_me().await?.user.username.unwrap();
 
     if let Some(entry) = entry {
+        let private_text = ui::subscription_ui::lesson_notification(&entry);
+        // Lessons of a subgroup concern only its members.
+        let members = if entry.subgroup.is_empty() {
+            None
+        } else {
+            Some(get_subgroup_members(&state.db, entry.timetable_id).await?)
+        };
+        let res = match &members {
+            Some(members) if !members.is_empty() => {
+                format!("{}{}", res, ui::timetable_ui::subgroup_mentions(members))
+            }
+            _ => res,
+        };
+        let check_in = if get_reminder_settings(&state.db, chat_id).await?.attendance {
+            let occurrence = record_occurrence(&state.db, chat_id, &entry, class_date).await?;
+            let attended = count_attendance(&state.db, occurrence.id).await?;
+            Some(check_in_button(occurrence.id, attended))
+        } else {
+            None
+        };
         let (inline_text, inline_link) = entry.link.map_or(
             (
                 "Додати посилання 🔗",
@@ -36,22 +81,84 @@ pub async fn notify(bot: Arc<Bot>, state: State, event: Event) -> anyhow::Result
             |link| ("Туда нам нада 🌐", link),
         );
 
+        let mut keyboard = vec![vec![InlineKeyboardButton::url(
+            inline_text,
+            Url::parse(&inline_link).unwrap(),
+        )]];
+        if let Some(check_in) = check_in {
+            keyboard.push(vec![check_in]);
+        }
+
         let new_msg = bot
             .send_message(chat_id, res)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-            .reply_markup(InlineKeyboardMarkup::new(vec![vec![
-                InlineKeyboardButton::url(inline_text, Url::parse(&inline_link).unwrap()),
-            ]]))
+            .reply_markup(InlineKeyboardMarkup::new(keyboard))
             .await?;
 
-        delete_message!(state, new_msg);
+        async {
+            delete_message!(state, new_msg, CleanupKind::Timetable);
+
+            match members {
+                Some(members) => {
+                    for member in members {
+                        notify_subscriber(
+                            &state,
+                            chat_id,
+                            SubscriptionKind::Lessons,
+                            member.account_id,
+                            &private_text,
+                        )
+                        .await?;
+                    }
+                }
+                None => {
+                    notify_subscribers(&state, chat_id, SubscriptionKind::Lessons, &private_text)
+                        .await?
+                }
+            }
+            anyhow::Ok(())
+        }
+        .await
+        .context(Delivered)?;
     } else {
         let new_msg = bot
             .send_message(chat_id, res)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
             .await?;
-        delete_message!(state, new_msg);
+        async {
+            delete_message!(state, new_msg, CleanupKind::Timetable);
+            anyhow::Ok(())
+        }
+        .await
+        .context(Delivered)?;
     }
 
     Ok(())
 }
+
+/// Morning overview of the day's lessons; it stays in the chat for the whole day.
+pub async fn digest(bot: Arc<Bot>, state: State, event: Event) -> anyhow::Result<()> {
+    let Event::TimetableDigest { chat_id } = event else {
+        return Ok(());
+    };
+    let entries = get_today_timetable(&state.db, chat_id).await?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    bot.send_message(chat_id, ui::timetable_ui::day_view(entries))
+        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
+
+/// Announces the changes found by the timetable re-sync; it stays in the chat.
+pub async fn timetable_changed(bot: Arc<Bot>, event: Event) -> anyhow::Result<()> {
+    let Event::TimetableChanged { chat_id, summary } = event else {
+        return Ok(());
+    };
+    bot.send_message(chat_id, summary).await?;
+
+    Ok(())
+}
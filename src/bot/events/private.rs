@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use teloxide::{payloads::SendMessageSetters, prelude::Requester, ApiError, Bot, RequestError};
+
+use crate::{
+    repositories::subscription_repository::unsubscribe,
+    state::{Event, State},
+};
+
+/// Sends a notification to the user's private chat. Users the bot can no longer
+/// write to are unsubscribed instead of retrying.
+pub async fn send(bot: Arc<Bot>, state: State, event: Event) -> anyhow::Result<()> {
+    let Event::PrivateNotification {
+        user_id,
+        text,
+        silent,
+    } = event
+    else {
+        return Ok(());
+    };
+
+    let result = bot
+        .send_message(user_id, text)
+        .disable_notification(silent)
+        .await;
+    match result {
+        Ok(_) => Ok(()),
+        Err(RequestError::Api(
+            ApiError::BotBlocked
+            | ApiError::UserDeactivated
+            | ApiError::CantInitiateConversation
+            | ApiError::ChatNotFound,
+        )) => {
+            tracing::info!("Can't reach user {} privately, unsubscribing", user_id);
+            unsubscribe(&state.db, user_id, None, None).await?;
+            Ok(())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use teloxide::Bot;
+
+use crate::{
+    bot::queues::QueueMessages,
+    repositories::queue_repository::{get_queue_by_id, get_users},
+    state::{Event, State},
+};
+
+pub async fn refresh(bot: Arc<Bot>, state: State, event: Event) -> anyhow::Result<()> {
+    let Event::RefreshQueue { queue_id } = event else {
+        return Ok(());
+    };
+    let queue = get_queue_by_id(&state.db, queue_id).await?;
+    let users = get_users(&state.db, queue_id).await?;
+    bot.edit_queue(queue, users).await;
+
+    Ok(())
+}
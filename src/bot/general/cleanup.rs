@@ -0,0 +1,92 @@
+use teloxide::{prelude::Requester, types::Message, Bot};
+
+use crate::{
+    bot::{
+        handler::HandlerResult,
+        utils::{params::get_n_params, permissions::is_privileged},
+    },
+    delete_message,
+    models::cleanup::CleanupKind,
+    redis::RedisCache,
+    repositories::chat_repository::set_cleanup_delay,
+    state::State,
+};
+
+/// Longest delay accepted; Telegram no longer lets bots delete messages after 48 hours.
+const MAX_CLEANUP_DELAY_SECS: i32 = 24 * 60 * 60;
+
+/// `/cleanup_delay <kind> <seconds>` sets how long messages of a kind stay in the chat.
+pub async fn cleanup_delay(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+
+    if !is_privileged(&bot, &msg).await? {
+        let new_msg = bot
+            .send_message(msg.chat.id, "Тільки адміністратори можуть це змінювати")
+            .await?;
+        delete_message!(state, new_msg);
+        return Ok(());
+    }
+
+    let kinds = CleanupKind::ALL
+        .iter()
+        .map(|kind| String::from(*kind))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let usage = format!(
+        "Використання: /cleanup_delay <тип> <секунди>\nТипи: {}",
+        kinds
+    );
+
+    let params = match get_n_params::<String>(&msg, 2) {
+        Ok(params) => params,
+        Err(_) => {
+            let new_msg = bot.send_message(msg.chat.id, usage).await?;
+            delete_message!(state, new_msg);
+            return Ok(());
+        }
+    };
+
+    let kind = CleanupKind::try_from(params[0].as_str());
+    let delay = params[1].parse::<i32>();
+    let (kind, delay) = match (kind, delay) {
+        (Ok(kind), Ok(delay)) if (0..=MAX_CLEANUP_DELAY_SECS).contains(&delay) => (kind, delay),
+        _ => {
+            let new_msg = bot.send_message(msg.chat.id, usage).await?;
+            delete_message!(state, new_msg);
+            return Ok(());
+        }
+    };
+
+    set_cleanup_delay(&state.db, msg.chat.id, kind, delay).await?;
+
+    let new_msg = bot
+        .send_message(
+            msg.chat.id,
+            format!(
+                "Повідомлення типу {} видалятимуться через {} с",
+                String::from(kind),
+                delay
+            ),
+        )
+        .await?;
+    delete_message!(state, new_msg);
+
+    Ok(())
+}
+
+/// `/clear` deletes every message of the chat still waiting for cleanup.
+pub async fn clear(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    if !is_privileged(&bot, &msg).await? {
+        delete_message!(state, msg);
+        return Ok(());
+    }
+
+    let cleared = state.redis.expedite_deletions(msg.chat.id)?;
+    tracing::debug!("Expedited {} deletions in chat {}", cleared, msg.chat.id);
+
+    if let Err(err) = bot.delete_message(msg.chat.id, msg.id).await {
+        tracing::error!("Failed to delete message: {:?}", err);
+    }
+
+    Ok(())
+}
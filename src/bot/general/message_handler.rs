@@ -6,7 +6,8 @@ use teloxide::{
 };
 
 use crate::{
-    bot::handler::HandlerResult, clients::gemini::send_to_gemini, redis::RedisCache, state::State,
+    bot::handler::HandlerResult, clients::gemini::send_to_gemini, redis::RedisCache,
+    repositories::stats_repository::record_message_activity, state::State,
 };
 
 pub async fn handler(bot: Bot, msg: Message, state: State) -> HandlerResult {
@@ -14,6 +15,12 @@ pub async fn handler(bot: Bot, msg: Message, state: State) -> HandlerResult {
 
     handle_gemini_mention(&bot, &msg).await?;
 
+    if let Some(user) = msg.from.as_ref() {
+        if let Err(err) = record_message_activity(&state.db, user.id).await {
+            tracing::error!("Failed to record message activity: {:?}", err);
+        }
+    }
+
     state.redis.store_message(msg)?;
 
     Ok(())
@@ -1,5 +1,6 @@
 use teloxide::types::{ChatId, MessageId};
 
+pub mod cleanup;
 pub mod commands;
 pub mod message_handler;
 
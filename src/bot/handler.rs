@@ -1,21 +1,23 @@
 use teloxide::{
-    dispatching::{
-        dialogue::{self, InMemStorage},
-        UpdateFilterExt, UpdateHandler,
-    },
+    dispatching::{dialogue, UpdateFilterExt, UpdateHandler},
     dptree,
-    types::Update,
+    types::{Message, Update, UserId},
 };
 
 use crate::{
     bot::{
+        attendance,
         callbacks::handle_callback,
-        general,
+        deadlines, general,
         inline::answer_inline_query,
-        queues, stats,
+        queues, stats, subscriptions,
         timetable::{self, external::receive_timetable_entry_link},
     },
-    config::{commands::Command, state::StateMachine},
+    config::{
+        commands::Command,
+        state::{StateMachine, TimetableEdit},
+    },
+    redis::dialogue::RedisDialogueStorage,
     repositories::{
         chat_repository::create_chat_if_not_exists, user_repository::create_user_if_not_exists,
     },
@@ -41,6 +43,8 @@ pub fn handler() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'sta
                 .branch(case![Command::Start].endpoint(general::commands::start)),
         )
         .branch(case![Command::Help].endpoint(general::commands::help))
+        .branch(case![Command::CleanupDelay].endpoint(general::cleanup::cleanup_delay))
+        .branch(case![Command::Clear].endpoint(general::cleanup::clear))
         // timetable
         .branch(case![Command::Week].endpoint(timetable::commands::week))
         .branch(case![Command::Today].endpoint(timetable::commands::today))
@@ -49,10 +53,41 @@ pub fn handler() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'sta
         .branch(case![Command::Next].endpoint(timetable::commands::next))
         .branch(case![Command::Import].endpoint(timetable::commands::import))
         .branch(case![Command::EditTimetable].endpoint(timetable::commands::edit_timetable))
+        .branch(case![Command::ExportIcs].endpoint(timetable::commands::export_ics))
+        .branch(case![Command::Subgroup].endpoint(timetable::subgroups::subgroup))
+        .branch(case![Command::RemoveSubgroup].endpoint(timetable::subgroups::remove_subgroup))
+        .branch(case![Command::Calendar].endpoint(timetable::settings::calendar))
+        .branch(case![Command::Timezone].endpoint(timetable::settings::timezone))
+        .branch(case![Command::SemesterStart].endpoint(timetable::settings::semester_start))
+        .branch(case![Command::AddHoliday].endpoint(timetable::settings::add_holiday))
+        .branch(case![Command::RemoveHoliday].endpoint(timetable::settings::remove_holiday))
+        .branch(case![Command::Reminders].endpoint(timetable::settings::reminders))
+        .branch(case![Command::ReminderOffsets].endpoint(timetable::settings::reminder_offsets))
+        .branch(case![Command::MuteClassType].endpoint(timetable::settings::mute_class_type))
+        .branch(case![Command::Digest].endpoint(timetable::settings::digest))
+        .branch(case![Command::Attendance].endpoint(attendance::commands::attendance))
+        // deadlines
+        .branch(case![Command::Deadline].endpoint(deadlines::commands::deadline))
+        .branch(case![Command::Deadlines].endpoint(deadlines::commands::deadlines))
+        .branch(case![Command::RemoveDeadline].endpoint(deadlines::commands::remove))
+        .branch(case![Command::DeadlineReminders].endpoint(deadlines::commands::reminders))
+        // subscriptions
+        .branch(case![Command::Subscribe].endpoint(subscriptions::commands::subscribe))
+        .branch(case![Command::Unsubscribe].endpoint(subscriptions::commands::unsubscribe))
+        .branch(case![Command::Subscriptions].endpoint(subscriptions::commands::subscriptions))
+        .branch(case![Command::QuietHours].endpoint(subscriptions::commands::quiet_hours))
         // queues
         .branch(case![Command::Queue].endpoint(queues::commands::queue))
         .branch(case![Command::Mixed].endpoint(queues::commands::mixed))
         .branch(case![Command::PriorityQueue].endpoint(queues::commands::priority_queue))
+        .branch(case![Command::Lottery].endpoint(queues::lottery::lottery))
+        .branch(case![Command::QueueMove].endpoint(queues::admin::queue_move))
+        .branch(case![Command::QueueSwap].endpoint(queues::admin::queue_swap))
+        .branch(case![Command::QueueKick].endpoint(queues::admin::queue_kick))
+        .branch(case![Command::QueueAdd].endpoint(queues::admin::queue_add))
+        .branch(case![Command::QueueHistory].endpoint(queues::commands::queue_history))
+        .branch(case![Command::QueueSchedule].endpoint(queues::schedule::queue_schedule))
+        .branch(case![Command::QueuePriority].endpoint(queues::priority::queue_priority))
         // stats
         .branch(case![Command::Stats].endpoint(stats::commands::stats))
         .branch(case![Command::Casino].endpoint(stats::commands::casino))
@@ -75,6 +110,13 @@ pub fn handler() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'sta
             case![StateMachine::ReceiveEditTimetableEntry { id }]
                 .endpoint(receive_timetable_entry_link),
         )
+        .branch(
+            case![StateMachine::ReceiveTimetableEdit { edit, user_id }]
+                .filter(|msg: Message, (_, user_id): (TimetableEdit, UserId)| {
+                    msg.from.as_ref().map(|user| user.id) == Some(user_id)
+                })
+                .endpoint(timetable::editor::receive_timetable_edit),
+        )
         .branch(dptree::endpoint(general::message_handler::handler));
 
     let inline_handler = Update::filter_inline_query().endpoint(answer_inline_query);
@@ -83,7 +125,7 @@ pub fn handler() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'sta
         .filter_map_async(preprocess_update)
         .branch(inline_handler)
         .branch(
-            dialogue::enter::<Update, InMemStorage<StateMachine>, StateMachine, _>()
+            dialogue::enter::<Update, RedisDialogueStorage, StateMachine, _>()
                 .branch(message_handler)
                 .branch(reaction_handler),
         )
@@ -1,12 +1,15 @@
 use teloxide::prelude::ResponseResult;
 
+pub mod attendance;
 pub mod callbacks;
+pub mod deadlines;
 pub mod events;
 pub mod general;
 pub mod handler;
 pub mod inline;
 pub mod queues;
 pub mod stats;
+pub mod subscriptions;
 pub mod timetable;
 pub mod ui;
 pub mod utils;
@@ -0,0 +1,225 @@
+use teloxide::{prelude::Requester, types::Message, Bot};
+
+use crate::{
+    bot::{
+        handler::HandlerResult,
+        queues::{first_in_queue, notify_next, QueueMessages},
+        utils::permissions::is_privileged,
+    },
+    delete_message,
+    models::{queue::QueueModel, user::UserModel},
+    repositories::{
+        queue_repository::{
+            add_queue_user_by_admin, get_queue, get_queue_by_id, get_users, kick_queue_user,
+            move_queue_user, order_by_priority, swap_queue_users,
+        },
+        user_repository::{find_chat_user, get_user_by_account_id},
+    },
+    state::State,
+};
+
+/// Moves the member at `from` to `to`, shifting the ones in between. Positions
+/// start at 1; `false` is returned when one is out of range.
+pub fn move_member<T>(members: &mut Vec<T>, from: usize, to: usize) -> bool {
+    let range = 1..=members.len();
+    if !range.contains(&from) || !range.contains(&to) {
+        return false;
+    }
+    let member = members.remove(from - 1);
+    members.insert(to - 1, member);
+    true
+}
+
+/// Swaps the members at positions `a` and `b`, counted from 1.
+pub fn swap_members<T>(members: &mut [T], a: usize, b: usize) -> bool {
+    let range = 1..=members.len();
+    if !range.contains(&a) || !range.contains(&b) {
+        return false;
+    }
+    members.swap(a - 1, b - 1);
+    true
+}
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg);
+    Ok(())
+}
+
+fn positions<const N: usize>(msg: &Message) -> Option<[i32; N]> {
+    let positions: Option<Vec<i32>> = msg
+        .text()?
+        .split_whitespace()
+        .skip(1)
+        .map(|param| param.parse().ok())
+        .collect();
+    positions?.try_into().ok()
+}
+
+/// The queue the command replies to and the admin who sent it, or what to tell
+/// the sender instead.
+async fn admin_queue(
+    bot: &Bot,
+    msg: &Message,
+    state: &State,
+) -> anyhow::Result<Result<(QueueModel, UserModel), &'static str>> {
+    let sender = match msg.from.as_ref() {
+        Some(sender) if is_privileged(bot, msg).await? => sender,
+        _ => return Ok(Err("Тільки адміністратори можуть керувати чергою")),
+    };
+    let queue = match msg.reply_to_message() {
+        Some(queue_msg) => get_queue(&state.db, msg.chat.id, queue_msg.id).await.ok(),
+        None => None,
+    };
+    let Some(queue) = queue else {
+        return Ok(Err("Надішліть команду у відповідь на повідомлення черги"));
+    };
+    let actor = get_user_by_account_id(state, sender.id).await?;
+    Ok(Ok((queue, actor)))
+}
+
+/// Redraws the queue after an admin changed it.
+async fn refresh(
+    bot: &Bot,
+    state: &State,
+    queue_id: i32,
+    previous_first: Option<i64>,
+) -> anyhow::Result<()> {
+    let queue = get_queue_by_id(&state.db, queue_id).await?;
+    let users = get_users(&state.db, queue_id).await?;
+    notify_next(state, &queue, previous_first, &users).await?;
+    bot.edit_queue(queue, users).await;
+    Ok(())
+}
+
+/// `/queue_move <from> <to>` in reply to a queue moves a member to another position.
+pub async fn queue_move(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+    let (queue, actor) = match admin_queue(&bot, &msg, &state).await? {
+        Ok(found) => found,
+        Err(text) => return reply(&bot, &msg, &state, text.to_string()).await,
+    };
+    let Some([from, to]) = positions(&msg) else {
+        let usage = "Використання: /queue_move <звідки> <куди> у відповідь на чергу";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let previous_first = first_in_queue(&state, queue.id).await?;
+    if move_queue_user(&state.db, queue.id, actor.id, from, to)
+        .await?
+        .is_none()
+    {
+        let text = format!("У черзі немає позиції {} або {}", from, to);
+        return reply(&bot, &msg, &state, text).await;
+    }
+    refresh(&bot, &state, queue.id, previous_first).await?;
+    Ok(())
+}
+
+/// `/queue_swap <a> <b>` in reply to a queue swaps two members.
+pub async fn queue_swap(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+    let (queue, actor) = match admin_queue(&bot, &msg, &state).await? {
+        Ok(found) => found,
+        Err(text) => return reply(&bot, &msg, &state, text.to_string()).await,
+    };
+    let Some([a, b]) = positions(&msg) else {
+        let usage = "Використання: /queue_swap <позиція> <позиція> у відповідь на чергу";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let previous_first = first_in_queue(&state, queue.id).await?;
+    if !swap_queue_users(&state.db, queue.id, actor.id, a, b).await? {
+        let text = format!("У черзі немає позиції {} або {}", a, b);
+        return reply(&bot, &msg, &state, text).await;
+    }
+    refresh(&bot, &state, queue.id, previous_first).await?;
+    Ok(())
+}
+
+/// `/queue_kick <position>` in reply to a queue removes a member from it.
+pub async fn queue_kick(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+    let (queue, actor) = match admin_queue(&bot, &msg, &state).await? {
+        Ok(found) => found,
+        Err(text) => return reply(&bot, &msg, &state, text.to_string()).await,
+    };
+    let Some([position]) = positions(&msg) else {
+        let usage = "Використання: /queue_kick <позиція> у відповідь на чергу";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let previous_first = first_in_queue(&state, queue.id).await?;
+    if kick_queue_user(&state.db, queue.id, actor.id, position)
+        .await?
+        .is_none()
+    {
+        let text = format!("У черзі немає позиції {}", position);
+        return reply(&bot, &msg, &state, text).await;
+    }
+    refresh(&bot, &state, queue.id, previous_first).await?;
+    Ok(())
+}
+
+/// `/queue_add <@username|name>` in reply to a queue adds someone who can't
+/// press the button themselves.
+pub async fn queue_add(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+    let (queue, actor) = match admin_queue(&bot, &msg, &state).await? {
+        Ok(found) => found,
+        Err(text) => return reply(&bot, &msg, &state, text.to_string()).await,
+    };
+    let query = msg
+        .text()
+        .and_then(|text| text.split_once(char::is_whitespace))
+        .map(|(_, query)| query.trim())
+        .filter(|query| !query.is_empty());
+    let Some(query) = query else {
+        let usage = "Використання: /queue_add <@username або ім'я> у відповідь на чергу";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let Some(user) = find_chat_user(&state, msg.chat.id, query).await? else {
+        let text = format!("Користувача {} ще немає в цьому чаті", query);
+        return reply(&bot, &msg, &state, text).await;
+    };
+    let previous_first = first_in_queue(&state, queue.id).await?;
+    if add_queue_user_by_admin(&state.db, queue.id, actor.id, user.id)
+        .await?
+        .is_none()
+    {
+        let text = format!("{} вже в черзі", user.name);
+        return reply(&bot, &msg, &state, text).await;
+    }
+    if queue.is_priority {
+        order_by_priority(&state.db, queue.id).await?;
+    }
+    refresh(&bot, &state, queue.id, previous_first).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_member() {
+        let mut members = vec![1, 2, 3, 4];
+        assert!(move_member(&mut members, 4, 1));
+        assert_eq!(members, vec![4, 1, 2, 3]);
+        assert!(move_member(&mut members, 1, 3));
+        assert_eq!(members, vec![1, 2, 4, 3]);
+        assert!(!move_member(&mut members, 0, 1));
+        assert!(!move_member(&mut members, 2, 5));
+        assert_eq!(members, vec![1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn test_swap_members() {
+        let mut members = vec![1, 2, 3];
+        assert!(swap_members(&mut members, 1, 3));
+        assert_eq!(members, vec![3, 2, 1]);
+        assert!(!swap_members(&mut members, 3, 4));
+        assert_eq!(members, vec![3, 2, 1]);
+    }
+}
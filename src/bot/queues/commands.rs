@@ -1,6 +1,9 @@
-use crate::bot::queues::QueueMessages;
+use crate::bot::queues::{
+    priority::{parse_queue_subject, resolve_subject},
+    QueueMessages,
+};
 use crate::bot::ui;
-use crate::repositories::queue_repository::create_queue;
+use crate::repositories::queue_repository::{create_queue, get_queue, get_queue_history};
 use crate::delete_message;
 use crate::state::State;
 use crate::{bot::handler::HandlerResult, param};
@@ -10,13 +13,24 @@ use teloxide::{
     Bot,
 };
 
+/// How many of the latest changes `/queue_history` shows.
+const HISTORY_LIMIT: i64 = 20;
+
 pub async fn queue(bot: Bot, msg: Message, state: State) -> HandlerResult {
     let name = param!(bot, msg, state, String, "Вкажіть назву черги");
 
     let loading_msg = loading_message(&bot, msg.chat.id).await?;
 
-    let new_queue =
-        create_queue(&state.db, &name, msg.chat.id, loading_msg.id, None, false).await?;
+    let new_queue = create_queue(
+        &state.db,
+        &name,
+        msg.chat.id,
+        loading_msg.id,
+        None,
+        false,
+        None,
+    )
+    .await?;
 
     bot.edit_queue(new_queue, Vec::new()).await;
 
@@ -36,6 +50,7 @@ pub async fn mixed(bot: Bot, msg: Message, state: State) -> HandlerResult {
         loading_msg.id,
         Some(false),
         false,
+        None,
     )
     .await?;
 
@@ -45,12 +60,35 @@ pub async fn mixed(bot: Bot, msg: Message, state: State) -> HandlerResult {
     Ok(())
 }
 
+/// `/priority_queue <title> [| subject]`; priority queues of the same subject
+/// share their history.
 pub async fn priority_queue(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let name = param!(bot, msg, state, String, "Вкажіть назву черги");
+    let text = param!(bot, msg, state, String, "Вкажіть назву черги");
+    let Some((name, subject)) = parse_queue_subject(&text) else {
+        let new_msg = bot
+            .send_message(
+                msg.chat.id,
+                "Використання: /priority_queue <назва> [| предмет]",
+            )
+            .await?;
+        delete_message!(state, msg);
+        delete_message!(state, new_msg);
+        return Ok(());
+    };
+    let subject = resolve_subject(&state, msg.chat.id, &subject).await?;
 
     let loading_msg = loading_message(&bot, msg.chat.id).await?;
 
-    let new_queue = create_queue(&state.db, &name, msg.chat.id, loading_msg.id, None, true).await?;
+    let new_queue = create_queue(
+        &state.db,
+        &name,
+        msg.chat.id,
+        loading_msg.id,
+        None,
+        true,
+        Some(&subject),
+    )
+    .await?;
 
     bot.edit_queue(new_queue, Vec::new()).await;
 
@@ -58,6 +96,27 @@ pub async fn priority_queue(bot: Bot, msg: Message, state: State) -> HandlerResu
     Ok(())
 }
 
+/// `/queue_history` in reply to a queue shows who changed it and how.
+pub async fn queue_history(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    let queue = match msg.reply_to_message() {
+        Some(queue_msg) => get_queue(&state.db, msg.chat.id, queue_msg.id).await.ok(),
+        None => None,
+    };
+    let text = match queue {
+        Some(queue) => {
+            let events = get_queue_history(&state.db, queue.id, HISTORY_LIMIT).await?;
+            ui::queue_ui::history_view(&queue, &events)
+        }
+        None => "Надішліть /queue_history у відповідь на повідомлення черги".to_string(),
+    };
+
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+
+    delete_message!(state, msg);
+    delete_message!(state, new_msg);
+    Ok(())
+}
+
 async fn loading_message(bot: &Bot, chat_id: ChatId) -> anyhow::Result<Message> {
     let msg = bot
         .send_message(chat_id, ui::queue_ui::title(&"Нова черга".to_string()))
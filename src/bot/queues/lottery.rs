@@ -0,0 +1,162 @@
+use chrono::Duration;
+use teloxide::{prelude::Requester, types::Message, Bot};
+
+use crate::{
+    api::games::fairness::{generate_seed, FairRng},
+    bot::{
+        handler::HandlerResult,
+        queues::{first_in_queue, notify_next, QueueMessages},
+        ui,
+    },
+    delete_message,
+    repositories::{
+        chat_repository::get_calendar,
+        queue_repository::{
+            create_queue, draw_lottery, get_due_lotteries, get_queue_by_id, get_users,
+            start_lottery,
+        },
+    },
+    state::{Event, State},
+};
+
+const DEFAULT_WINDOW_MINUTES: i64 = 5;
+const MAX_WINDOW_MINUTES: i64 = 24 * 60;
+
+/// Fisher–Yates over the members in join order: for `i` from the last index
+/// down to 1, swap with `j = below(i + 1)` drawn from [`FairRng`] seeded with
+/// the queue seed and the queue id. Anyone can replay it from the seed.
+pub fn draw_order<T>(members: &mut [T], seed: &str, queue_id: i32) {
+    let mut rng = FairRng::new(seed, &queue_id.to_string());
+    for i in (1..members.len()).rev() {
+        let j = rng.below(i as u32 + 1) as usize;
+        members.swap(i, j);
+    }
+}
+
+/// `[minutes] <title>`; without a leading number the window is
+/// [`DEFAULT_WINDOW_MINUTES`] long.
+pub fn parse_lottery(params: &[&str]) -> Option<(i64, String)> {
+    let (minutes, title) = match params.split_first() {
+        Some((minutes, title)) if !title.is_empty() => match minutes.parse::<i64>() {
+            Ok(minutes) => (minutes, title),
+            Err(_) => (DEFAULT_WINDOW_MINUTES, params),
+        },
+        _ => (DEFAULT_WINDOW_MINUTES, params),
+    };
+    if title.is_empty() || !(1..=MAX_WINDOW_MINUTES).contains(&minutes) {
+        return None;
+    }
+    Some((minutes, title.join(" ")))
+}
+
+/// `/lottery [minutes] <title>` creates a mixed queue whose members, joined
+/// within the window, are ordered by a draw when it closes.
+pub async fn lottery(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+    let params: Vec<&str> = msg
+        .text()
+        .map(|text| text.split_whitespace().skip(1).collect())
+        .unwrap_or_default();
+    let Some((minutes, title)) = parse_lottery(&params) else {
+        let usage = format!(
+            "Використання: /lottery [хвилин, до {}] <назва черги>",
+            MAX_WINDOW_MINUTES
+        );
+        let new_msg = bot.send_message(msg.chat.id, usage).await?;
+        delete_message!(state, new_msg);
+        return Ok(());
+    };
+
+    let loading_msg = bot
+        .send_message(msg.chat.id, ui::queue_ui::title(&"Нова черга".to_string()))
+        .await?;
+    let mut new_queue = create_queue(
+        &state.db,
+        &title,
+        msg.chat.id,
+        loading_msg.id,
+        Some(false),
+        false,
+        None,
+    )
+    .await?;
+
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    let seed = generate_seed();
+    let closes_at = calendar.now().naive_local() + Duration::minutes(minutes);
+    start_lottery(&state.db, new_queue.id, &seed, closes_at).await?;
+    new_queue.lottery_seed = Some(seed);
+    new_queue.lottery_closes_at = Some(closes_at);
+
+    bot.edit_queue(new_queue, Vec::new()).await;
+    Ok(())
+}
+
+/// Draws the lottery queues whose window is over. The windows are stored with
+/// the queues, so draws missed while the bot was down happen on the next run.
+pub async fn queue_lotteries(state: State) {
+    let queue_ids = match get_due_lotteries(&state.db).await {
+        Ok(queue_ids) => queue_ids,
+        Err(err) => {
+            tracing::error!("Failed to query due lotteries: {:?}", err);
+            return;
+        }
+    };
+    for queue_id in queue_ids {
+        if let Err(err) = draw(&state, queue_id).await {
+            tracing::error!("Failed to draw lottery of queue {}: {:?}", queue_id, err);
+        }
+    }
+}
+
+async fn draw(state: &State, queue_id: i32) -> anyhow::Result<()> {
+    let previous_first = first_in_queue(state, queue_id).await?;
+    if !draw_lottery(&state.db, queue_id).await? {
+        return Ok(());
+    }
+    let queue = get_queue_by_id(&state.db, queue_id).await?;
+    let users = get_users(&state.db, queue_id).await?;
+    notify_next(state, &queue, previous_first, &users).await?;
+    state.events.send(Event::RefreshQueue { queue_id })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_order() {
+        let members: Vec<i32> = (1..=10).collect();
+
+        let mut a = members.clone();
+        let mut b = members.clone();
+        let mut c = members.clone();
+        draw_order(&mut a, "seed", 1);
+        draw_order(&mut b, "seed", 1);
+        draw_order(&mut c, "seed", 2);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        a.sort_unstable();
+        assert_eq!(a, members);
+    }
+
+    #[test]
+    fn test_parse_lottery() {
+        assert_eq!(
+            parse_lottery(&["10", "Лаба", "3"]),
+            Some((10, "Лаба 3".to_string()))
+        );
+        assert_eq!(
+            parse_lottery(&["Лаба", "3"]),
+            Some((DEFAULT_WINDOW_MINUTES, "Лаба 3".to_string()))
+        );
+        assert_eq!(
+            parse_lottery(&["3"]),
+            Some((DEFAULT_WINDOW_MINUTES, "3".to_string()))
+        );
+        assert_eq!(parse_lottery(&["0", "Лаба"]), None);
+        assert_eq!(parse_lottery(&[]), None);
+    }
+}
@@ -1,4 +1,8 @@
+pub mod admin;
 pub mod commands;
+pub mod lottery;
+pub mod priority;
+pub mod schedule;
 
 use async_trait::async_trait;
 use teloxide::{
@@ -8,14 +12,29 @@ use teloxide::{
     Bot,
 };
 
-use crate::models::queue::{QueueModel, QueueUserWithUserModel};
+use crate::{
+    models::{
+        queue::{QueueModel, QueueUserWithUserModel},
+        subscription::SubscriptionKind,
+    },
+    repositories::queue_repository::get_users,
+    state::State,
+};
+
+use super::{
+    subscriptions::notify_subscriber, ui, utils::reply_markup_builder::ReplyMarkupBuilder,
+};
 
-use super::{ui, utils::reply_markup_builder::ReplyMarkupBuilder};
+/// How long the last change of a queue can be undone.
+pub const UNDO_WINDOW_MINUTES: i32 = 10;
 
 pub trait QueueMarkupExt {
     fn regular_queue_markup(queue_id: i32) -> InlineKeyboardMarkup;
     fn mixed_queue_markup(queue_id: i32, is_mixed: bool) -> InlineKeyboardMarkup;
     fn priority_queue_markup(queue_id: i32) -> InlineKeyboardMarkup;
+    fn queue_admin_markup(queue_id: i32, users: &[QueueUserWithUserModel]) -> InlineKeyboardMarkup;
+    fn queue_member_markup(member: &QueueUserWithUserModel) -> InlineKeyboardMarkup;
+    fn deleted_queue_markup(queue_id: i32) -> InlineKeyboardMarkup;
 }
 
 impl QueueMarkupExt for ReplyMarkupBuilder {
@@ -29,6 +48,10 @@ impl QueueMarkupExt for ReplyMarkupBuilder {
                 ("Delete ❌", format!("delete-queue_{}", queue_id)),
                 ("Notify 📢", format!("notify-queue_{}", queue_id)),
             ])
+            .button_row(vec![
+                ("Undo ↩️", format!("queue-undo_{}", queue_id)),
+                ("Admin ⚙️", format!("queue-admin_{}", queue_id)),
+            ])
             .build()
     }
 
@@ -47,7 +70,12 @@ impl QueueMarkupExt for ReplyMarkupBuilder {
             markup = markup.single_button("Shuffle 🔀", format!("shuffle-queue_{}", queue_id));
         }
 
-        markup.build()
+        markup
+            .button_row(vec![
+                ("Undo ↩️", format!("queue-undo_{}", queue_id)),
+                ("Admin ⚙️", format!("queue-admin_{}", queue_id)),
+            ])
+            .build()
     }
 
     fn priority_queue_markup(queue_id: i32) -> InlineKeyboardMarkup {
@@ -64,32 +92,131 @@ impl QueueMarkupExt for ReplyMarkupBuilder {
                 ("Freeze ❄️", format!("freeze-queue_{}", queue_id)),
                 ("Notify 📢", format!("notify-queue_{}", queue_id)),
             ])
+            .button_row(vec![
+                ("Undo ↩️", format!("queue-undo_{}", queue_id)),
+                ("Admin ⚙️", format!("queue-admin_{}", queue_id)),
+            ])
+            .build()
+    }
+
+    fn queue_admin_markup(queue_id: i32, users: &[QueueUserWithUserModel]) -> InlineKeyboardMarkup {
+        let labels: Vec<(String, String)> = users
+            .iter()
+            .enumerate()
+            .map(|(i, user)| {
+                (
+                    format!("{}. {}", i + 1, user.name),
+                    format!("queue-member_{}", user.id),
+                )
+            })
+            .collect();
+
+        let mut markup = ReplyMarkupBuilder::new();
+        for pair in labels.chunks(2) {
+            markup = markup.button_row(
+                pair.iter()
+                    .map(|(text, data)| (text.as_str(), data.clone()))
+                    .collect(),
+            );
+        }
+
+        markup
+            .single_button("Back ⬅️", format!("queue-admin-close_{}", queue_id))
+            .build()
+    }
+
+    fn queue_member_markup(member: &QueueUserWithUserModel) -> InlineKeyboardMarkup {
+        ReplyMarkupBuilder::new()
+            .button_row(vec![
+                ("Top ⏫", format!("queue-top_{}", member.id)),
+                ("Up ⬆️", format!("queue-up_{}", member.id)),
+                ("Down ⬇️", format!("queue-down_{}", member.id)),
+            ])
+            .single_button("Kick ❌", format!("queue-kick_{}", member.id))
+            .single_button("Back ⬅️", format!("queue-admin_{}", member.queue_id))
+            .build()
+    }
+
+    fn deleted_queue_markup(queue_id: i32) -> InlineKeyboardMarkup {
+        ReplyMarkupBuilder::new()
+            .single_button("Undo ↩️", format!("queue-undo_{}", queue_id))
             .build()
     }
 }
 
+/// The buttons a queue shows outside of the admin panel.
+pub fn queue_markup(queue: &QueueModel) -> InlineKeyboardMarkup {
+    if queue.is_archived {
+        InlineKeyboardMarkup::default()
+    } else if queue.is_mixed.is_some() {
+        // A lottery queue is shuffled by its draw, not by hand.
+        let is_mixed = queue.is_mixed.unwrap_or(false) || queue.lottery_closes_at.is_some();
+        ReplyMarkupBuilder::mixed_queue_markup(queue.id, is_mixed)
+    } else if queue.is_priority {
+        ReplyMarkupBuilder::priority_queue_markup(queue.id)
+    } else {
+        ReplyMarkupBuilder::regular_queue_markup(queue.id)
+    }
+}
+
+pub async fn first_in_queue(state: &State, queue_id: i32) -> anyhow::Result<Option<i64>> {
+    let users = get_users(&state.db, queue_id).await?;
+    Ok(users.first().map(|user| user.account_id))
+}
+
+/// Tells the user who became first in the queue, if they subscribed to it.
+pub async fn notify_next(
+    state: &State,
+    queue: &QueueModel,
+    previous_first: Option<i64>,
+    users: &[QueueUserWithUserModel],
+) -> anyhow::Result<()> {
+    let Some(first) = users.first() else {
+        return Ok(());
+    };
+    if previous_first == Some(first.account_id) {
+        return Ok(());
+    }
+    notify_subscriber(
+        state,
+        ChatId(queue.chat_id),
+        SubscriptionKind::Queue,
+        first.account_id,
+        &ui::subscription_ui::queue_notification(queue),
+    )
+    .await
+}
+
 #[async_trait]
 pub trait QueueMessages {
     async fn edit_queue(&self, queue: QueueModel, users: Vec<QueueUserWithUserModel>);
+    async fn edit_queue_with_markup(
+        &self,
+        queue: QueueModel,
+        users: Vec<QueueUserWithUserModel>,
+        markup: InlineKeyboardMarkup,
+    );
 }
 
 #[async_trait]
 impl QueueMessages for Bot {
     async fn edit_queue(&self, queue: QueueModel, users: Vec<QueueUserWithUserModel>) {
+        let markup = queue_markup(&queue);
+        self.edit_queue_with_markup(queue, users, markup).await;
+    }
+
+    async fn edit_queue_with_markup(
+        &self,
+        queue: QueueModel,
+        users: Vec<QueueUserWithUserModel>,
+        markup: InlineKeyboardMarkup,
+    ) {
         let content = if queue.is_priority {
             ui::queue_ui::priority_queue(&queue, users)
         } else {
             ui::queue_ui::regular_queue(&queue, users)
         };
 
-        let markup = if queue.is_mixed.is_some() {
-            ReplyMarkupBuilder::mixed_queue_markup(queue.id, queue.is_mixed.unwrap_or(false))
-        } else if queue.is_priority {
-            ReplyMarkupBuilder::priority_queue_markup(queue.id)
-        } else {
-            ReplyMarkupBuilder::regular_queue_markup(queue.id)
-        };
-
         let result = self
             .edit_message_text(ChatId(queue.chat_id), MessageId(queue.message_id), content)
             .reply_markup(markup)
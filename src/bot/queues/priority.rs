@@ -0,0 +1,204 @@
+use std::iter;
+
+use teloxide::{
+    prelude::Requester,
+    types::{ChatId, Message},
+    Bot,
+};
+
+use crate::{
+    bot::{
+        deadlines::commands::match_class_name, handler::HandlerResult, ui,
+        utils::permissions::is_privileged,
+    },
+    delete_message,
+    models::queue::{QueuePrioritySettingsModel, QueueUserModel},
+    repositories::{
+        queue_repository::{
+            get_queue_priority_settings, get_queue_subjects, set_queue_priority_settings,
+        },
+        timetable_repository::get_full_timetable,
+    },
+    state::State,
+};
+
+const MAX_DAYS: i32 = 365;
+
+/// Sum of the defences, each worth `0.5^(age / half_life)`, or 1 when
+/// `half_life_days` is 0. Ages are in days.
+pub fn history_weight(ages_days: &[f64], half_life_days: i32) -> f64 {
+    if half_life_days <= 0 {
+        return ages_days.len() as f64;
+    }
+    ages_days
+        .iter()
+        .map(|age| 0.5f64.powf(age.max(0.0) / half_life_days as f64))
+        .sum()
+}
+
+/// Stable sort by answers in this queue, then by history weight; members who
+/// compare equal keep their order. Members without a weight count as 0.
+pub fn rank_by_priority(users: &mut Vec<QueueUserModel>, weights: &[f64]) {
+    let weights = weights.iter().copied().chain(iter::repeat(0.0));
+    let mut ranked: Vec<(QueueUserModel, f64)> = users.drain(..).zip(weights).collect();
+    ranked.sort_by(|(a, a_weight), (b, b_weight)| {
+        a.priority
+            .unwrap_or(0)
+            .cmp(&b.priority.unwrap_or(0))
+            .then(a_weight.total_cmp(b_weight))
+    });
+    users.extend(ranked.into_iter().map(|(user, _)| user));
+}
+
+/// `<title> [| subject]`; without a subject the title is one.
+pub fn parse_queue_subject(text: &str) -> Option<(String, String)> {
+    let (title, subject) = match text.split_once('|') {
+        Some((title, subject)) => (title.trim(), subject.trim()),
+        None => (text.trim(), text.trim()),
+    };
+    if title.is_empty() || subject.is_empty() {
+        return None;
+    }
+    Some((title.to_string(), subject.to_string()))
+}
+
+/// The timetable class or earlier queue subject `subject` names, or `subject`
+/// itself if none matches, so queues of one subject are spelled the same.
+pub async fn resolve_subject(
+    state: &State,
+    chat_id: ChatId,
+    subject: &str,
+) -> anyhow::Result<String> {
+    let mut names: Vec<String> = get_full_timetable(&state.db, chat_id)
+        .await?
+        .into_iter()
+        .map(|entry| entry.class_name)
+        .chain(get_queue_subjects(&state.db, chat_id).await?)
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(match_class_name(&names, subject).unwrap_or_else(|| subject.to_string()))
+}
+
+/// `window <days>` or `half_life <days|off>` applied to `settings`.
+pub fn parse_priority_settings(
+    params: &[&str],
+    settings: &QueuePrioritySettingsModel,
+) -> Option<QueuePrioritySettingsModel> {
+    let days = |value: &str| {
+        value
+            .parse::<i32>()
+            .ok()
+            .filter(|days| (1..=MAX_DAYS).contains(days))
+    };
+    match params {
+        ["window", value] => Some(QueuePrioritySettingsModel {
+            window_days: days(value)?,
+            ..settings.clone()
+        }),
+        ["half_life", "off"] => Some(QueuePrioritySettingsModel {
+            half_life_days: 0,
+            ..settings.clone()
+        }),
+        ["half_life", value] => Some(QueuePrioritySettingsModel {
+            half_life_days: days(value)?,
+            ..settings.clone()
+        }),
+        _ => None,
+    }
+}
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg);
+    Ok(())
+}
+
+/// `/queue_priority` shows how past defences weigh in priority queues; admins
+/// change it with `window` and `half_life`.
+pub async fn queue_priority(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+    let params: Vec<&str> = msg
+        .text()
+        .map(|text| text.split_whitespace().skip(1).collect())
+        .unwrap_or_default();
+    let mut settings = get_queue_priority_settings(&state.db, msg.chat.id).await?;
+
+    if !params.is_empty() {
+        if !is_privileged(&bot, &msg).await? {
+            let text = "Тільки адміністратори можуть це змінювати";
+            return reply(&bot, &msg, &state, text.to_string()).await;
+        }
+        let Some(new_settings) = parse_priority_settings(&params, &settings) else {
+            let usage = format!(
+                "Використання: /queue_priority window <днів>, /queue_priority half_life <днів|off> (до {} днів)",
+                MAX_DAYS
+            );
+            return reply(&bot, &msg, &state, usage).await;
+        };
+        set_queue_priority_settings(&state.db, &new_settings).await?;
+        settings = new_settings;
+    }
+
+    reply(
+        &bot,
+        &msg,
+        &state,
+        ui::queue_ui::priority_settings_view(&settings),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: i32, priority: Option<i32>) -> QueueUserModel {
+        QueueUserModel {
+            id,
+            position: id,
+            priority,
+            is_frozen: Some(false),
+            queue_id: 1,
+            user_id: id,
+        }
+    }
+
+    #[test]
+    fn test_history_weight() {
+        assert_eq!(history_weight(&[], 30), 0.0);
+        assert_eq!(history_weight(&[0.0, 30.0], 30), 1.5);
+        assert_eq!(history_weight(&[5.0, 50.0], 0), 2.0);
+    }
+
+    #[test]
+    fn test_rank_by_priority() {
+        let mut users = vec![
+            user(1, None),
+            user(2, Some(1)),
+            user(3, None),
+            user(4, None),
+        ];
+        rank_by_priority(&mut users, &[1.0, 0.0, 0.5, 0.5]);
+        let order: Vec<i32> = users.iter().map(|user| user.id).collect();
+        assert_eq!(order, vec![3, 4, 1, 2]);
+
+        let mut users = vec![user(1, None), user(2, None), user(3, None)];
+        rank_by_priority(&mut users, &[1.0]);
+        let order: Vec<i32> = users.iter().map(|user| user.id).collect();
+        assert_eq!(order, vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_parse_queue_subject() {
+        assert_eq!(
+            parse_queue_subject("Лаба 3 | ООП"),
+            Some(("Лаба 3".to_string(), "ООП".to_string()))
+        );
+        assert_eq!(
+            parse_queue_subject("ООП lab"),
+            Some(("ООП lab".to_string(), "ООП lab".to_string()))
+        );
+        assert_eq!(parse_queue_subject("Лаба 3 |"), None);
+    }
+}
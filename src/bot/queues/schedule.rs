@@ -0,0 +1,319 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use teloxide::{prelude::Requester, types::Message, Bot};
+
+use crate::{
+    bot::{
+        deadlines::commands::match_class_name, handler::HandlerResult, queues::QueueMessages,
+        timetable::export::CLASS_DURATION, ui, utils::permissions::is_privileged,
+    },
+    delete_message,
+    models::queue::QueueModel,
+    repositories::{
+        chat_repository::get_calendar,
+        queue_repository::{apply_queue_schedules, get_queue, get_users, set_queue_schedule},
+        timetable_repository::{get_full_timetable, get_overrides},
+    },
+    state::{Event, State},
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleChange {
+    OpenAt(NaiveDateTime),
+    OpenNow,
+    CloseAt(NaiveDateTime),
+    CloseNow,
+    /// Cancels the scheduled closing and reopens the queue.
+    KeepOpen,
+    Limit(Option<i32>),
+    /// Ties the queue to today's lesson, the one with this name if given.
+    Lesson(Option<String>),
+    NoLesson,
+}
+
+/// `[YYYY-MM-DD] <HH:MM>`; a time alone is for today.
+fn parse_time(params: &[&str], today: NaiveDate) -> Option<NaiveDateTime> {
+    let (date, time) = match params {
+        [time] => (today, time),
+        [date, time] => (NaiveDate::parse_from_str(date, DATE_FORMAT).ok()?, time),
+        _ => return None,
+    };
+    Some(date.and_time(NaiveTime::parse_from_str(time, "%H:%M").ok()?))
+}
+
+pub fn parse_schedule_change(params: &[&str], today: NaiveDate) -> Option<ScheduleChange> {
+    match params {
+        ["open", "now"] => Some(ScheduleChange::OpenNow),
+        ["open", time @ ..] => Some(ScheduleChange::OpenAt(parse_time(time, today)?)),
+        ["close", "now"] => Some(ScheduleChange::CloseNow),
+        ["close", "off"] => Some(ScheduleChange::KeepOpen),
+        ["close", time @ ..] => Some(ScheduleChange::CloseAt(parse_time(time, today)?)),
+        ["limit", "off"] => Some(ScheduleChange::Limit(None)),
+        ["limit", limit] => {
+            let limit = limit.parse().ok().filter(|limit| *limit > 0)?;
+            Some(ScheduleChange::Limit(Some(limit)))
+        }
+        ["lesson", "off"] => Some(ScheduleChange::NoLesson),
+        ["lesson"] => Some(ScheduleChange::Lesson(None)),
+        ["lesson", name @ ..] => Some(ScheduleChange::Lesson(Some(name.join(" ")))),
+        _ => None,
+    }
+}
+
+/// Why a join at `now` (chat-local) is rejected, if it is.
+pub fn join_rejection(queue: &QueueModel, users: usize, now: NaiveDateTime) -> Option<String> {
+    let closed = queue.is_closed
+        || queue.is_archived
+        || matches!(queue.closes_at, Some(closes_at) if closes_at <= now);
+    if closed {
+        return Some("Черга закрита".to_string());
+    }
+    if let Some(opens_at) = queue.opens_at.filter(|opens_at| *opens_at > now) {
+        let time = if opens_at.date() == now.date() {
+            opens_at.format("%H:%M")
+        } else {
+            opens_at.format("%d.%m %H:%M")
+        };
+        return Some(format!("Черга відкриється о {}", time));
+    }
+    match queue.max_users {
+        Some(max_users) if users as i32 >= max_users => {
+            Some(format!("У черзі вже {} учасників", max_users))
+        }
+        _ => None,
+    }
+}
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg);
+    Ok(())
+}
+
+/// The lesson of today that isn't over yet, matching `name` if given. Returns
+/// the timetable entry id with its start and end.
+async fn find_lesson(
+    state: &State,
+    msg: &Message,
+    now: NaiveDateTime,
+    name: Option<&str>,
+) -> anyhow::Result<Option<(i32, NaiveDateTime, NaiveDateTime)>> {
+    let today = now.date();
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    let entries = get_full_timetable(&state.db, msg.chat.id).await?;
+    let overrides = get_overrides(&state.db, msg.chat.id, today, today).await?;
+    let lessons: Vec<_> = calendar
+        .lessons_on(&entries, &overrides, today)
+        .into_iter()
+        .filter(|lesson| today.and_time(lesson.class_time) + CLASS_DURATION > now)
+        .collect();
+
+    let class_name = match name {
+        Some(name) => {
+            let class_names: Vec<String> = lessons
+                .iter()
+                .map(|lesson| lesson.class_name.clone())
+                .collect();
+            match match_class_name(&class_names, name) {
+                Some(class_name) => Some(class_name),
+                None => return Ok(None),
+            }
+        }
+        None => None,
+    };
+    let lesson = lessons
+        .into_iter()
+        .find(|lesson| class_name.is_none() || class_name.as_ref() == Some(&lesson.class_name));
+
+    Ok(lesson.map(|lesson| {
+        let starts_at = today.and_time(lesson.class_time);
+        (lesson.id, starts_at, starts_at + CLASS_DURATION)
+    }))
+}
+
+/// `/queue_schedule` in reply to a queue shows when it opens and closes. Admins
+/// change it with `open`, `close`, `limit` and `lesson`.
+pub async fn queue_schedule(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg);
+    let queue = match msg.reply_to_message() {
+        Some(queue_msg) => get_queue(&state.db, msg.chat.id, queue_msg.id).await.ok(),
+        None => None,
+    };
+    let Some(mut queue) = queue else {
+        let text = "Надішліть /queue_schedule у відповідь на повідомлення черги";
+        return reply(&bot, &msg, &state, text.to_string()).await;
+    };
+
+    let params: Vec<&str> = msg
+        .text()
+        .map(|text| text.split_whitespace().skip(1).collect())
+        .unwrap_or_default();
+    if params.is_empty() {
+        return reply(&bot, &msg, &state, ui::queue_ui::schedule_view(&queue)).await;
+    }
+    if !is_privileged(&bot, &msg).await? {
+        let text = "Тільки адміністратори можуть керувати чергою";
+        return reply(&bot, &msg, &state, text.to_string()).await;
+    }
+
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    let now = calendar.now().naive_local();
+    let Some(change) = parse_schedule_change(&params, now.date()) else {
+        let usage = "Використання: /queue_schedule open|close <[РРРР-ММ-ДД] ГГ:ХХ|now>, \
+            /queue_schedule close off, /queue_schedule limit <N|off>, \
+            /queue_schedule lesson [назва|off]";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    match change {
+        ScheduleChange::OpenAt(opens_at) => {
+            queue.opens_at = Some(opens_at);
+            queue.is_closed = false;
+        }
+        ScheduleChange::OpenNow => {
+            queue.opens_at = None;
+            queue.is_closed = false;
+        }
+        ScheduleChange::CloseAt(closes_at) => queue.closes_at = Some(closes_at),
+        ScheduleChange::CloseNow => {
+            queue.closes_at = None;
+            queue.is_closed = true;
+        }
+        ScheduleChange::KeepOpen => {
+            queue.closes_at = None;
+            queue.is_closed = false;
+        }
+        ScheduleChange::Limit(max_users) => queue.max_users = max_users,
+        ScheduleChange::Lesson(name) => {
+            let Some((entry_id, starts_at, ends_at)) =
+                find_lesson(&state, &msg, now, name.as_deref()).await?
+            else {
+                let text = "Сьогодні більше немає такої пари";
+                return reply(&bot, &msg, &state, text.to_string()).await;
+            };
+            queue.entry_id = Some(entry_id);
+            queue.archive_at = Some(ends_at);
+            if queue.opens_at.is_none() && starts_at > now {
+                queue.opens_at = Some(starts_at);
+            }
+        }
+        ScheduleChange::NoLesson => {
+            queue.entry_id = None;
+            queue.archive_at = None;
+        }
+    }
+
+    set_queue_schedule(&state.db, &queue).await?;
+    let users = get_users(&state.db, queue.id).await?;
+    reply(&bot, &msg, &state, ui::queue_ui::schedule_view(&queue)).await?;
+    bot.edit_queue(queue, users).await;
+    Ok(())
+}
+
+/// Opens, closes and archives the queues whose time has come, and redraws them.
+pub async fn queue_schedules(state: State) {
+    let queue_ids = match apply_queue_schedules(&state.db).await {
+        Ok(queue_ids) => queue_ids,
+        Err(err) => {
+            tracing::error!("Failed to apply queue schedules: {:?}", err);
+            return;
+        }
+    };
+    for queue_id in queue_ids {
+        if let Err(err) = state.events.send(Event::RefreshQueue { queue_id }) {
+            tracing::error!("Failed to refresh queue {}: {:?}", queue_id, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn queue() -> QueueModel {
+        QueueModel {
+            id: 1,
+            title: "Лаба 3".to_string(),
+            chat_id: 1,
+            message_id: 1,
+            is_mixed: None,
+            is_priority: false,
+            is_deleted: false,
+            created_at: at(1, 9, 0),
+            opens_at: None,
+            closes_at: None,
+            max_users: None,
+            entry_id: None,
+            archive_at: None,
+            is_closed: false,
+            is_archived: false,
+            lottery_seed: None,
+            lottery_closes_at: None,
+            subject: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_schedule_change() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        assert_eq!(
+            parse_schedule_change(&["open", "14:30"], today),
+            Some(ScheduleChange::OpenAt(at(1, 14, 30)))
+        );
+        assert_eq!(
+            parse_schedule_change(&["close", "2025-10-02", "08:00"], today),
+            Some(ScheduleChange::CloseAt(at(2, 8, 0)))
+        );
+        assert_eq!(
+            parse_schedule_change(&["limit", "20"], today),
+            Some(ScheduleChange::Limit(Some(20)))
+        );
+        assert_eq!(
+            parse_schedule_change(&["lesson", "Бази", "даних"], today),
+            Some(ScheduleChange::Lesson(Some("Бази даних".to_string())))
+        );
+        assert_eq!(parse_schedule_change(&["limit", "0"], today), None);
+        assert_eq!(parse_schedule_change(&["open"], today), None);
+        assert_eq!(parse_schedule_change(&["close", "25:00"], today), None);
+    }
+
+    #[test]
+    fn test_join_rejection() {
+        let now = at(1, 14, 0);
+        assert_eq!(join_rejection(&queue(), 0, now), None);
+
+        let scheduled = QueueModel {
+            opens_at: Some(at(1, 14, 30)),
+            ..queue()
+        };
+        assert_eq!(
+            join_rejection(&scheduled, 0, now),
+            Some("Черга відкриється о 14:30".to_string())
+        );
+        assert_eq!(join_rejection(&scheduled, 0, at(1, 14, 30)), None);
+
+        let full = QueueModel {
+            max_users: Some(2),
+            ..queue()
+        };
+        assert_eq!(join_rejection(&full, 1, now), None);
+        assert!(join_rejection(&full, 2, now).is_some());
+
+        let closing = QueueModel {
+            closes_at: Some(at(1, 14, 0)),
+            ..queue()
+        };
+        assert_eq!(
+            join_rejection(&closing, 0, now),
+            Some("Черга закрита".to_string())
+        );
+    }
+}
@@ -1,22 +1,32 @@
 use crate::bot::handler::HandlerResult;
 use crate::bot::utils::random::get_random_bool;
-use crate::models::gamble::{GambleDto, GambleType};
-use crate::repositories::gamble_repository::insert_gamble;
-use crate::repositories::stats_repository::{get_group_stats, update_balance};
+use crate::models::cleanup::CleanupKind;
+use crate::models::gamble::{GambleDto, GambleType, WheelSpinDto};
+use crate::models::stats::GambleModel;
+use crate::repositories::gamble_repository::{
+    insert_gamble, set_gamble_message_id, settle_wheel_spin,
+};
+use crate::repositories::stats_repository::{get_free_spins, get_group_stats, update_balance};
 use crate::repositories::user_repository::get_user_by_account_id;
 use crate::state::Event;
 use crate::{bot::ui, repositories::stats_repository::get_user_stats, State};
 use crate::{delete_message, param};
+use anyhow::Context;
 use reqwest::Url;
-use teloxide::payloads::{EditMessageReplyMarkupSetters, SendMessageSetters, SendPhotoSetters};
+use teloxide::payloads::{
+    EditMessageReplyMarkupSetters, SendAnimationSetters, SendMessageSetters, SendPhotoSetters,
+};
 use teloxide::prelude::Request;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, InputFile};
 use teloxide::{prelude::Requester, types::Message, Bot};
 
 use super::gifs::get_random_gif;
+use super::wheel::{load_wheel_config, SectorKind, WheelSettings, WHEEL_CONFIG_PATH};
 
 pub async fn stats(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let users_stats = get_group_stats(&state.db, msg.chat.id).await?;
+    let users_stats = get_group_stats(&state.db, msg.chat.id)
+        .await?
+        .context(format!("Chat not found for chat_id: {}", msg.chat.id))?;
     let res = ui::stats_ui::group_stats(users_stats);
     let new_msg = bot
         .send_message(msg.chat.id, &res)
@@ -24,8 +34,8 @@ pub async fn stats(bot: Bot, msg: Message, state: State) -> HandlerResult {
         .send()
         .await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Stats);
+    delete_message!(state, new_msg, CleanupKind::Stats);
     Ok(())
 }
 
@@ -45,8 +55,8 @@ pub async fn casino(bot: Bot, msg: Message, state: State) -> HandlerResult {
         .send()
         .await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Stats);
+    delete_message!(state, new_msg, CleanupKind::Stats);
 
     Ok(())
 }
@@ -55,9 +65,10 @@ pub async fn me(bot: Bot, msg: Message, state: State) -> HandlerResult {
     let user_id = msg.from.as_ref().unwrap().id;
     let stats = get_user_stats(&state.db, msg.from.unwrap().id).await?;
     let res = ui::stats_ui::short_stats(stats);
-    if let Err(e) = state.sender.send(Event::DeleteMessage {
+    if let Err(e) = state.events.send(Event::DeleteMessage {
         chat_id: msg.chat.id,
         message_id: msg.id,
+        kind: CleanupKind::Stats,
     }) {
         eprintln!("Failed to send delete message event: {:?}", e);
     }
@@ -80,14 +91,49 @@ pub async fn me(bot: Bot, msg: Message, state: State) -> HandlerResult {
         .send()
         .await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, sent_msg);
+    delete_message!(state, msg, CleanupKind::Stats);
+    delete_message!(state, sent_msg, CleanupKind::Stats);
 
     Ok(())
 }
 
-pub async fn wheel(bot: Bot, msg: Message, _state: State) -> HandlerResult {
-    bot.send_message(msg.chat.id, "Wheel command").await?;
+pub async fn wheel(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    let config = match load_wheel_config(WHEEL_CONFIG_PATH) {
+        Ok(config) => config,
+        Err(_) => {
+            bot.send_message(msg.chat.id, "Колесо фортуни зламалося, спробуйте пізніше")
+                .await?;
+            return Ok(());
+        }
+    };
+    let wheel = config.for_chat(msg.chat.id);
+
+    let result = spin_wheel(&state, &msg, wheel).await;
+    if let Err(error) = result {
+        bot.send_message(msg.chat.id, error.to_string()).await?;
+        return Ok(());
+    }
+    let gamble = result.unwrap();
+
+    let new_msg = bot
+        .send_animation(
+            msg.chat.id,
+            InputFile::url(Url::parse(&wheel.img_url).unwrap()),
+        )
+        .caption(ui::stats_ui::wheel_spinning(gamble.bet))
+        .send()
+        .await?;
+
+    set_gamble_message_id(&state.db, gamble.id, new_msg.id).await?;
+
+    state.events.send(Event::GambleResult {
+        chat_id: msg.chat.id,
+        gamble_id: gamble.id,
+    })?;
+
+    delete_message!(state, msg, CleanupKind::Gamble);
+    delete_message!(state, new_msg, CleanupKind::Gamble);
+
     Ok(())
 }
 
@@ -112,13 +158,13 @@ pub async fn gamble(bot: Bot, msg: Message, state: State) -> HandlerResult {
 
     let gamble = insert_gamble(&state.db, result).await?;
 
-    state.sender.send(Event::GambleResult {
+    state.events.send(Event::GambleResult {
         chat_id: msg.chat.id,
         gamble_id: gamble.id,
     })?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Gamble);
+    delete_message!(state, new_msg, CleanupKind::Gamble);
 
     Ok(())
 }
@@ -142,13 +188,13 @@ pub async fn gamble_all(bot: Bot, msg: Message, state: State) -> HandlerResult {
 
     let gamble = insert_gamble(&state.db, result).await?;
 
-    state.sender.send(Event::GambleResult {
+    state.events.send(Event::GambleResult {
         chat_id: msg.chat.id,
         gamble_id: gamble.id,
     })?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Gamble);
+    delete_message!(state, new_msg, CleanupKind::Gamble);
 
     Ok(())
 }
@@ -195,5 +241,64 @@ async fn make_bet(state: &State, msg: &Message, amount: Amount) -> anyhow::Resul
         change,
         bet: amount as i32,
         gamble_type: GambleType::Bet,
+        outcome: None,
     })
 }
+
+async fn spin_wheel(
+    state: &State,
+    msg: &Message,
+    wheel: &WheelSettings,
+) -> anyhow::Result<GambleModel> {
+    let user = msg.from.as_ref().unwrap();
+    let stored_user = get_user_by_account_id(state, user.id).await?;
+    let user_stats = get_user_stats(&state.db, user.id).await?;
+
+    let used_free_spin = get_free_spins(&state.db, stored_user.id).await? > 0;
+    let cost = if used_free_spin {
+        0
+    } else if user_stats.balance < wheel.cost {
+        return Err(anyhow::anyhow!("Недостатньо коштів"));
+    } else {
+        wheel.cost
+    };
+
+    let sector = wheel.spin()?;
+
+    let mut won_free_spin = false;
+    let mut limit_boost = 0;
+    let (change, is_win) = match sector.kind {
+        SectorKind::Multiplier { multiplier } => {
+            let payout = (wheel.cost as f32 * multiplier) as i32;
+            (payout - cost, payout > cost)
+        }
+        SectorKind::LoseEverything => (-user_stats.balance, false),
+        SectorKind::ExtraSpin => {
+            won_free_spin = true;
+            (-cost, true)
+        }
+        SectorKind::LimitBoost { amount } => {
+            limit_boost = amount;
+            (-cost, true)
+        }
+    };
+
+    settle_wheel_spin(
+        &state.db,
+        WheelSpinDto {
+            gamble: GambleDto {
+                user_id: stored_user.id,
+                message_id: msg.id,
+                is_win,
+                change,
+                bet: cost,
+                gamble_type: GambleType::Wheel,
+                outcome: Some(sector.title.clone()),
+            },
+            used_free_spin,
+            won_free_spin,
+            limit_boost,
+        },
+    )
+    .await
+}
@@ -0,0 +1,40 @@
+use teloxide::types::ChatId;
+
+use crate::{
+    bot::utils::time::get_local_time,
+    config::daily_limits::DailyLimitConfig,
+    repositories::{chat_repository::get_chats, stats_repository::reset_daily_limits},
+    state::State,
+};
+
+pub async fn daily_limit_reset(state: State) {
+    let chats = match get_chats(&state.db).await {
+        Ok(chats) => chats,
+        Err(err) => {
+            tracing::error!("Failed to get chats for daily reset: {:?}", err);
+            return;
+        }
+    };
+    let config = DailyLimitConfig::from_env();
+
+    for chat in chats {
+        let local_date = get_local_time(&chat.timezone).date_naive();
+        match reset_daily_limits(&state.db, ChatId(chat.chat_id), local_date, &config).await {
+            Ok(true) => {
+                tracing::info!(
+                    "Daily limits reset for chat {} ({})",
+                    chat.chat_id,
+                    local_date
+                );
+            }
+            Ok(false) => {}
+            Err(err) => {
+                tracing::error!(
+                    "Failed to reset daily limits for chat {}: {:?}",
+                    chat.chat_id,
+                    err
+                );
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod commands;
+pub mod daily_reset;
 pub mod gifs;
 pub mod reactions;
+pub mod wheel;
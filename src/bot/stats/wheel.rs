@@ -0,0 +1,126 @@
+use std::{collections::HashMap, fs};
+
+use rand::distributions::{Distribution, WeightedIndex};
+use serde::Deserialize;
+use teloxide::types::ChatId;
+
+pub const WHEEL_CONFIG_PATH: &str = "wheel-config.toml";
+
+#[derive(Deserialize)]
+pub struct WheelConfig {
+    default: WheelSettings,
+    #[serde(default)]
+    chats: HashMap<String, WheelSettings>,
+}
+
+#[derive(Deserialize)]
+pub struct WheelSettings {
+    pub cost: i32,
+    pub img_url: String,
+    pub sectors: Vec<Sector>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Sector {
+    pub title: String,
+    pub weight: u32,
+    #[serde(flatten)]
+    pub kind: SectorKind,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SectorKind {
+    Multiplier { multiplier: f32 },
+    LoseEverything,
+    ExtraSpin,
+    LimitBoost { amount: i32 },
+}
+
+impl WheelConfig {
+    pub fn for_chat(&self, chat_id: ChatId) -> &WheelSettings {
+        self.chats
+            .get(&chat_id.to_string())
+            .unwrap_or(&self.default)
+    }
+}
+
+impl WheelSettings {
+    pub fn spin(&self) -> anyhow::Result<&Sector> {
+        let weights = WeightedIndex::new(self.sectors.iter().map(|sector| sector.weight))?;
+        Ok(&self.sectors[weights.sample(&mut rand::thread_rng())])
+    }
+}
+
+pub fn load_wheel_config(file_path: &str) -> anyhow::Result<WheelConfig> {
+    let config_data = fs::read_to_string(file_path).map_err(|err| {
+        tracing::error!("Failed to read the wheel configuration file: {:?}", err);
+        anyhow::anyhow!(err)
+    })?;
+    let config: WheelConfig = toml::from_str(&config_data).map_err(|err| {
+        tracing::error!("Failed to parse wheel configuration: {:?}", err);
+        anyhow::anyhow!(err)
+    })?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_has_every_sector_kind() {
+        let config = load_wheel_config(WHEEL_CONFIG_PATH).unwrap();
+        let wheel = config.for_chat(ChatId(0));
+
+        assert!(wheel.cost > 0);
+        assert!(wheel
+            .sectors
+            .iter()
+            .any(|s| matches!(s.kind, SectorKind::Multiplier { .. })));
+        assert!(wheel
+            .sectors
+            .iter()
+            .any(|s| s.kind == SectorKind::LoseEverything));
+        assert!(wheel
+            .sectors
+            .iter()
+            .any(|s| s.kind == SectorKind::ExtraSpin));
+        assert!(wheel
+            .sectors
+            .iter()
+            .any(|s| matches!(s.kind, SectorKind::LimitBoost { .. })));
+    }
+
+    #[test]
+    fn test_chat_override() {
+        let config: WheelConfig = toml::from_str(
+            r#"
+            [default]
+            cost = 10
+            img_url = "https://example.com/wheel.gif"
+            [[default.sectors]]
+            title = "x2"
+            kind = "multiplier"
+            multiplier = 2.0
+            weight = 1
+
+            [chats."-100"]
+            cost = 99
+            img_url = "https://example.com/wheel.gif"
+            [[chats."-100".sectors]]
+            title = "bankrupt"
+            kind = "lose_everything"
+            weight = 1
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.for_chat(ChatId(1)).cost, 10);
+        assert_eq!(config.for_chat(ChatId(-100)).cost, 99);
+        assert_eq!(
+            config.for_chat(ChatId(-100)).spin().unwrap().kind,
+            SectorKind::LoseEverything
+        );
+    }
+}
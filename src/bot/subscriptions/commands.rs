@@ -0,0 +1,181 @@
+use chrono::NaiveTime;
+use teloxide::{prelude::Requester, types::Message, Bot};
+
+use crate::{
+    bot::{handler::HandlerResult, ui},
+    delete_message,
+    models::{cleanup::CleanupKind, subscription::SubscriptionKind},
+    repositories::subscription_repository::{
+        get_quiet_hours, get_subscriptions, set_quiet_hours, subscribe as insert_subscription,
+        unsubscribe as delete_subscriptions,
+    },
+    state::State,
+};
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    if !msg.chat.is_private() {
+        delete_message!(state, new_msg, CleanupKind::General);
+    }
+    Ok(())
+}
+
+fn command_params(msg: &Message) -> Vec<&str> {
+    msg.text()
+        .map(|text| text.split_whitespace().skip(1).collect())
+        .unwrap_or_default()
+}
+
+/// No parameter means every kind; `None` is returned for unknown kinds.
+fn parse_kinds(params: &[&str]) -> Option<Vec<SubscriptionKind>> {
+    match params {
+        [] => Some(SubscriptionKind::ALL.to_vec()),
+        [kind] => Some(vec![SubscriptionKind::try_from(*kind).ok()?]),
+        _ => None,
+    }
+}
+
+fn parse_quiet_hours(params: &[&str]) -> Option<Option<(NaiveTime, NaiveTime)>> {
+    match params {
+        ["off"] => Some(None),
+        [starts_at, ends_at] => {
+            let starts_at = NaiveTime::parse_from_str(starts_at, "%H:%M").ok()?;
+            let ends_at = NaiveTime::parse_from_str(ends_at, "%H:%M").ok()?;
+            (starts_at != ends_at).then_some(Some((starts_at, ends_at)))
+        }
+        _ => None,
+    }
+}
+
+/// `/subscribe [lessons|queue|deadlines]` in a group forwards its notifications
+/// to the private chat of the user.
+pub async fn subscribe(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    if !msg.chat.is_private() {
+        delete_message!(state, msg, CleanupKind::General);
+    }
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    if msg.chat.is_private() {
+        let text = "Надішліть /subscribe у групі, сповіщення якої хочете отримувати";
+        return reply(&bot, &msg, &state, text.to_string()).await;
+    }
+    let Some(kinds) = parse_kinds(&command_params(&msg)) else {
+        let usage = "Використання: /subscribe [lessons|queue|deadlines]";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    for kind in kinds.iter() {
+        insert_subscription(&state.db, user.id, msg.chat.id, *kind).await?;
+    }
+
+    let labels: Vec<&str> = kinds
+        .iter()
+        .map(|kind| ui::subscription_ui::kind_label(*kind))
+        .collect();
+    let bot_username = bot.get_me().await?.user.username.unwrap_or_default();
+    let text = format!(
+        "{}, надсилатиму вам в особисті: {}\nЯкщо ви ще не писали мені, відкрийте @{} і натисніть Start",
+        user.first_name,
+        labels.join(", "),
+        bot_username
+    );
+    reply(&bot, &msg, &state, text).await
+}
+
+/// `/unsubscribe [kind]` stops notifications of the group, or of every group
+/// when sent in the private chat.
+pub async fn unsubscribe(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    if !msg.chat.is_private() {
+        delete_message!(state, msg, CleanupKind::General);
+    }
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    let params = command_params(&msg);
+    let kind = match params.as_slice() {
+        [] => None,
+        [kind] => match SubscriptionKind::try_from(*kind) {
+            Ok(kind) => Some(kind),
+            Err(_) => {
+                let usage = "Використання: /unsubscribe [lessons|queue|deadlines]";
+                return reply(&bot, &msg, &state, usage.to_string()).await;
+            }
+        },
+        _ => {
+            let usage = "Використання: /unsubscribe [lessons|queue|deadlines]";
+            return reply(&bot, &msg, &state, usage.to_string()).await;
+        }
+    };
+
+    let chat_id = (!msg.chat.is_private()).then_some(msg.chat.id);
+    let removed = delete_subscriptions(&state.db, user.id, chat_id, kind).await?;
+    let text = if removed > 0 {
+        "Підписку скасовано"
+    } else {
+        "Такої підписки немає"
+    };
+    reply(&bot, &msg, &state, text.to_string()).await
+}
+
+pub async fn subscriptions(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    if !msg.chat.is_private() {
+        delete_message!(state, msg, CleanupKind::General);
+    }
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+
+    let subscriptions = get_subscriptions(&state.db, user.id).await?;
+    let quiet_hours = get_quiet_hours(&state.db, user.id).await?;
+    reply(
+        &bot,
+        &msg,
+        &state,
+        ui::subscription_ui::subscriptions_view(&subscriptions, quiet_hours.as_ref()),
+    )
+    .await
+}
+
+/// `/quiet_hours <HH:MM> <HH:MM>` delivers private notifications without sound
+/// in that range; `/quiet_hours off` turns it off.
+pub async fn quiet_hours(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    if !msg.chat.is_private() {
+        delete_message!(state, msg, CleanupKind::General);
+    }
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    let Some(hours) = parse_quiet_hours(&command_params(&msg)) else {
+        let usage = "Використання: /quiet_hours <ГГ:ХХ> <ГГ:ХХ>, наприклад /quiet_hours 23:00 08:00, або /quiet_hours off";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    set_quiet_hours(&state.db, user.id, hours).await?;
+    let text = match hours {
+        Some((starts_at, ends_at)) => format!(
+            "Тихі години: {}–{}. У цей час сповіщення приходитимуть без звуку",
+            starts_at.format("%H:%M"),
+            ends_at.format("%H:%M")
+        ),
+        None => "Тихі години вимкнено".to_string(),
+    };
+    reply(&bot, &msg, &state, text).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quiet_hours() {
+        let time = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+        assert_eq!(
+            parse_quiet_hours(&["23:00", "08:00"]),
+            Some(Some((time(23), time(8))))
+        );
+        assert_eq!(parse_quiet_hours(&["off"]), Some(None));
+        assert_eq!(parse_quiet_hours(&["23:00", "23:00"]), None);
+        assert_eq!(parse_quiet_hours(&["23:00"]), None);
+    }
+}
@@ -0,0 +1,98 @@
+pub mod commands;
+
+use chrono::NaiveTime;
+use teloxide::types::{ChatId, UserId};
+
+use crate::{
+    models::subscription::{QuietHoursModel, SubscriptionKind},
+    repositories::{chat_repository::get_calendar, subscription_repository::get_subscribers},
+    state::{Event, State},
+};
+
+/// Whether `time` falls into the quiet hours, which may wrap past midnight.
+pub fn is_quiet(quiet_hours: &QuietHoursModel, time: NaiveTime) -> bool {
+    if quiet_hours.starts_at <= quiet_hours.ends_at {
+        quiet_hours.starts_at <= time && time < quiet_hours.ends_at
+    } else {
+        time >= quiet_hours.starts_at || time < quiet_hours.ends_at
+    }
+}
+
+/// Forwards a notification of the chat to the private chats of its subscribers.
+/// Quiet hours are checked in the chat's timezone; notifications sent during
+/// them arrive without sound.
+pub async fn notify_subscribers(
+    state: &State,
+    chat_id: ChatId,
+    kind: SubscriptionKind,
+    text: &str,
+) -> anyhow::Result<()> {
+    notify(state, chat_id, kind, None, text).await
+}
+
+/// Like [`notify_subscribers`], for a single user of the chat.
+pub async fn notify_subscriber(
+    state: &State,
+    chat_id: ChatId,
+    kind: SubscriptionKind,
+    account_id: i64,
+    text: &str,
+) -> anyhow::Result<()> {
+    notify(state, chat_id, kind, Some(account_id), text).await
+}
+
+async fn notify(
+    state: &State,
+    chat_id: ChatId,
+    kind: SubscriptionKind,
+    account_id: Option<i64>,
+    text: &str,
+) -> anyhow::Result<()> {
+    let subscribers = get_subscribers(&state.db, chat_id, kind, account_id).await?;
+    if subscribers.is_empty() {
+        return Ok(());
+    }
+
+    let now = get_calendar(&state.db, chat_id).await?.now().time();
+    for subscriber in subscribers {
+        state.events.send(Event::PrivateNotification {
+            user_id: UserId(subscriber.account_id as u64),
+            text: format!("📣 {}\n{}", subscriber.chat_title, text),
+            silent: subscriber
+                .quiet_hours
+                .is_some_and(|quiet_hours| is_quiet(&quiet_hours, now)),
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn quiet_hours(starts_at: NaiveTime, ends_at: NaiveTime) -> QuietHoursModel {
+        QuietHoursModel {
+            account_id: 1,
+            starts_at,
+            ends_at,
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_past_midnight() {
+        let night = quiet_hours(time(23, 0), time(8, 0));
+        assert!(is_quiet(&night, time(23, 30)));
+        assert!(is_quiet(&night, time(2, 0)));
+        assert!(!is_quiet(&night, time(8, 0)));
+        assert!(!is_quiet(&night, time(12, 0)));
+
+        let lunch = quiet_hours(time(13, 0), time(14, 0));
+        assert!(is_quiet(&lunch, time(13, 30)));
+        assert!(!is_quiet(&lunch, time(23, 30)));
+    }
+}
@@ -0,0 +1,207 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::models::{
+    chat::HolidayModel,
+    timetable::{TimetableEntryModel, TimetableOverrideModel},
+};
+
+use super::{Day, Week};
+
+/// Chat-local view of time used by every timetable lookup.
+#[derive(Debug, Clone)]
+pub struct AcademicCalendar {
+    pub timezone: Tz,
+    /// Any day of the first week of the semester; that week is [`Week::First`].
+    pub semester_start: Option<NaiveDate>,
+    pub holidays: Vec<HolidayModel>,
+}
+
+impl AcademicCalendar {
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.timezone)
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    /// Without a semester start, odd ISO weeks are counted as the first week.
+    pub fn week_of(&self, date: NaiveDate) -> Week {
+        let index = match self.semester_start {
+            Some(start) => (date - monday_of(start)).num_days().div_euclid(7),
+            None => date.iso_week().week() as i64 - 1,
+        };
+        match index.rem_euclid(2) {
+            0 => Week::First,
+            _ => Week::Second,
+        }
+    }
+
+    pub fn day_of(date: NaiveDate) -> Day {
+        date.weekday().into()
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays
+            .iter()
+            .any(|holiday| holiday.starts_on <= date && date <= holiday.ends_on)
+    }
+
+    /// Lessons taking place on `date`, ordered by time. Cancelled lessons are
+    /// left out and moved ones appear on their new date with the new time.
+    pub fn lessons_on(
+        &self,
+        entries: &[TimetableEntryModel],
+        overrides: &[TimetableOverrideModel],
+        date: NaiveDate,
+    ) -> Vec<TimetableEntryModel> {
+        let mut lessons = Vec::new();
+        if !self.is_holiday(date) {
+            let week = self.week_of(date) as i32;
+            let day = Self::day_of(date) as i32;
+            lessons.extend(
+                entries
+                    .iter()
+                    .filter(|entry| entry.week == week && entry.day == day)
+                    .filter(|entry| {
+                        !overrides.iter().any(|override_| {
+                            override_.entry_id == entry.id && override_.class_date == date
+                        })
+                    })
+                    .cloned(),
+            );
+        }
+
+        for override_ in overrides {
+            let Some(moved_to) = override_
+                .moved_to
+                .filter(|moved_to| moved_to.date() == date)
+            else {
+                continue;
+            };
+            if let Some(entry) = entries.iter().find(|entry| entry.id == override_.entry_id) {
+                lessons.push(TimetableEntryModel {
+                    class_time: moved_to.time(),
+                    ..entry.clone()
+                });
+            }
+        }
+
+        lessons.sort_by_key(|entry| entry.class_time);
+        lessons
+    }
+
+    /// Date of `day` in the week containing `date`.
+    pub fn date_in_week(date: NaiveDate, day: Day) -> NaiveDate {
+        monday_of(date) + Duration::days(u8::from(day) as i64)
+    }
+}
+
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn calendar(semester_start: Option<NaiveDate>) -> AcademicCalendar {
+        AcademicCalendar {
+            timezone: chrono_tz::Europe::Kyiv,
+            semester_start,
+            holidays: vec![HolidayModel {
+                id: 1,
+                chat_id: 1,
+                starts_on: date(2025, 12, 29),
+                ends_on: date(2026, 1, 11),
+                title: "Зимові канікули".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_week_parity_continues_across_new_year() {
+        let calendar = calendar(Some(date(2025, 9, 1)));
+
+        assert_eq!(calendar.week_of(date(2025, 9, 1)), Week::First);
+        assert_eq!(calendar.week_of(date(2025, 9, 7)), Week::First);
+        assert_eq!(calendar.week_of(date(2025, 9, 8)), Week::Second);
+        // 2025-12-29 is the 18th week of the semester, 2026-01-05 the 19th.
+        assert_eq!(calendar.week_of(date(2025, 12, 29)), Week::Second);
+        assert_eq!(calendar.week_of(date(2026, 1, 5)), Week::First);
+    }
+
+    #[test]
+    fn test_semester_start_mid_week() {
+        let calendar = calendar(Some(date(2026, 2, 4)));
+
+        assert_eq!(calendar.week_of(date(2026, 2, 2)), Week::First);
+        assert_eq!(calendar.week_of(date(2026, 2, 9)), Week::Second);
+    }
+
+    #[test]
+    fn test_lessons_with_overrides() {
+        let calendar = calendar(Some(date(2025, 9, 1)));
+        let entry = |id: i32, day: i32, hour: u32| TimetableEntryModel {
+            id,
+            week: 0,
+            day,
+            timetable_id: 1,
+            class_name: format!("Пара {}", id),
+            class_type: "lec".to_string(),
+            class_time: NaiveTime::from_hms_opt(hour, 0, 0).unwrap(),
+            link: None,
+            subgroup: String::new(),
+        };
+        let entries = vec![entry(1, 0, 10), entry(2, 0, 12), entry(3, 2, 8)];
+        let overrides = vec![
+            TimetableOverrideModel {
+                id: 1,
+                chat_id: 1,
+                entry_id: 2,
+                class_date: date(2025, 9, 1),
+                moved_to: None,
+            },
+            TimetableOverrideModel {
+                id: 2,
+                chat_id: 1,
+                entry_id: 3,
+                class_date: date(2025, 9, 3),
+                moved_to: date(2025, 9, 1).and_hms_opt(8, 0, 0),
+            },
+        ];
+
+        let monday = calendar.lessons_on(&entries, &overrides, date(2025, 9, 1));
+        assert_eq!(
+            monday.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            vec![3, 1]
+        );
+        assert!(calendar
+            .lessons_on(&entries, &overrides, date(2025, 9, 3))
+            .is_empty());
+        // Overrides only apply to their date.
+        assert_eq!(
+            calendar
+                .lessons_on(&entries, &overrides, date(2025, 9, 15))
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_holidays() {
+        let calendar = calendar(None);
+
+        assert!(!calendar.is_holiday(date(2025, 12, 28)));
+        assert!(calendar.is_holiday(date(2025, 12, 29)));
+        assert!(calendar.is_holiday(date(2026, 1, 11)));
+        assert!(!calendar.is_holiday(date(2026, 1, 12)));
+    }
+}
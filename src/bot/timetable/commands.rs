@@ -1,69 +1,167 @@
-use crate::{bot::handler::HandlerResult, delete_message, param, redis::RedisCache};
+use crate::{bot::handler::HandlerResult, delete_message, redis::RedisCache};
 use reqwest::Url;
 use teloxide::{
-    payloads::SendMessageSetters,
+    net::Download,
+    payloads::{SendDocumentSetters, SendMessageSetters},
     prelude::Requester,
-    types::{InlineKeyboardButton, InlineKeyboardMarkup, LinkPreviewOptions, Message},
+    types::{
+        ChatId, Document, InlineKeyboardButton, InlineKeyboardMarkup, InputFile,
+        LinkPreviewOptions, Message,
+    },
     Bot,
 };
 
 use crate::{
-    bot::ui::{self},
-    repositories::timetable_repository::{
-        get_current_entry, get_full_timetable, get_next_entry, get_today_timetable,
-        get_tomorrow_timetable, get_week_timetable, import_timetable,
+    bot::{
+        timetable::{
+            editor::TimetableMarkupExt,
+            export::{feed_url, render_ics},
+            sources::{kpi::KpiCampusSource, source_for_file, TimetableSource, MAX_FILE_SIZE},
+            subgroups::{entries_for_sender, parse_subgroup_name},
+            sync::KPI_SOURCE,
+        },
+        ui::{self},
+        utils::{permissions::is_privileged, reply_markup_builder::ReplyMarkupBuilder},
+    },
+    models::cleanup::CleanupKind,
+    repositories::{
+        chat_repository::{get_calendar, get_or_create_calendar_token, rotate_calendar_token},
+        timetable_repository::{
+            get_full_timetable, get_started_entries, get_today_timetable, get_tomorrow_timetable,
+            get_upcoming_entries, get_week_timetable, import_timetable, set_timetable_source,
+        },
     },
     State,
 };
 
+/// `/import <group> [subgroup]` loads the group's schedule from KPI Campus and
+/// keeps it in sync. Replying `/import [subgroup]` to an .ics, .csv or .toml
+/// document imports that file instead. Without a subgroup the schedule is for
+/// the whole chat.
 pub async fn import(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let group_name = param!(
-        bot,
-        msg,
-        state,
-        String,
-        "Вкажіть назву групи, наприклад ІП-32"
-    );
+    let params: Vec<&str> = msg
+        .text()
+        .map(|text| text.split_whitespace().skip(1).collect())
+        .unwrap_or_default();
+    let document = msg.reply_to_message().and_then(Message::document);
+    let subgroup_param = match document {
+        Some(_) => params.first(),
+        None => params.get(1),
+    };
+    let subgroup = match subgroup_param {
+        Some(name) => match parse_subgroup_name(name) {
+            Some(subgroup) => subgroup,
+            None => {
+                let text = "Назва підгрупи має бути одним словом до 16 літер, цифр або дефісів";
+                return send_import_result(&bot, &msg, &state, text.to_string()).await;
+            }
+        },
+        None => String::new(),
+    };
 
-    let group_api_result = state
-        .http_client
-        .get("https://api.campus.kpi.ua/group/find")
-        .query(&[("name", &group_name)])
-        .send()
-        .await?;
-    let group_json = group_api_result.json::<serde_json::Value>().await?;
-    let group_id = group_json
-        .as_array()
-        .and_then(|array| array.iter().find(|x| x["name"] == group_name))
-        .and_then(|group| group.get("id"))
-        .and_then(|id| id.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Виникла помилка під час імпортування розкладу"))?;
-    let timetable_api_result = state
-        .http_client
-        .get("https://api.campus.kpi.ua/schedule/lessons")
-        .query(&[
-            ("groupId", group_id.to_string()),
-            ("groupName", group_name.to_string()),
-        ])
-        .send()
-        .await?;
-    let timetable = timetable_api_result.json::<serde_json::Value>().await?;
+    let (source, group_name): (Box<dyn TimetableSource>, _) = match document {
+        Some(document) => match file_source(&bot, &state, msg.chat.id, document).await? {
+            Some(source) => (source, None),
+            None => {
+                let text = "Підтримуються файли .ics, .csv та .toml розміром до 512 КБ";
+                return send_import_result(&bot, &msg, &state, text.to_string()).await;
+            }
+        },
+        None => {
+            let Some(group_name) = params.first().map(|name| name.to_string()) else {
+                let text = "Вкажіть назву групи, наприклад ІП-32, або дайте відповідь /import на файл розкладу. Щоб імпортувати розклад підгрупи, додайте її назву: /import ІП-32 1";
+                return send_import_result(&bot, &msg, &state, text.to_string()).await;
+            };
+            (
+                Box::new(KpiCampusSource::new(
+                    state.http_client.clone(),
+                    group_name.clone(),
+                )),
+                Some(group_name),
+            )
+        }
+    };
 
-    import_timetable(&state.db, msg.chat.id.0, timetable).await?;
-    state.redis.clear_timetable_entries(msg.chat.id)?;
+    let text = match source.fetch().await {
+        Ok(entries) if entries.is_empty() => {
+            "Не знайдено жодного заняття, розклад не змінено".to_string()
+        }
+        Ok(entries) => {
+            let diff = import_timetable(&state.db, msg.chat.id.0, &subgroup, &entries).await?;
+            set_timetable_source(
+                &state.db,
+                msg.chat.id,
+                &subgroup,
+                KPI_SOURCE,
+                group_name.as_deref(),
+            )
+            .await?;
+            state.redis.clear_timetable_entries(msg.chat.id)?;
+            let imported = if subgroup.is_empty() {
+                "Розклад успішно імпортовано ✅".to_string()
+            } else {
+                format!("Розклад підгрупи {} успішно імпортовано ✅", subgroup)
+            };
+            match (diff.is_empty(), group_name) {
+                (true, _) => "Розклад вже актуальний ✅".to_string(),
+                (false, Some(group_name)) => format!(
+                    "{}\nЗміни розкладу групи {} будуть підтягуватися автоматично",
+                    imported, group_name
+                ),
+                (false, None) => imported,
+            }
+        }
+        Err(err) => {
+            tracing::warn!("Failed to import timetable: {:?}", err);
+            format!("Не вдалося імпортувати розклад: {}", err)
+        }
+    };
 
-    let new_msg = bot
-        .send_message(msg.chat.id, "Розклад успішно імпортовано ✅")
-        .await?;
+    send_import_result(&bot, &msg, &state, text).await
+}
+
+async fn file_source(
+    bot: &Bot,
+    state: &State,
+    chat_id: ChatId,
+    document: &Document,
+) -> anyhow::Result<Option<Box<dyn TimetableSource>>> {
+    let Some(file_name) = document.file_name.as_deref() else {
+        return Ok(None);
+    };
+    if document.file.size > MAX_FILE_SIZE {
+        return Ok(None);
+    }
+
+    let file = bot.get_file(document.file.id.clone()).await?;
+    let mut content = Vec::new();
+    bot.download_file(&file.path, &mut content).await?;
+    let calendar = get_calendar(&state.db, chat_id).await?;
+
+    Ok(source_for_file(
+        file_name,
+        String::from_utf8_lossy(&content).into_owned(),
+        calendar,
+    ))
+}
+
+async fn send_import_result(
+    bot: &Bot,
+    msg: &Message,
+    state: &State,
+    text: String,
+) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Timetable);
+    delete_message!(state, new_msg, CleanupKind::Timetable);
 
     Ok(())
 }
 
 pub async fn today(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let entries = get_today_timetable(&state.db, &msg.chat.id.to_string()).await?;
+    let entries = get_today_timetable(&state.db, msg.chat.id).await?;
+    let entries = entries_for_sender(&state, &msg, entries).await?;
     let res = ui::timetable_ui::day_view(entries);
 
     let new_msg = bot
@@ -72,14 +170,15 @@ pub async fn today(bot: Bot, msg: Message, state: State) -> HandlerResult {
         .link_preview_options(DISABLED_LINK_PREVIEW_OPTIONS)
         .await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Timetable);
+    delete_message!(state, new_msg, CleanupKind::Timetable);
 
     Ok(())
 }
 
 pub async fn tomorrow(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let entries = get_tomorrow_timetable(&state.db, &msg.chat.id.to_string()).await?;
+    let entries = get_tomorrow_timetable(&state.db, msg.chat.id).await?;
+    let entries = entries_for_sender(&state, &msg, entries).await?;
     let res = ui::timetable_ui::day_view(entries);
 
     let new_msg = bot
@@ -88,13 +187,14 @@ pub async fn tomorrow(bot: Bot, msg: Message, state: State) -> HandlerResult {
         .link_preview_options(DISABLED_LINK_PREVIEW_OPTIONS)
         .await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Timetable);
+    delete_message!(state, new_msg, CleanupKind::Timetable);
     Ok(())
 }
 
 pub async fn week(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let entries = get_week_timetable(&state.db, &msg.chat.id.to_string()).await?;
+    let entries = get_week_timetable(&state.db, msg.chat.id).await?;
+    let entries = entries_for_sender(&state, &msg, entries).await?;
     let res = ui::timetable_ui::week_view(entries);
 
     let new_msg = bot
@@ -103,28 +203,30 @@ pub async fn week(bot: Bot, msg: Message, state: State) -> HandlerResult {
         .link_preview_options(DISABLED_LINK_PREVIEW_OPTIONS)
         .await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Timetable);
+    delete_message!(state, new_msg, CleanupKind::Timetable);
     Ok(())
 }
 
 pub async fn edit_timetable(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let entries = get_full_timetable(&state.db, &msg.chat.id.to_string()).await?;
-    let res = ui::timetable_ui::edit_view(entries);
+    let entries = get_full_timetable(&state.db, msg.chat.id).await?;
+    let res = ui::timetable_ui::edit_view(entries.clone());
 
     let new_msg = bot
         .send_message(msg.chat.id, res)
         .parse_mode(teloxide::types::ParseMode::MarkdownV2)
         .link_preview_options(DISABLED_LINK_PREVIEW_OPTIONS)
+        .reply_markup(ReplyMarkupBuilder::timetable_editor_markup(&entries))
         .await?;
 
-    delete_message!(state, msg);
-    delete_message!(state, new_msg);
+    delete_message!(state, msg, CleanupKind::Timetable);
+    delete_message!(state, new_msg, CleanupKind::Timetable);
     Ok(())
 }
 
 pub async fn now(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let entry = get_current_entry(&state.db, &msg.chat.id.to_string()).await?;
+    let entries = get_started_entries(&state.db, msg.chat.id).await?;
+    let entry = entries_for_sender(&state, &msg, entries).await?.pop();
     let res = ui::timetable_ui::entry_view(entry.clone());
     let bot_username = bot.get_me().await?.user.username.unwrap();
 
@@ -148,21 +250,25 @@ pub async fn now(bot: Bot, msg: Message, state: State) -> HandlerResult {
             ]]))
             .await?;
 
-        delete_message!(state, new_msg);
+        delete_message!(state, new_msg, CleanupKind::Timetable);
     } else {
         let new_msg = bot
             .send_message(msg.chat.id, res)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
             .await?;
-        delete_message!(state, new_msg);
+        delete_message!(state, new_msg, CleanupKind::Timetable);
     }
 
-    delete_message!(state, msg);
+    delete_message!(state, msg, CleanupKind::Timetable);
     Ok(())
 }
 
 pub async fn next(bot: Bot, msg: Message, state: State) -> HandlerResult {
-    let entry = get_next_entry(&state.db, &msg.chat.id.to_string()).await?;
+    let entries = get_upcoming_entries(&state.db, msg.chat.id).await?;
+    let entry = entries_for_sender(&state, &msg, entries)
+        .await?
+        .into_iter()
+        .next();
     let res = ui::timetable_ui::entry_view(entry.clone());
     let bot_username = bot.get_me().await?.user.username.unwrap();
 
@@ -186,15 +292,73 @@ pub async fn next(bot: Bot, msg: Message, state: State) -> HandlerResult {
             ]]))
             .await?;
 
-        delete_message!(state, new_msg);
+        delete_message!(state, new_msg, CleanupKind::Timetable);
     } else {
         let new_msg = bot
             .send_message(msg.chat.id, res)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
             .await?;
-        delete_message!(state, new_msg);
+        delete_message!(state, new_msg, CleanupKind::Timetable);
     }
-    delete_message!(state, msg);
+    delete_message!(state, msg, CleanupKind::Timetable);
+    Ok(())
+}
+
+/// `/export_ics` sends the timetable as a calendar file along with a feed link
+/// calendar apps can subscribe to. `/export_ics reset` replaces the feed link.
+pub async fn export_ics(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+
+    let reset = msg
+        .text()
+        .is_some_and(|text| text.split_whitespace().nth(1) == Some("reset"));
+    if reset && !is_privileged(&bot, &msg).await? {
+        let new_msg = bot
+            .send_message(msg.chat.id, "Тільки адміністратори можуть це змінювати")
+            .await?;
+        delete_message!(state, new_msg, CleanupKind::Timetable);
+        return Ok(());
+    }
+
+    let entries = get_full_timetable(&state.db, msg.chat.id).await?;
+    if entries.is_empty() {
+        let new_msg = bot
+            .send_message(
+                msg.chat.id,
+                "Розклад порожній, спершу імпортуйте його через /import",
+            )
+            .await?;
+        delete_message!(state, new_msg, CleanupKind::Timetable);
+        return Ok(());
+    }
+
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    let ics = render_ics(
+        &calendar,
+        &entries,
+        msg.chat.title().unwrap_or("Розклад"),
+        chrono::Utc::now(),
+    );
+    let token = if reset {
+        rotate_calendar_token(&state.db, msg.chat.id).await?
+    } else {
+        get_or_create_calendar_token(&state.db, msg.chat.id).await?
+    };
+    let caption = match feed_url(&token) {
+        Some(url) => format!(
+            "Щоб розклад оновлювався сам, підпишіться на календар: {}",
+            url
+        ),
+        None => "Імпортуйте файл у свій календар".to_string(),
+    };
+
+    bot.send_document(
+        msg.chat.id,
+        InputFile::memory(ics.into_bytes()).file_name("timetable.ics"),
+    )
+    .caption(caption)
+    .await?;
+
     Ok(())
 }
 
@@ -0,0 +1,326 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::Requester,
+    types::{InlineKeyboardMarkup, Message, UserId},
+    Bot,
+};
+
+use crate::{
+    bot::{handler::HandlerResult, ui, utils::reply_markup_builder::ReplyMarkupBuilder},
+    config::state::{BotDialogue, TimetableEdit},
+    delete_message,
+    models::{
+        cleanup::CleanupKind,
+        timetable::{TimetableEntryModel, TimetableOverrideModel},
+    },
+    redis::RedisCache,
+    repositories::{
+        chat_repository::get_calendar,
+        timetable_repository::{
+            add_entries, get_chat_entry, set_override, update_class_name, update_class_time,
+            update_link,
+        },
+    },
+    state::State,
+};
+
+use super::{
+    calendar::AcademicCalendar,
+    sources::{normalize_class_type, parse_day, parse_time, parse_weeks, ImportedEntry},
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const ENTRY_BUTTONS_PER_ROW: usize = 2;
+
+pub trait TimetableMarkupExt {
+    fn timetable_editor_markup(entries: &[TimetableEntryModel]) -> InlineKeyboardMarkup;
+    fn entry_editor_markup(
+        entry_id: i32,
+        overrides: &[TimetableOverrideModel],
+    ) -> InlineKeyboardMarkup;
+    fn confirm_delete_entry_markup(entry_id: i32) -> InlineKeyboardMarkup;
+    fn cancel_timetable_edit_markup() -> InlineKeyboardMarkup;
+}
+
+impl TimetableMarkupExt for ReplyMarkupBuilder {
+    fn timetable_editor_markup(entries: &[TimetableEntryModel]) -> InlineKeyboardMarkup {
+        let mut markup = ReplyMarkupBuilder::new();
+        for row in entries.chunks(ENTRY_BUTTONS_PER_ROW) {
+            let labels: Vec<String> = row
+                .iter()
+                .map(ui::timetable_ui::entry_button_label)
+                .collect();
+            markup = markup.button_row(
+                row.iter()
+                    .zip(labels.iter())
+                    .map(|(entry, label)| (label.as_str(), format!("edit-entry_{}", entry.id)))
+                    .collect(),
+            );
+        }
+        markup
+            .single_button("Додати пару ➕", "add-entry".to_string())
+            .build()
+    }
+
+    fn entry_editor_markup(
+        entry_id: i32,
+        overrides: &[TimetableOverrideModel],
+    ) -> InlineKeyboardMarkup {
+        let mut markup = ReplyMarkupBuilder::new()
+            .button_row(vec![
+                ("Назва ✏️", format!("rename-entry_{}", entry_id)),
+                ("Час 🕒", format!("retime-entry_{}", entry_id)),
+                ("Посилання 🔗", format!("link-entry_{}", entry_id)),
+            ])
+            .button_row(vec![
+                ("Скасувати раз 🚫", format!("cancel-lesson_{}", entry_id)),
+                ("Перенести раз 📆", format!("move-lesson_{}", entry_id)),
+            ]);
+        for override_ in overrides {
+            let label = format!("Повернути {} ↩️", override_.class_date.format(DATE_FORMAT));
+            markup = markup.single_button(&label, format!("restore-lesson_{}", override_.id));
+        }
+        markup
+            .button_row(vec![
+                ("Видалити 🗑", format!("delete-entry_{}", entry_id)),
+                ("Назад ⬅️", "timetable-editor".to_string()),
+            ])
+            .build()
+    }
+
+    fn confirm_delete_entry_markup(entry_id: i32) -> InlineKeyboardMarkup {
+        ReplyMarkupBuilder::new()
+            .button_row(vec![
+                (
+                    "Так, видалити 🗑",
+                    format!("confirm-delete-entry_{}", entry_id),
+                ),
+                ("Назад ⬅️", format!("edit-entry_{}", entry_id)),
+            ])
+            .build()
+    }
+
+    fn cancel_timetable_edit_markup() -> InlineKeyboardMarkup {
+        ReplyMarkupBuilder::new()
+            .single_button("Відміна ✖️", "cancel-timetable-edit".to_string())
+            .build()
+    }
+}
+
+pub fn prompt(edit: &TimetableEdit) -> &'static str {
+    match edit {
+        TimetableEdit::AddEntry => {
+            "Надішліть пару у форматі <тиждень 1, 2 або *> <день> <ГГ:ХХ> <lec, prac або lab> <назва>, наприклад:\n1 пн 08:30 lec Математичний аналіз"
+        }
+        TimetableEdit::Rename { .. } => "Надішліть нову назву пари",
+        TimetableEdit::Retime { .. } => "Надішліть новий час пари у форматі ГГ:ХХ",
+        TimetableEdit::Link { .. } => {
+            "Надішліть посилання що починається з http:// або https://"
+        }
+        TimetableEdit::Cancel { .. } => {
+            "Надішліть дату пари, яку потрібно скасувати, у форматі РРРР-ММ-ДД"
+        }
+        TimetableEdit::Move { .. } => {
+            "Надішліть дату пари, нову дату та час у форматі РРРР-ММ-ДД РРРР-ММ-ДД ГГ:ХХ"
+        }
+    }
+}
+
+/// `<week 1|2|*> <day> <HH:MM> <type> <name>`; `*` adds the class to both weeks.
+pub fn parse_new_entry(text: &str) -> Option<Vec<ImportedEntry>> {
+    let mut parts = text.split_whitespace();
+    let weeks = match parts.next()? {
+        "*" => parse_weeks(None),
+        week => parse_weeks(Some(week.parse().ok()?)),
+    }?;
+    let day = parse_day(parts.next()?)?;
+    let class_time = parse_time(parts.next()?)?;
+    let class_type = normalize_class_type(parts.next()?);
+    let class_name = parts.collect::<Vec<_>>().join(" ");
+    if class_type.is_empty() || class_name.is_empty() {
+        return None;
+    }
+
+    Some(
+        weeks
+            .into_iter()
+            .map(|week| ImportedEntry {
+                week,
+                day,
+                class_name: class_name.clone(),
+                class_type: class_type.clone(),
+                class_time,
+                link: None,
+            })
+            .collect(),
+    )
+}
+
+/// `<class date> <new date> <HH:MM>`.
+pub fn parse_move(text: &str) -> Option<(NaiveDate, NaiveDateTime)> {
+    let [class_date, new_date, new_time] = text.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let class_date = NaiveDate::parse_from_str(class_date, DATE_FORMAT).ok()?;
+    let new_date = NaiveDate::parse_from_str(new_date, DATE_FORMAT).ok()?;
+    Some((class_date, new_date.and_time(parse_time(new_time)?)))
+}
+
+/// Whether the recurring entry takes place on `date`.
+pub fn occurs_on(
+    calendar: &AcademicCalendar,
+    entry: &TimetableEntryModel,
+    date: NaiveDate,
+) -> bool {
+    calendar.week_of(date) as i32 == entry.week
+        && AcademicCalendar::day_of(date) as i32 == entry.day
+}
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg, CleanupKind::Timetable);
+    Ok(())
+}
+
+/// Applies the text reply to the pending edit. Invalid replies keep the edit
+/// pending so the user can try again or press the cancel button.
+pub async fn receive_timetable_edit(
+    bot: Bot,
+    dialogue: BotDialogue,
+    msg: Message,
+    (edit, _): (TimetableEdit, UserId),
+    state: State,
+) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    let text = msg.text().unwrap_or_default().trim().to_string();
+    let chat_id = msg.chat.id;
+
+    let entry = match edit {
+        TimetableEdit::AddEntry => None,
+        TimetableEdit::Rename { entry_id }
+        | TimetableEdit::Retime { entry_id }
+        | TimetableEdit::Link { entry_id }
+        | TimetableEdit::Cancel { entry_id }
+        | TimetableEdit::Move { entry_id } => {
+            match get_chat_entry(&state.db, chat_id, entry_id).await? {
+                Some(entry) => Some(entry),
+                None => {
+                    dialogue.exit().await?;
+                    return reply(&bot, &msg, &state, "Пару не знайдено".to_string()).await;
+                }
+            }
+        }
+    };
+
+    let result = match (&edit, entry) {
+        (TimetableEdit::AddEntry, _) => match parse_new_entry(&text) {
+            Some(entries) => {
+                add_entries(&state.db, chat_id, &entries).await?;
+                Ok("Пару додано ✅".to_string())
+            }
+            None => Err(prompt(&edit).to_string()),
+        },
+        (TimetableEdit::Rename { .. }, Some(entry)) if !text.is_empty() => {
+            update_class_name(&state.db, entry.id, &text).await?;
+            Ok(format!("Пару перейменовано на {}", text))
+        }
+        (TimetableEdit::Retime { .. }, Some(entry)) => match parse_time(&text) {
+            Some(class_time) => {
+                update_class_time(&state.db, entry.id, class_time).await?;
+                Ok(format!("Тепер пара о {}", class_time.format("%H:%M")))
+            }
+            None => Err(prompt(&edit).to_string()),
+        },
+        (TimetableEdit::Link { .. }, Some(entry)) if text.starts_with("http") => {
+            update_link(&state.db, entry.id, &text).await?;
+            Ok("Посилання успішно змінено".to_string())
+        }
+        (TimetableEdit::Cancel { .. }, Some(entry)) => {
+            let calendar = get_calendar(&state.db, chat_id).await?;
+            match NaiveDate::parse_from_str(&text, DATE_FORMAT) {
+                Ok(date) if occurs_on(&calendar, &entry, date) => {
+                    set_override(&state.db, chat_id, entry.id, date, None).await?;
+                    Ok(format!("Пару {} скасовано", date.format(DATE_FORMAT)))
+                }
+                Ok(_) => Err("Цього дня такої пари немає".to_string()),
+                Err(_) => Err(prompt(&edit).to_string()),
+            }
+        }
+        (TimetableEdit::Move { .. }, Some(entry)) => {
+            let calendar = get_calendar(&state.db, chat_id).await?;
+            match parse_move(&text) {
+                Some((date, moved_to)) if occurs_on(&calendar, &entry, date) => {
+                    set_override(&state.db, chat_id, entry.id, date, Some(moved_to)).await?;
+                    Ok(format!(
+                        "Пару {} перенесено на {}",
+                        date.format(DATE_FORMAT),
+                        moved_to.format("%Y-%m-%d %H:%M")
+                    ))
+                }
+                Some(_) => Err("Цього дня такої пари немає".to_string()),
+                None => Err(prompt(&edit).to_string()),
+            }
+        }
+        _ => Err(prompt(&edit).to_string()),
+    };
+
+    match result {
+        Ok(text) => {
+            dialogue.exit().await?;
+            state.redis.clear_timetable_entries(chat_id)?;
+            reply(&bot, &msg, &state, text).await
+        }
+        Err(text) => {
+            let new_msg = bot
+                .send_message(chat_id, text)
+                .reply_markup(ReplyMarkupBuilder::cancel_timetable_edit_markup())
+                .await?;
+            delete_message!(state, new_msg, CleanupKind::Timetable);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use super::*;
+    use crate::bot::timetable::{Day, Week};
+
+    #[test]
+    fn test_parse_new_entry() {
+        let entries = parse_new_entry("* ср 12:20 лаб Бази даних").unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].week, Week::First);
+        assert_eq!(entries[1].week, Week::Second);
+        assert_eq!(entries[0].day, Day::Wed);
+        assert_eq!(entries[0].class_type, "lab");
+        assert_eq!(entries[0].class_name, "Бази даних");
+        assert_eq!(
+            entries[0].class_time,
+            NaiveTime::from_hms_opt(12, 20, 0).unwrap()
+        );
+
+        assert!(parse_new_entry("3 ср 12:20 lab Бази даних").is_none());
+        assert!(parse_new_entry("1 ср 12:20 lab").is_none());
+        assert!(parse_new_entry("1 ср 12:20 Бази даних").is_none());
+    }
+
+    #[test]
+    fn test_parse_move() {
+        let (date, moved_to) = parse_move("2025-09-15 2025-09-17 14:15").unwrap();
+
+        assert_eq!(date, NaiveDate::from_ymd_opt(2025, 9, 15).unwrap());
+        assert_eq!(
+            moved_to,
+            NaiveDate::from_ymd_opt(2025, 9, 17)
+                .unwrap()
+                .and_hms_opt(14, 15, 0)
+                .unwrap()
+        );
+        assert!(parse_move("2025-09-15 14:15").is_none());
+    }
+}
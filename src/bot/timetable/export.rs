@@ -0,0 +1,263 @@
+use std::env;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+
+use crate::{bot::ui::timetable_ui::class_type_label, models::timetable::TimetableEntryModel};
+
+use super::{calendar::AcademicCalendar, Day, Week};
+
+/// How long a class lasts.
+pub const CLASS_DURATION: Duration = Duration::minutes(95);
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Public address of the feed, if the API's external URL is configured.
+pub fn feed_url(token: &str) -> Option<String> {
+    let base = env::var("PUBLIC_API_URL").ok()?;
+    Some(format!(
+        "{}/timetable/{}.ics",
+        base.trim_end_matches('/'),
+        token
+    ))
+}
+
+/// Renders the two-week timetable as an iCalendar with a biweekly event per class.
+pub fn render_ics(
+    calendar: &AcademicCalendar,
+    entries: &[TimetableEntryModel],
+    name: &str,
+    generated_at: DateTime<Utc>,
+) -> String {
+    let timezone = calendar.timezone.name();
+    let anchor = calendar.semester_start.unwrap_or_else(|| calendar.today());
+    let stamp = generated_at.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Lumios//Timetable//UK".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+        format!("X-WR-TIMEZONE:{}", timezone),
+    ];
+
+    for entry in entries {
+        let week = match entry.week {
+            0 => Week::First,
+            _ => Week::Second,
+        };
+        let day = Day::from(entry.day);
+        let starts_at = first_occurrence(calendar, anchor, week, day).and_time(entry.class_time);
+        let type_label = class_type_label(&entry.class_type).trim_end_matches(':');
+
+        let mut description = type_label.to_string();
+        if let Some(link) = &entry.link {
+            description.push_str(&format!("\n{}", link));
+        }
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:timetable-entry-{}@lumios", entry.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!(
+            "DTSTART;TZID={}:{}",
+            timezone,
+            format_time(starts_at)
+        ));
+        lines.push(format!(
+            "DTEND;TZID={}:{}",
+            timezone,
+            format_time(starts_at + CLASS_DURATION)
+        ));
+        lines.push(format!(
+            "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY={}",
+            ics_day(day)
+        ));
+        for date in holiday_dates(calendar, starts_at.date(), week, day) {
+            lines.push(format!(
+                "EXDATE;TZID={}:{}",
+                timezone,
+                format_time(date.and_time(entry.class_time))
+            ));
+        }
+        lines.push(format!("SUMMARY:{}", escape(&entry.class_name)));
+        lines.push(format!(
+            "CATEGORIES:{}",
+            escape(type_label.trim_start_matches(|c: char| !c.is_alphabetic()))
+        ));
+        lines.push(format!("DESCRIPTION:{}", escape(&description)));
+        if let Some(link) = &entry.link {
+            lines.push(format!("URL:{}", link));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold(line))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+/// First date on or after `anchor`'s week that falls on `day` of `week`.
+fn first_occurrence(
+    calendar: &AcademicCalendar,
+    anchor: NaiveDate,
+    week: Week,
+    day: Day,
+) -> NaiveDate {
+    let mut date = AcademicCalendar::date_in_week(anchor, day);
+    if calendar.week_of(date) != week {
+        date += Duration::weeks(1);
+    }
+    if calendar.semester_start.is_some_and(|start| date < start) {
+        date += Duration::weeks(2);
+    }
+    date
+}
+
+/// Occurrences of the class that fall on holidays.
+fn holiday_dates(
+    calendar: &AcademicCalendar,
+    first: NaiveDate,
+    week: Week,
+    day: Day,
+) -> Vec<NaiveDate> {
+    calendar
+        .holidays
+        .iter()
+        .flat_map(|holiday| {
+            holiday
+                .starts_on
+                .iter_days()
+                .take_while(|date| *date <= holiday.ends_on)
+        })
+        .filter(|date| {
+            *date >= first
+                && AcademicCalendar::day_of(*date) == day
+                && calendar.week_of(*date) == week
+        })
+        .collect()
+}
+
+fn format_time(time: NaiveDateTime) -> String {
+    time.format(DATE_TIME_FORMAT).to_string()
+}
+
+fn ics_day(day: Day) -> &'static str {
+    match day {
+        Day::Mon => "MO",
+        Day::Tue => "TU",
+        Day::Wed => "WE",
+        Day::Thu => "TH",
+        Day::Fri => "FR",
+        Day::Sat => "SA",
+        Day::Sun => "SU",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into CRLF-terminated chunks of at most 75 octets.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveTime, TimeZone};
+
+    use super::*;
+    use crate::{bot::timetable::sources::ics::parse_ics, models::chat::HolidayModel};
+
+    fn calendar() -> AcademicCalendar {
+        AcademicCalendar {
+            timezone: chrono_tz::Europe::Kyiv,
+            semester_start: NaiveDate::from_ymd_opt(2025, 9, 3),
+            holidays: vec![HolidayModel {
+                id: 1,
+                chat_id: 1,
+                starts_on: NaiveDate::from_ymd_opt(2025, 10, 13).unwrap(),
+                ends_on: NaiveDate::from_ymd_opt(2025, 10, 19).unwrap(),
+                title: "Осінні канікули".to_string(),
+            }],
+        }
+    }
+
+    fn entry(
+        id: i32,
+        week: i32,
+        day: i32,
+        class_type: &str,
+        link: Option<&str>,
+    ) -> TimetableEntryModel {
+        TimetableEntryModel {
+            id,
+            week,
+            day,
+            timetable_id: 1,
+            class_name: "Об'єктно-орієнтоване програмування; частина 2".to_string(),
+            class_type: class_type.to_string(),
+            class_time: NaiveTime::from_hms_opt(10, 25, 0).unwrap(),
+            link: link.map(String::from),
+            subgroup: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_biweekly_events() {
+        let entries = vec![
+            entry(1, 0, 0, "lec", Some("https://meet.google.com/abc-defg-hij")),
+            entry(2, 1, 2, "lab", None),
+        ];
+        let generated_at = Utc.with_ymd_and_hms(2025, 9, 1, 12, 0, 0).unwrap();
+        let ics = render_ics(&calendar(), &entries, "ІП-32", generated_at);
+
+        // The semester starts on Wednesday, so Monday of the first week is skipped.
+        assert!(ics.contains("DTSTART;TZID=Europe/Kyiv:20250915T102500\r\n"));
+        assert!(ics.contains("DTSTART;TZID=Europe/Kyiv:20250910T102500\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO\r\n"));
+        assert!(ics.contains("EXDATE;TZID=Europe/Kyiv:20251013T102500\r\n"));
+        assert!(!ics.contains("EXDATE;TZID=Europe/Kyiv:20251015"));
+        assert!(ics.contains("URL:https://meet.google.com/abc-defg-hij\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let entries = vec![
+            entry(1, 0, 0, "lec", Some("https://meet.google.com/abc-defg-hij")),
+            entry(2, 1, 2, "lab", None),
+            entry(3, 1, 4, "prac", None),
+        ];
+        let ics = render_ics(&calendar(), &entries, "ІП-32", Utc::now());
+        let imported = parse_ics(&ics, &calendar()).unwrap();
+
+        assert_eq!(imported.len(), entries.len());
+        for (imported, entry) in imported.iter().zip(entries.iter()) {
+            assert_eq!(imported.week as i32, entry.week);
+            assert_eq!(u8::from(imported.day) as i32, entry.day);
+            assert_eq!(imported.class_name, entry.class_name);
+            assert_eq!(imported.class_type, entry.class_type);
+            assert_eq!(imported.class_time, entry.class_time);
+            assert_eq!(imported.link, entry.link);
+        }
+    }
+}
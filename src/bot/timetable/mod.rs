@@ -1,14 +1,20 @@
+pub mod calendar;
 pub mod commands;
+pub mod editor;
+pub mod export;
 pub mod external;
+pub mod reminders;
 pub mod schedule;
+pub mod settings;
+pub mod sources;
+pub mod subgroups;
+pub mod sync;
 use std::{
     fmt::Display,
     fmt::Formatter,
     ops::{Add, Mul},
 };
 
-use chrono::Datelike;
-
 #[derive(Copy, Clone, Debug)]
 pub enum Day {
     Mon,
@@ -101,11 +107,6 @@ impl Day {
             Day::Sun => Day::Mon,
         }
     }
-    pub fn current() -> Self {
-        let now = chrono::Utc::now();
-        let day = now.date_naive().weekday();
-        day.into()
-    }
 }
 
 impl From<Week> for u8 {
@@ -149,15 +150,6 @@ impl Week {
             Week::Second => Week::First,
         }
     }
-    pub fn current() -> Self {
-        let now = chrono::Utc::now();
-        let start = chrono::NaiveDate::from_isoywd_opt(now.year(), 1, chrono::Weekday::Mon);
-        let week_number = now.date_naive().iso_week().week() - start.unwrap().iso_week().week() + 1;
-        match week_number % 2 {
-            0 => Week::Second,
-            _ => Week::First,
-        }
-    }
 }
 
 impl Add<u8> for Week {
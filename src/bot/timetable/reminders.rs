@@ -0,0 +1,155 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::models::timetable::{ReminderSettingsModel, TimetableEntryModel};
+
+/// A reminder missed by up to this many minutes (e.g. a skipped cron tick) is still sent.
+const CATCH_UP_MINUTES: i64 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DueReminder {
+    pub entry_id: i32,
+    pub class_date: NaiveDate,
+    pub offset_minutes: i32,
+    pub class_time: NaiveTime,
+}
+
+/// Reminders whose time has come at `now` (chat-local). Works across hour and
+/// day boundaries since it compares full date-times.
+pub fn due_reminders(
+    now: NaiveDateTime,
+    lessons: &[(NaiveDate, TimetableEntryModel)],
+    settings: &ReminderSettingsModel,
+) -> Vec<DueReminder> {
+    let mut due = Vec::new();
+    for (date, entry) in lessons {
+        if settings.muted_class_types.contains(&entry.class_type) {
+            continue;
+        }
+        let starts_at = date.and_time(entry.class_time);
+        for offset in settings.offsets.iter() {
+            let remind_at = starts_at - Duration::minutes(*offset as i64);
+            if remind_at <= now
+                && now < starts_at
+                && now < remind_at + Duration::minutes(CATCH_UP_MINUTES)
+            {
+                due.push(DueReminder {
+                    entry_id: entry.id,
+                    class_date: *date,
+                    offset_minutes: *offset,
+                    class_time: entry.class_time,
+                });
+            }
+        }
+    }
+    due
+}
+
+pub fn digest_due(now: NaiveDateTime, settings: &ReminderSettingsModel) -> bool {
+    let Some(digest_time) = settings.digest_time else {
+        return false;
+    };
+    let digest_at = now.date().and_time(digest_time);
+    digest_at <= now && now < digest_at + Duration::minutes(CATCH_UP_MINUTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i32, class_type: &str, hour: u32, minute: u32) -> TimetableEntryModel {
+        TimetableEntryModel {
+            id,
+            week: 0,
+            day: 0,
+            timetable_id: 1,
+            class_name: "Математичний аналіз".to_string(),
+            class_type: class_type.to_string(),
+            class_time: NaiveTime::from_hms_opt(hour, minute, 0).unwrap(),
+            link: None,
+            subgroup: String::new(),
+        }
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_reminders_cross_hour_boundary() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let lecture = entry(1, "lec", 10, 0);
+        let lessons = vec![(date, lecture)];
+        let settings = ReminderSettingsModel {
+            offsets: vec![15, 3],
+            ..ReminderSettingsModel::default_for(1)
+        };
+
+        assert!(due_reminders(at(date, 9, 44), &lessons, &settings).is_empty());
+        assert_eq!(
+            due_reminders(at(date, 9, 45), &lessons, &settings),
+            vec![DueReminder {
+                entry_id: 1,
+                class_date: date,
+                offset_minutes: 15,
+                class_time: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+            }]
+        );
+        assert_eq!(
+            due_reminders(at(date, 9, 57), &lessons, &settings),
+            vec![DueReminder {
+                entry_id: 1,
+                class_date: date,
+                offset_minutes: 3,
+                class_time: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+            }]
+        );
+        assert!(due_reminders(at(date, 10, 0), &lessons, &settings).is_empty());
+    }
+
+    #[test]
+    fn test_muted_class_type() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let lab = entry(2, "lab", 8, 30);
+        let lessons = vec![(date, lab)];
+        let settings = ReminderSettingsModel {
+            muted_class_types: vec!["lab".to_string()],
+            ..ReminderSettingsModel::default_for(1)
+        };
+
+        assert!(due_reminders(at(date, 8, 27), &lessons, &settings).is_empty());
+    }
+
+    #[test]
+    fn test_reminder_before_midnight() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 2).unwrap();
+        let late = entry(3, "prac", 0, 5);
+        let lessons = vec![(date, late)];
+        let settings = ReminderSettingsModel {
+            offsets: vec![15],
+            ..ReminderSettingsModel::default_for(1)
+        };
+        let previous_day = date.pred_opt().unwrap();
+
+        assert_eq!(
+            due_reminders(at(previous_day, 23, 50), &lessons, &settings).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_digest_due() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let settings = ReminderSettingsModel {
+            digest_time: NaiveTime::from_hms_opt(7, 30, 0),
+            ..ReminderSettingsModel::default_for(1)
+        };
+
+        assert!(!digest_due(at(date, 7, 29), &settings));
+        assert!(digest_due(at(date, 7, 30), &settings));
+        assert!(!digest_due(at(date, 7, 40), &settings));
+        assert!(!digest_due(
+            at(date, 7, 30),
+            &ReminderSettingsModel::default_for(1)
+        ));
+    }
+}
@@ -1,46 +1,92 @@
-use chrono::Timelike;
+use chrono::{Duration, Timelike};
 use teloxide::types::ChatId;
 
 use crate::{
-    bot::utils::time::get_current_time,
-    repositories::{self, timetable_repository::get_full_timetable},
+    repositories::{
+        self,
+        chat_repository::get_calendar,
+        reminder_repository::{
+            claim_digest, claim_reminder, get_reminder_settings, prune_sent_notifications,
+        },
+        timetable_repository::{get_full_timetable, get_overrides},
+    },
     models::timetable::TimetableEntryModel,
     redis::RedisCache,
     state::{Event, State},
 };
 
-use super::{Day, Week};
+use super::reminders::{digest_due, due_reminders};
 
 pub async fn timetable_notifications(state: State) {
     let chat_ids = get_chat_ids(&state).await.unwrap();
     for chat_id in chat_ids {
-        let entries = match get_entries(&state, chat_id).await {
-            Ok(entries) => entries,
-            Err(err) => {
-                eprintln!("Failed to get entries: {}", err);
-                return;
-            }
-        };
-        let now = get_current_time();
-        let current_week = Week::current() as i32;
-        let current_day = Day::current() as i32;
-        for entry in entries.iter() {
-            if entry.week != current_week || entry.day != current_day {
-                continue;
-            }
-            let class_time = entry.class_time;
-            if (class_time.hour() as i32) == (now.hour() as i32)
-                && (class_time.minute() as i32) - (now.minute() as i32) == 3
-            {
-                _ = state.sender.send(Event::NotifyTimetable {
-                    chat_id,
-                    entry_id: entry.id,
-                });
-            }
+        if let Err(err) = notify_chat(&state, chat_id).await {
+            tracing::error!(
+                "Failed to send timetable notifications to chat {}: {:?}",
+                chat_id,
+                err
+            );
         }
     }
 }
 
+pub async fn prune_notifications(state: State) {
+    if let Err(err) = prune_sent_notifications(&state.db).await {
+        tracing::error!("Failed to prune sent notifications: {:?}", err);
+    }
+}
+
+async fn notify_chat(state: &State, chat_id: ChatId) -> anyhow::Result<()> {
+    let entries = get_entries(state, chat_id).await?;
+    let calendar = get_calendar(&state.db, chat_id).await?;
+    let settings = get_reminder_settings(&state.db, chat_id).await?;
+
+    let now = calendar.now().naive_local();
+    let now = now.with_second(0).unwrap_or(now);
+    let today = now.date();
+
+    let tomorrow = today + Duration::days(1);
+    let overrides = get_overrides(&state.db, chat_id, today, tomorrow).await?;
+    let lessons: Vec<_> = [today, tomorrow]
+        .into_iter()
+        .flat_map(|date| {
+            calendar
+                .lessons_on(&entries, &overrides, date)
+                .into_iter()
+                .map(move |entry| (date, entry))
+        })
+        .collect();
+
+    for reminder in due_reminders(now, &lessons, &settings) {
+        let claimed = claim_reminder(
+            &state.db,
+            chat_id,
+            reminder.entry_id,
+            reminder.class_date,
+            reminder.offset_minutes,
+        )
+        .await?;
+        if claimed {
+            state.events.send(Event::NotifyTimetable {
+                chat_id,
+                entry_id: reminder.entry_id,
+                class_date: reminder.class_date,
+                class_time: reminder.class_time,
+            })?;
+        }
+    }
+
+    let has_lessons_today = lessons.iter().any(|(date, _)| *date == today);
+    if has_lessons_today
+        && digest_due(now, &settings)
+        && claim_digest(&state.db, chat_id, today).await?
+    {
+        state.events.send(Event::TimetableDigest { chat_id })?;
+    }
+
+    Ok(())
+}
+
 async fn get_chat_ids(state: &State) -> anyhow::Result<Vec<ChatId>> {
     if let Ok(chat_ids) = state.redis.get_all_chat_ids() {
         return Ok(chat_ids);
@@ -54,7 +100,7 @@ async fn get_entries(state: &State, chat_id: ChatId) -> anyhow::Result<Vec<Timet
     if let Ok(entries) = state.redis.get_timetable_entries(chat_id) {
         return Ok(entries);
     }
-    let entries = get_full_timetable(&state.db, &chat_id.to_string()).await?;
+    let entries = get_full_timetable(&state.db, chat_id).await?;
     state
         .redis
         .store_timetable_entries(chat_id, entries.clone())?;
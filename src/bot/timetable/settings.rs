@@ -0,0 +1,290 @@
+use chrono::{NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use teloxide::{prelude::Requester, types::Message, Bot};
+
+use crate::{
+    bot::{
+        handler::HandlerResult,
+        ui,
+        utils::{params::get_n_params, permissions::is_privileged},
+    },
+    delete_message,
+    models::cleanup::CleanupKind,
+    repositories::{
+        chat_repository::{
+            add_holiday as insert_holiday, get_calendar, remove_holiday as delete_holiday,
+            set_semester_start, set_timezone,
+        },
+        reminder_repository::{
+            get_reminder_settings, set_digest_time, set_reminder_offsets, toggle_muted_class_type,
+        },
+    },
+    state::State,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const MAX_REMINDER_OFFSET: i32 = 180;
+const MAX_REMINDER_OFFSETS: usize = 5;
+const CLASS_TYPES: [&str; 3] = ["lec", "prac", "lab"];
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg, CleanupKind::Timetable);
+    Ok(())
+}
+
+/// Stops the command unless it comes from a chat admin.
+async fn ensure_privileged(bot: &Bot, msg: &Message, state: &State) -> anyhow::Result<bool> {
+    if is_privileged(bot, msg).await? {
+        return Ok(true);
+    }
+    let new_msg = bot
+        .send_message(msg.chat.id, "Тільки адміністратори можуть це змінювати")
+        .await?;
+    delete_message!(state, new_msg, CleanupKind::Timetable);
+    Ok(false)
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).ok()
+}
+
+pub async fn calendar(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    reply(
+        &bot,
+        &msg,
+        &state,
+        ui::timetable_ui::calendar_view(&calendar),
+    )
+    .await
+}
+
+pub async fn timezone(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !ensure_privileged(&bot, &msg, &state).await? {
+        return Ok(());
+    }
+
+    let timezone = match get_n_params::<String>(&msg, 1) {
+        Ok(params) if params[0].parse::<Tz>().is_ok() => params[0].clone(),
+        _ => {
+            let usage =
+                "Використання: /timezone <часовий пояс IANA>, наприклад /timezone Europe/Kyiv";
+            return reply(&bot, &msg, &state, usage.to_string()).await;
+        }
+    };
+
+    set_timezone(&state.db, msg.chat.id, &timezone).await?;
+
+    reply(
+        &bot,
+        &msg,
+        &state,
+        format!("Часовий пояс чату: {}", timezone),
+    )
+    .await
+}
+
+pub async fn semester_start(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !ensure_privileged(&bot, &msg, &state).await? {
+        return Ok(());
+    }
+
+    let date = match get_n_params::<String>(&msg, 1) {
+        Ok(params) => parse_date(&params[0]),
+        Err(_) => None,
+    };
+    let Some(date) = date else {
+        let usage = "Використання: /semester_start <РРРР-ММ-ДД>";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    set_semester_start(&state.db, msg.chat.id, date).await?;
+
+    let calendar = get_calendar(&state.db, msg.chat.id).await?;
+    reply(
+        &bot,
+        &msg,
+        &state,
+        format!(
+            "Семестр починається {}. Зараз {}",
+            date.format(DATE_FORMAT),
+            calendar.week_of(calendar.today())
+        ),
+    )
+    .await
+}
+
+/// `/add_holiday <from> <to> [title]` adds an inclusive date range without classes.
+pub async fn add_holiday(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !ensure_privileged(&bot, &msg, &state).await? {
+        return Ok(());
+    }
+
+    let params: Vec<&str> = msg
+        .text()
+        .map(|text| text.split_whitespace().skip(1).collect())
+        .unwrap_or_default();
+    let range = match params.as_slice() {
+        [from, to, ..] => parse_date(from).zip(parse_date(to)),
+        _ => None,
+    };
+    let Some((starts_on, ends_on)) = range.filter(|(from, to)| from <= to) else {
+        let usage = "Використання: /add_holiday <РРРР-ММ-ДД> <РРРР-ММ-ДД> [назва]";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+    let title = params.get(2..).unwrap_or_default().join(" ");
+
+    let holiday = insert_holiday(&state.db, msg.chat.id, starts_on, ends_on, &title).await?;
+
+    reply(
+        &bot,
+        &msg,
+        &state,
+        format!("Додано: {}", ui::timetable_ui::holiday_row(&holiday)),
+    )
+    .await
+}
+
+pub async fn remove_holiday(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !ensure_privileged(&bot, &msg, &state).await? {
+        return Ok(());
+    }
+
+    let Ok(params) = get_n_params::<i32>(&msg, 1) else {
+        let usage = "Використання: /remove_holiday <номер>, номери показує /calendar";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let text = if delete_holiday(&state.db, msg.chat.id, params[0]).await? {
+        "Канікули видалено"
+    } else {
+        "Канікули з таким номером не знайдено"
+    };
+
+    reply(&bot, &msg, &state, text.to_string()).await
+}
+
+fn command_params(msg: &Message) -> Vec<&str> {
+    msg.text()
+        .map(|text| text.split_whitespace().skip(1).collect())
+        .unwrap_or_default()
+}
+
+/// Parses reminder offsets in minutes. `off` disables reminders.
+fn parse_offsets(params: &[&str]) -> Option<Vec<i32>> {
+    if params == ["off"] {
+        return Some(Vec::new());
+    }
+    if params.is_empty() || params.len() > MAX_REMINDER_OFFSETS {
+        return None;
+    }
+    let mut offsets = params
+        .iter()
+        .map(|param| param.parse::<i32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if offsets
+        .iter()
+        .any(|offset| !(0..=MAX_REMINDER_OFFSET).contains(offset))
+    {
+        return None;
+    }
+    offsets.sort_unstable_by(|a, b| b.cmp(a));
+    offsets.dedup();
+    Some(offsets)
+}
+
+pub async fn reminders(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    let settings = get_reminder_settings(&state.db, msg.chat.id).await?;
+    reply(
+        &bot,
+        &msg,
+        &state,
+        ui::timetable_ui::reminder_settings_view(&settings),
+    )
+    .await
+}
+
+/// `/reminder_offsets 15 3` reminds 15 and 3 minutes before each class.
+pub async fn reminder_offsets(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !ensure_privileged(&bot, &msg, &state).await? {
+        return Ok(());
+    }
+
+    let Some(offsets) = parse_offsets(&command_params(&msg)) else {
+        let usage = format!(
+            "Використання: /reminder_offsets <хвилини> ... (до {} значень від 0 до {}) або /reminder_offsets off",
+            MAX_REMINDER_OFFSETS, MAX_REMINDER_OFFSET
+        );
+        return reply(&bot, &msg, &state, usage).await;
+    };
+
+    set_reminder_offsets(&state.db, msg.chat.id, &offsets).await?;
+
+    let settings = get_reminder_settings(&state.db, msg.chat.id).await?;
+    reply(
+        &bot,
+        &msg,
+        &state,
+        ui::timetable_ui::reminder_settings_view(&settings),
+    )
+    .await
+}
+
+pub async fn mute_class_type(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !ensure_privileged(&bot, &msg, &state).await? {
+        return Ok(());
+    }
+
+    let class_type = match command_params(&msg).as_slice() {
+        [class_type] if CLASS_TYPES.contains(class_type) => class_type.to_string(),
+        _ => {
+            let usage = "Використання: /mute_class_type <lec|prac|lab>";
+            return reply(&bot, &msg, &state, usage.to_string()).await;
+        }
+    };
+
+    let muted = toggle_muted_class_type(&state.db, msg.chat.id, &class_type).await?;
+    let label = ui::timetable_ui::class_type_label(&class_type).trim_end_matches(':');
+    let text = if muted {
+        format!("Нагадування вимкнено: {}", label)
+    } else {
+        format!("Нагадування увімкнено: {}", label)
+    };
+
+    reply(&bot, &msg, &state, text).await
+}
+
+/// `/digest 07:30` sends the day's timetable every morning, `/digest off` stops it.
+pub async fn digest(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !ensure_privileged(&bot, &msg, &state).await? {
+        return Ok(());
+    }
+
+    let digest_time = match command_params(&msg).as_slice() {
+        ["off"] => Some(None),
+        [time] => NaiveTime::parse_from_str(time, "%H:%M").ok().map(Some),
+        _ => None,
+    };
+    let Some(digest_time) = digest_time else {
+        let usage = "Використання: /digest <ГГ:ХХ> або /digest off";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    set_digest_time(&state.db, msg.chat.id, digest_time).await?;
+
+    let text = match digest_time {
+        Some(time) => format!("Ранковий розклад щодня о {}", time.format("%H:%M")),
+        None => "Ранковий розклад вимкнено".to_string(),
+    };
+    reply(&bot, &msg, &state, text).await
+}
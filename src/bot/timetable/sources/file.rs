@@ -0,0 +1,216 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{
+    normalize_class_type, parse_day, parse_time, parse_weeks, sorted, ImportedEntry,
+    TimetableSource,
+};
+
+/// `week,day,time,name,type,link` rows. An empty week means every week and
+/// `type` and `link` may be left out. A leading header row is skipped.
+pub struct CsvSource {
+    content: String,
+}
+
+impl CsvSource {
+    pub fn new(content: String) -> Self {
+        Self { content }
+    }
+}
+
+#[async_trait]
+impl TimetableSource for CsvSource {
+    async fn fetch(&self) -> anyhow::Result<Vec<ImportedEntry>> {
+        parse_csv(&self.content)
+    }
+}
+
+pub fn parse_csv(content: &str) -> anyhow::Result<Vec<ImportedEntry>> {
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let fields = split_csv_line(line);
+        if fields.iter().all(|field| field.is_empty()) {
+            continue;
+        }
+        if line_number == 1 && fields[0].eq_ignore_ascii_case("week") {
+            continue;
+        }
+
+        let [week, day, time, name, rest @ ..] = fields.as_slice() else {
+            anyhow::bail!("Рядок {}: потрібно щонайменше 4 колонки", line_number);
+        };
+        let week = match week.as_str() {
+            "" => None,
+            week => Some(week.parse::<u8>().unwrap_or_default()),
+        };
+        let row = ClassRow {
+            week,
+            day: day.clone(),
+            time: time.clone(),
+            name: name.clone(),
+            class_type: rest.first().cloned(),
+            link: rest.get(1).cloned(),
+        };
+        entries.extend(row.into_entries(line_number)?);
+    }
+
+    Ok(sorted(entries))
+}
+
+/// Splits a CSV line, honouring double-quoted fields with `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// ```toml
+/// [[classes]]
+/// week = 1 # optional, every week when left out
+/// day = "mon"
+/// time = "08:30"
+/// name = "Математичний аналіз"
+/// type = "lec"
+/// link = "https://meet.google.com/abc-defg-hij"
+/// ```
+pub struct TomlSource {
+    content: String,
+}
+
+impl TomlSource {
+    pub fn new(content: String) -> Self {
+        Self { content }
+    }
+}
+
+#[async_trait]
+impl TimetableSource for TomlSource {
+    async fn fetch(&self) -> anyhow::Result<Vec<ImportedEntry>> {
+        parse_toml(&self.content)
+    }
+}
+
+#[derive(Deserialize)]
+struct TomlTimetable {
+    #[serde(default)]
+    classes: Vec<ClassRow>,
+}
+
+pub fn parse_toml(content: &str) -> anyhow::Result<Vec<ImportedEntry>> {
+    let timetable: TomlTimetable =
+        toml::from_str(content).map_err(|err| anyhow::anyhow!("Некоректний TOML: {}", err))?;
+
+    let mut entries = Vec::new();
+    for (index, row) in timetable.classes.into_iter().enumerate() {
+        entries.extend(row.into_entries(index + 1)?);
+    }
+
+    Ok(sorted(entries))
+}
+
+#[derive(Deserialize)]
+struct ClassRow {
+    week: Option<u8>,
+    day: String,
+    time: String,
+    name: String,
+    #[serde(rename = "type")]
+    class_type: Option<String>,
+    link: Option<String>,
+}
+
+impl ClassRow {
+    fn into_entries(self, number: usize) -> anyhow::Result<Vec<ImportedEntry>> {
+        let weeks = parse_weeks(self.week)
+            .ok_or_else(|| anyhow::anyhow!("Заняття {}: тиждень має бути 1 або 2", number))?;
+        let day = parse_day(&self.day)
+            .ok_or_else(|| anyhow::anyhow!("Заняття {}: невідомий день '{}'", number, self.day))?;
+        let class_time = parse_time(&self.time)
+            .ok_or_else(|| anyhow::anyhow!("Заняття {}: час має бути у форматі ГГ:ХХ", number))?;
+        if self.name.trim().is_empty() {
+            anyhow::bail!("Заняття {}: відсутня назва", number);
+        }
+        let class_type = normalize_class_type(self.class_type.as_deref().unwrap_or_default());
+        let link = self
+            .link
+            .map(|link| link.trim().to_string())
+            .filter(|link| link.starts_with("http"));
+
+        Ok(weeks
+            .into_iter()
+            .map(|week| ImportedEntry {
+                week,
+                day,
+                class_name: self.name.trim().to_string(),
+                class_type: class_type.clone(),
+                class_time,
+                link: link.clone(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use super::*;
+    use crate::bot::timetable::{Day, Week};
+
+    #[test]
+    fn test_parse_recorded_csv() {
+        let entries = parse_csv(include_str!("fixtures/schedule.csv")).unwrap();
+
+        assert_eq!(entries.len(), 5);
+        assert_eq!(
+            entries[0],
+            ImportedEntry {
+                week: Week::First,
+                day: Day::Mon,
+                class_name: "Алгебра, геометрія".to_string(),
+                class_type: "lec".to_string(),
+                class_time: NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+                link: Some("https://meet.google.com/abc-defg-hij".to_string()),
+            }
+        );
+        assert_eq!(
+            entries
+                .iter()
+                .filter(|entry| entry.class_name == "Фізичне виховання")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_parse_recorded_toml() {
+        let entries = parse_toml(include_str!("fixtures/schedule.toml")).unwrap();
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].day, Day::Tue);
+        assert_eq!(entries[0].class_type, "prac");
+        assert_eq!(entries[3].week, Week::Second);
+        assert_eq!(entries[3].link, None);
+    }
+
+    #[test]
+    fn test_csv_reports_line() {
+        let err = parse_csv("week,day,time,name\n1,mon,25:00,Фізика").unwrap_err();
+        assert!(err.to_string().contains('2'));
+    }
+}
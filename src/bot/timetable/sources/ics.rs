@@ -0,0 +1,280 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use super::{normalize_class_type, parse_day, sorted, ImportedEntry, TimetableSource};
+use crate::bot::timetable::{calendar::AcademicCalendar, Week};
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// iCalendar exports, such as those of Google Calendar or university portals.
+/// Weekly events land in both weeks, biweekly and single events in the week
+/// their first date falls on according to the chat's calendar.
+pub struct IcsSource {
+    content: String,
+    calendar: AcademicCalendar,
+}
+
+impl IcsSource {
+    pub fn new(content: String, calendar: AcademicCalendar) -> Self {
+        Self { content, calendar }
+    }
+}
+
+#[async_trait]
+impl TimetableSource for IcsSource {
+    async fn fetch(&self) -> anyhow::Result<Vec<ImportedEntry>> {
+        parse_ics(&self.content, &self.calendar)
+    }
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub fn parse_ics(content: &str, calendar: &AcademicCalendar) -> anyhow::Result<Vec<ImportedEntry>> {
+    if !content.trim_start().starts_with("BEGIN:VCALENDAR") {
+        anyhow::bail!("Файл не схожий на календар iCalendar");
+    }
+
+    let mut entries = Vec::new();
+    let mut event: Option<Vec<Property>> = None;
+    for line in unfold(content) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+        match (property.name.as_str(), property.value.as_str()) {
+            ("BEGIN", "VEVENT") => event = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(properties) = event.take() {
+                    entries.extend(event_entries(&properties, calendar));
+                }
+            }
+            _ => {
+                if let Some(properties) = event.as_mut() {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+
+    Ok(sorted(entries))
+}
+
+/// Joins folded lines, which continue with a leading space or tab.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    let (head, value) = line.split_once(':')?;
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.to_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(escaped) => result.push(escaped),
+            None => {}
+        }
+    }
+    result
+}
+
+fn event_entries(properties: &[Property], calendar: &AcademicCalendar) -> Vec<ImportedEntry> {
+    let find = |name: &str| properties.iter().find(|property| property.name == name);
+
+    let Some(summary) = find("SUMMARY").map(|property| unescape(&property.value)) else {
+        return Vec::new();
+    };
+    // All-day events are not classes.
+    let Some(starts_at) = find("DTSTART").and_then(|property| local_start(property, calendar))
+    else {
+        return Vec::new();
+    };
+    let rule = find("RRULE").map(|property| property.value.as_str());
+
+    let date = starts_at.date();
+    let days = rule
+        .and_then(|rule| rule_part(rule, "BYDAY"))
+        .map(|days| {
+            days.split(',')
+                .filter_map(|day| parse_day(&ics_day(day)))
+                .collect::<Vec<_>>()
+        })
+        .filter(|days| !days.is_empty())
+        .unwrap_or_else(|| vec![AcademicCalendar::day_of(date)]);
+    let every_week = rule.is_some_and(|rule| {
+        rule_part(rule, "FREQ") == Some("WEEKLY")
+            && rule_part(rule, "INTERVAL").unwrap_or("1") == "1"
+    });
+
+    let class_type = find("CATEGORIES")
+        .map(|property| normalize_class_type(&unescape(&property.value)))
+        .filter(|class_type| !class_type.is_empty())
+        .unwrap_or_else(|| {
+            summary
+                .split(|c: char| !c.is_alphabetic())
+                .map(normalize_class_type)
+                .find(|class_type| !class_type.is_empty())
+                .unwrap_or_default()
+        });
+    let link = ["URL", "LOCATION"]
+        .into_iter()
+        .filter_map(find)
+        .map(|property| unescape(&property.value))
+        .find(|value| value.starts_with("http"));
+
+    let mut entries = Vec::new();
+    for day in days {
+        let week = calendar.week_of(AcademicCalendar::date_in_week(date, day));
+        let weeks = if every_week {
+            vec![Week::First, Week::Second]
+        } else {
+            vec![week]
+        };
+        for week in weeks {
+            entries.push(ImportedEntry {
+                week,
+                day,
+                class_name: summary.trim().to_string(),
+                class_type: class_type.clone(),
+                class_time: starts_at.time(),
+                link: link.clone(),
+            });
+        }
+    }
+    entries
+}
+
+/// Start of the event in the chat's timezone.
+fn local_start(property: &Property, calendar: &AcademicCalendar) -> Option<NaiveDateTime> {
+    if property.param("VALUE") == Some("DATE") {
+        return None;
+    }
+    if let Some(utc) = property.value.strip_suffix('Z') {
+        let starts_at = NaiveDateTime::parse_from_str(utc, DATE_TIME_FORMAT).ok()?;
+        return Some(
+            Utc.from_utc_datetime(&starts_at)
+                .with_timezone(&calendar.timezone)
+                .naive_local(),
+        );
+    }
+
+    let starts_at = NaiveDateTime::parse_from_str(&property.value, DATE_TIME_FORMAT).ok()?;
+    match property.param("TZID").and_then(|tz| tz.parse::<Tz>().ok()) {
+        Some(timezone) => timezone
+            .from_local_datetime(&starts_at)
+            .earliest()
+            .map(|starts_at| starts_at.with_timezone(&calendar.timezone).naive_local()),
+        None => Some(starts_at),
+    }
+}
+
+fn rule_part<'a>(rule: &'a str, name: &str) -> Option<&'a str> {
+    rule.split(';')
+        .filter_map(|part| part.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// `BYDAY` values may carry an ordinal prefix, e.g. `1MO`.
+fn ics_day(day: &str) -> String {
+    let day = day.trim_start_matches(|c: char| c == '+' || c == '-' || c.is_ascii_digit());
+    match day {
+        "MO" => "mon",
+        "TU" => "tue",
+        "WE" => "wed",
+        "TH" => "thu",
+        "FR" => "fri",
+        "SA" => "sat",
+        "SU" => "sun",
+        _ => "",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::*;
+    use crate::bot::timetable::Day;
+
+    fn calendar() -> AcademicCalendar {
+        AcademicCalendar {
+            timezone: chrono_tz::Europe::Kyiv,
+            semester_start: NaiveDate::from_ymd_opt(2025, 9, 1),
+            holidays: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_recorded_ics() {
+        let entries = parse_ics(include_str!("fixtures/schedule.ics"), &calendar()).unwrap();
+
+        assert_eq!(entries.len(), 5);
+        assert_eq!(
+            entries[0],
+            ImportedEntry {
+                week: Week::First,
+                day: Day::Mon,
+                class_name: "Лекція: Комп'ютерні мережі".to_string(),
+                class_type: "lec".to_string(),
+                class_time: NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+                link: Some("https://meet.google.com/abc-defg-hij".to_string()),
+            }
+        );
+        // Biweekly lab starting in the second week of the semester.
+        let lab = entries
+            .iter()
+            .find(|entry| entry.class_type == "lab")
+            .unwrap();
+        assert_eq!(lab.week, Week::Second);
+        assert_eq!(lab.day, Day::Tue);
+        // Times in UTC are converted to the chat's timezone.
+        assert!(entries.iter().any(|entry| entry.day == Day::Fri
+            && entry.class_time == NaiveTime::from_hms_opt(12, 20, 0).unwrap()
+            && entry.class_type == "prac"));
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        assert!(parse_ics("week,day,time,name", &calendar()).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::{normalize_class_type, parse_time, sorted, ImportedEntry, TimetableSource};
+use crate::bot::timetable::{Day, Week};
+
+const GROUP_FIND_URL: &str = "https://api.campus.kpi.ua/group/find";
+const LESSONS_URL: &str = "https://api.campus.kpi.ua/schedule/lessons";
+
+/// Group schedules published by the KPI Campus API.
+pub struct KpiCampusSource {
+    client: reqwest::Client,
+    group_name: String,
+}
+
+impl KpiCampusSource {
+    pub fn new(client: reqwest::Client, group_name: String) -> Self {
+        Self { client, group_name }
+    }
+
+    async fn find_group_id(&self) -> anyhow::Result<String> {
+        let groups = self
+            .client
+            .get(GROUP_FIND_URL)
+            .query(&[("name", &self.group_name)])
+            .send()
+            .await
+            .context("Failed to query KPI Campus groups")?
+            .json::<Value>()
+            .await
+            .context("Failed to parse KPI Campus groups")?;
+
+        groups
+            .as_array()
+            .and_then(|groups| groups.iter().find(|x| x["name"] == self.group_name))
+            .and_then(|group| group["id"].as_str())
+            .map(String::from)
+            .ok_or_else(|| anyhow::anyhow!("Групу {} не знайдено", self.group_name))
+    }
+}
+
+#[async_trait]
+impl TimetableSource for KpiCampusSource {
+    async fn fetch(&self) -> anyhow::Result<Vec<ImportedEntry>> {
+        let group_id = self.find_group_id().await?;
+        let schedule = self
+            .client
+            .get(LESSONS_URL)
+            .query(&[("groupId", &group_id), ("groupName", &self.group_name)])
+            .send()
+            .await
+            .context("Failed to query KPI Campus schedule")?
+            .json::<Value>()
+            .await
+            .context("Failed to parse KPI Campus schedule")?;
+
+        parse_schedule(&schedule)
+    }
+}
+
+/// Parses the `schedule/lessons` response, where days are listed from Monday.
+pub fn parse_schedule(schedule: &Value) -> anyhow::Result<Vec<ImportedEntry>> {
+    let mut entries = Vec::new();
+    for (week, schedule_key) in [
+        (Week::First, "scheduleFirstWeek"),
+        (Week::Second, "scheduleSecondWeek"),
+    ] {
+        let Some(days) = schedule["data"][schedule_key].as_array() else {
+            continue;
+        };
+        for (index, day) in days.iter().enumerate().take(7) {
+            let Some(pairs) = day["pairs"].as_array() else {
+                continue;
+            };
+            for pair in pairs {
+                let time = pair["time"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'time' field in timetable entry"))?;
+                let class_time =
+                    parse_time(time).context(format!("Failed to parse time '{}'", time))?;
+
+                entries.push(ImportedEntry {
+                    week,
+                    day: Day::from(index as i32),
+                    class_name: pair["name"].as_str().unwrap_or_default().to_string(),
+                    class_type: normalize_class_type(pair["tag"].as_str().unwrap_or_default()),
+                    class_time,
+                    link: None,
+                });
+            }
+        }
+    }
+
+    Ok(sorted(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_recorded_schedule() {
+        let schedule: Value =
+            serde_json::from_str(include_str!("fixtures/kpi_schedule.json")).unwrap();
+        let entries = parse_schedule(&schedule).unwrap();
+
+        assert_eq!(entries.len(), 5);
+        assert_eq!(
+            entries[0],
+            ImportedEntry {
+                week: Week::First,
+                day: Day::Mon,
+                class_name: "Математичний аналіз".to_string(),
+                class_type: "lec".to_string(),
+                class_time: NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+                link: None,
+            }
+        );
+        assert_eq!(entries[2].day, Day::Wed);
+        assert_eq!(entries[2].class_type, "lab");
+        assert!(entries[3..].iter().all(|entry| entry.week == Week::Second));
+    }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use chrono::NaiveTime;
+
+use super::{calendar::AcademicCalendar, Day, Week};
+
+pub mod file;
+pub mod ics;
+pub mod kpi;
+
+/// Largest uploaded timetable file the bot will download.
+pub const MAX_FILE_SIZE: u32 = 512 * 1024;
+
+/// A class as produced by a timetable source, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEntry {
+    pub week: Week,
+    pub day: Day,
+    pub class_name: String,
+    pub class_type: String,
+    pub class_time: NaiveTime,
+    pub link: Option<String>,
+}
+
+#[async_trait]
+pub trait TimetableSource: Send + Sync {
+    async fn fetch(&self) -> anyhow::Result<Vec<ImportedEntry>>;
+}
+
+/// Picks a file-based source by the extension of the uploaded document.
+pub fn source_for_file(
+    file_name: &str,
+    content: String,
+    calendar: AcademicCalendar,
+) -> Option<Box<dyn TimetableSource>> {
+    let extension = file_name.rsplit_once('.')?.1.to_lowercase();
+    match extension.as_str() {
+        "ics" => Some(Box::new(ics::IcsSource::new(content, calendar))),
+        "csv" => Some(Box::new(file::CsvSource::new(content))),
+        "toml" => Some(Box::new(file::TomlSource::new(content))),
+        _ => None,
+    }
+}
+
+/// Maps the many spellings of class types onto the `lec`, `prac` and `lab` tags.
+pub fn normalize_class_type(value: &str) -> String {
+    let value = value.trim().to_lowercase();
+    let class_type = if value.starts_with("лек") || value.starts_with("lec") {
+        "lec"
+    } else if value.starts_with("лаб") || value.starts_with("lab") {
+        "lab"
+    } else if value.starts_with("прак")
+        || value.starts_with("сем")
+        || value.starts_with("prac")
+        || value.starts_with("sem")
+    {
+        "prac"
+    } else {
+        ""
+    };
+    class_type.to_string()
+}
+
+/// Accepts `1`-`7`, English and Ukrainian day abbreviations.
+pub fn parse_day(value: &str) -> Option<Day> {
+    let day = match value.trim().to_lowercase().as_str() {
+        "1" | "mon" | "monday" | "пн" | "понеділок" => Day::Mon,
+        "2" | "tue" | "tuesday" | "вт" | "вівторок" => Day::Tue,
+        "3" | "wed" | "wednesday" | "ср" | "середа" => Day::Wed,
+        "4" | "thu" | "thursday" | "чт" | "четвер" => Day::Thu,
+        "5" | "fri" | "friday" | "пт" | "п'ятниця" => Day::Fri,
+        "6" | "sat" | "saturday" | "сб" | "субота" => Day::Sat,
+        "7" | "sun" | "sunday" | "нд" | "неділя" => Day::Sun,
+        _ => return None,
+    };
+    Some(day)
+}
+
+/// Weeks a class takes place in; `None` means every week.
+pub fn parse_weeks(value: Option<u8>) -> Option<Vec<Week>> {
+    match value {
+        None => Some(vec![Week::First, Week::Second]),
+        Some(1) => Some(vec![Week::First]),
+        Some(2) => Some(vec![Week::Second]),
+        Some(_) => None,
+    }
+}
+
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Orders entries the way the timetable is shown and drops duplicates.
+fn sorted(mut entries: Vec<ImportedEntry>) -> Vec<ImportedEntry> {
+    entries.sort_by_key(|entry| {
+        (
+            u8::from(entry.week),
+            u8::from(entry.day),
+            entry.class_time,
+            entry.class_name.clone(),
+            entry.class_type.clone(),
+        )
+    });
+    entries.dedup();
+    entries
+}
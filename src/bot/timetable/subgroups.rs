@@ -0,0 +1,218 @@
+use teloxide::{
+    prelude::Requester,
+    types::{ChatId, Message, UserId},
+    Bot,
+};
+
+use crate::{
+    bot::{handler::HandlerResult, ui, utils::permissions::is_privileged},
+    delete_message,
+    models::{cleanup::CleanupKind, timetable::TimetableEntryModel, timetable::TimetableModel},
+    redis::RedisCache,
+    repositories::{
+        subgroup_repository::{
+            get_subgroup, get_subgroups, get_user_subgroup, join_subgroup, leave_subgroup,
+            remove_subgroup as delete_subgroup,
+        },
+        user_repository::get_user_by_account_id,
+    },
+    state::State,
+};
+
+const MAX_SUBGROUP_NAME_LENGTH: usize = 16;
+
+async fn reply(bot: &Bot, msg: &Message, state: &State, text: String) -> HandlerResult {
+    let new_msg = bot.send_message(msg.chat.id, text).await?;
+    delete_message!(state, new_msg, CleanupKind::Timetable);
+    Ok(())
+}
+
+/// Subgroup names are short words, so they fit in commands and stay safe to
+/// show in formatted messages.
+pub fn parse_subgroup_name(name: &str) -> Option<String> {
+    let length = name.chars().count();
+    let valid = (1..=MAX_SUBGROUP_NAME_LENGTH).contains(&length)
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-')
+        && name != "off";
+    valid.then(|| name.to_string())
+}
+
+/// Lessons for everyone plus those of the subgroup. Without a subgroup all
+/// lessons are kept; their subgroups are shown next to them.
+pub fn for_subgroup(
+    entries: Vec<TimetableEntryModel>,
+    subgroup: Option<&TimetableModel>,
+) -> Vec<TimetableEntryModel> {
+    match subgroup {
+        Some(subgroup) => entries
+            .into_iter()
+            .filter(|entry| entry.subgroup.is_empty() || entry.subgroup == subgroup.name)
+            .collect(),
+        None => entries,
+    }
+}
+
+/// The user's subgroup in this chat.
+pub async fn user_subgroup(
+    state: &State,
+    chat_id: ChatId,
+    user_id: UserId,
+) -> anyhow::Result<Option<TimetableModel>> {
+    let Ok(user) = get_user_by_account_id(state, user_id).await else {
+        return Ok(None);
+    };
+    let subgroup = get_user_subgroup(&state.db, user.id)
+        .await?
+        .filter(|subgroup| subgroup.chat_id == chat_id.0);
+    Ok(subgroup)
+}
+
+/// Lessons the sender of the message should see.
+pub async fn entries_for_sender(
+    state: &State,
+    msg: &Message,
+    entries: Vec<TimetableEntryModel>,
+) -> anyhow::Result<Vec<TimetableEntryModel>> {
+    let subgroup = match msg.from.as_ref() {
+        Some(user) => user_subgroup(state, msg.chat.id, user.id).await?,
+        None => None,
+    };
+    Ok(for_subgroup(entries, subgroup.as_ref()))
+}
+
+/// `/subgroup` lists the subgroups, `/subgroup <name>` joins one and
+/// `/subgroup off` leaves it. Admins can reply to someone's message to assign
+/// that user instead.
+pub async fn subgroup(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    let Some(sender) = msg.from.as_ref() else {
+        return Ok(());
+    };
+
+    let param = msg
+        .text()
+        .and_then(|text| text.split_whitespace().nth(1))
+        .map(String::from);
+    let Some(param) = param else {
+        let subgroups = get_subgroups(&state.db, msg.chat.id).await?;
+        let current = user_subgroup(&state, msg.chat.id, sender.id).await?;
+        return reply(
+            &bot,
+            &msg,
+            &state,
+            ui::timetable_ui::subgroups_view(&subgroups, current.as_ref()),
+        )
+        .await;
+    };
+
+    let target = match msg.reply_to_message().and_then(|reply| reply.from.as_ref()) {
+        Some(target) if target.id != sender.id => {
+            if !is_privileged(&bot, &msg).await? {
+                let text = "Тільки адміністратори можуть змінювати підгрупу інших";
+                return reply(&bot, &msg, &state, text.to_string()).await;
+            }
+            target
+        }
+        _ => sender,
+    };
+    let Ok(user) = get_user_by_account_id(&state, target.id).await else {
+        let text = format!("Користувача {} ще немає в цьому чаті", target.first_name);
+        return reply(&bot, &msg, &state, text).await;
+    };
+
+    if param == "off" {
+        let text = if leave_subgroup(&state.db, user.id).await? {
+            format!("{} більше не в підгрупі", user.name)
+        } else {
+            format!("{} не належить до жодної підгрупи", user.name)
+        };
+        return reply(&bot, &msg, &state, text).await;
+    }
+
+    let subgroup = match parse_subgroup_name(&param) {
+        Some(name) => get_subgroup(&state.db, msg.chat.id, &name).await?,
+        None => None,
+    };
+    let Some(subgroup) = subgroup else {
+        let text = format!(
+            "Підгрупу {} не знайдено. Імпортуйте її розклад командою /import <група> <підгрупа>",
+            param
+        );
+        return reply(&bot, &msg, &state, text).await;
+    };
+
+    join_subgroup(&state.db, user.id, subgroup.id).await?;
+    let text = format!("{} тепер у підгрупі {}", user.name, subgroup.name);
+    reply(&bot, &msg, &state, text).await
+}
+
+/// `/remove_subgroup <name>` deletes the subgroup together with its lessons.
+pub async fn remove_subgroup(bot: Bot, msg: Message, state: State) -> HandlerResult {
+    delete_message!(state, msg, CleanupKind::Timetable);
+    if !is_privileged(&bot, &msg).await? {
+        let text = "Тільки адміністратори можуть це змінювати";
+        return reply(&bot, &msg, &state, text.to_string()).await;
+    }
+
+    let Some(name) = msg.text().and_then(|text| text.split_whitespace().nth(1)) else {
+        let usage = "Використання: /remove_subgroup <підгрупа>";
+        return reply(&bot, &msg, &state, usage.to_string()).await;
+    };
+
+    let text = if delete_subgroup(&state.db, msg.chat.id, name).await? {
+        state.redis.clear_timetable_entries(msg.chat.id)?;
+        format!("Підгрупу {} та її розклад видалено", name)
+    } else {
+        format!("Підгрупу {} не знайдено", name)
+    };
+    reply(&bot, &msg, &state, text).await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use super::*;
+
+    fn entry(id: i32, subgroup: &str) -> TimetableEntryModel {
+        TimetableEntryModel {
+            id,
+            week: 0,
+            day: 0,
+            timetable_id: id,
+            class_name: format!("Пара {}", id),
+            class_type: "lab".to_string(),
+            class_time: NaiveTime::from_hms_opt(10, 25, 0).unwrap(),
+            link: None,
+            subgroup: subgroup.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_for_subgroup() {
+        let entries = vec![entry(1, ""), entry(2, "1"), entry(3, "2")];
+        let subgroup = TimetableModel {
+            id: 2,
+            chat_id: 1,
+            name: "1".to_string(),
+        };
+
+        let ids = |entries: Vec<TimetableEntryModel>| -> Vec<i32> {
+            entries.into_iter().map(|entry| entry.id).collect()
+        };
+        assert_eq!(
+            ids(for_subgroup(entries.clone(), Some(&subgroup))),
+            vec![1, 2]
+        );
+        assert_eq!(ids(for_subgroup(entries, None)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_parse_subgroup_name() {
+        assert_eq!(parse_subgroup_name("1"), Some("1".to_string()));
+        assert_eq!(parse_subgroup_name("лаб-А"), Some("лаб-А".to_string()));
+        assert_eq!(parse_subgroup_name("off"), None);
+        assert_eq!(parse_subgroup_name("a.b"), None);
+        assert_eq!(parse_subgroup_name("дуже-довга-назва-групи"), None);
+    }
+}
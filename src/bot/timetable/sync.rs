@@ -0,0 +1,188 @@
+use chrono::NaiveTime;
+use teloxide::types::ChatId;
+
+use crate::{
+    bot::ui,
+    models::timetable::{TimetableEntryModel, TimetableSourceModel},
+    redis::RedisCache,
+    repositories::timetable_repository::{get_timetable_sources, import_timetable, mark_synced},
+    state::{Event, State},
+};
+
+use super::sources::{kpi::KpiCampusSource, ImportedEntry, TimetableSource};
+
+pub const KPI_SOURCE: &str = "kpi";
+
+/// Changes between the stored timetable and a freshly fetched one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TimetableDiff {
+    pub added: Vec<ImportedEntry>,
+    pub removed: Vec<TimetableEntryModel>,
+    /// Stored entries that now take place at another week, day or time.
+    pub moved: Vec<(TimetableEntryModel, ImportedEntry)>,
+}
+
+impl TimetableDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+type EntryKey<'a> = (i32, i32, NaiveTime, &'a str);
+
+fn stored_key(entry: &TimetableEntryModel) -> EntryKey<'_> {
+    (entry.week, entry.day, entry.class_time, &entry.class_name)
+}
+
+fn imported_key(entry: &ImportedEntry) -> EntryKey<'_> {
+    (
+        entry.week as i32,
+        u8::from(entry.day) as i32,
+        entry.class_time,
+        &entry.class_name,
+    )
+}
+
+/// Matches entries by week, day, time and name. Unmatched entries with the same
+/// name and class type are treated as moved, so they keep their id and link.
+pub fn diff_timetable(stored: &[TimetableEntryModel], fetched: &[ImportedEntry]) -> TimetableDiff {
+    let mut unmatched_stored: Vec<&TimetableEntryModel> = stored.iter().collect();
+    let mut unmatched_fetched = Vec::new();
+    for entry in fetched {
+        let key = imported_key(entry);
+        match unmatched_stored
+            .iter()
+            .position(|stored| stored_key(stored) == key)
+        {
+            Some(index) => {
+                unmatched_stored.remove(index);
+            }
+            None => unmatched_fetched.push(entry),
+        }
+    }
+
+    let mut diff = TimetableDiff::default();
+    for entry in unmatched_stored {
+        match unmatched_fetched.iter().position(|fetched| {
+            fetched.class_name == entry.class_name && fetched.class_type == entry.class_type
+        }) {
+            Some(index) => {
+                let fetched = unmatched_fetched.remove(index);
+                diff.moved.push((entry.clone(), fetched.clone()));
+            }
+            None => diff.removed.push(entry.clone()),
+        }
+    }
+    diff.added = unmatched_fetched.into_iter().cloned().collect();
+    diff
+}
+
+/// Re-fetches every remembered import source and announces what changed.
+pub async fn resync_timetables(state: State) {
+    let sources = match get_timetable_sources(&state.db).await {
+        Ok(sources) => sources,
+        Err(err) => {
+            tracing::error!("Failed to get timetable sources: {:?}", err);
+            return;
+        }
+    };
+    for source in sources {
+        if let Err(err) = resync_chat(&state, &source).await {
+            tracing::error!(
+                "Failed to re-sync timetable of chat {}: {:?}",
+                source.chat_id,
+                err
+            );
+        }
+    }
+}
+
+async fn resync_chat(state: &State, source: &TimetableSourceModel) -> anyhow::Result<()> {
+    if source.source != KPI_SOURCE {
+        return Ok(());
+    }
+    let chat_id = ChatId(source.chat_id);
+    let entries = KpiCampusSource::new(state.http_client.clone(), source.group_name.clone())
+        .fetch()
+        .await?;
+    // An empty schedule is more likely an outage than a cancelled semester.
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let diff = import_timetable(&state.db, chat_id.0, &source.subgroup, &entries).await?;
+    mark_synced(&state.db, chat_id, &source.subgroup).await?;
+    if diff.is_empty() {
+        return Ok(());
+    }
+
+    state.redis.clear_timetable_entries(chat_id)?;
+    state.events.send(Event::TimetableChanged {
+        chat_id,
+        summary: ui::timetable_ui::timetable_diff_view(&diff, &source.subgroup),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::timetable::{Day, Week};
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn stored_entry(id: i32, day: i32, class_time: NaiveTime, name: &str) -> TimetableEntryModel {
+        TimetableEntryModel {
+            id,
+            week: 0,
+            day,
+            timetable_id: 1,
+            class_name: name.to_string(),
+            class_type: "lec".to_string(),
+            class_time,
+            link: Some(format!("https://meet.google.com/{}", id)),
+            subgroup: String::new(),
+        }
+    }
+
+    fn fetched_entry(day: Day, class_time: NaiveTime, name: &str) -> ImportedEntry {
+        ImportedEntry {
+            week: Week::First,
+            day,
+            class_name: name.to_string(),
+            class_type: "lec".to_string(),
+            class_time,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_timetable() {
+        let stored = vec![
+            stored_entry(1, 0, time(8, 30), "Фізика"),
+            stored_entry(2, 0, time(10, 25), "Алгебра"),
+            stored_entry(3, 2, time(12, 20), "Історія"),
+        ];
+        let fetched = vec![
+            fetched_entry(Day::Mon, time(8, 30), "Фізика"),
+            fetched_entry(Day::Tue, time(10, 25), "Алгебра"),
+            fetched_entry(Day::Fri, time(14, 15), "Бази даних"),
+        ];
+        let diff = diff_timetable(&stored, &fetched);
+
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].0.id, 2);
+        assert_eq!(diff.moved[0].1.day, Day::Tue);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].id, 3);
+        assert_eq!(
+            diff.added,
+            vec![fetched_entry(Day::Fri, time(14, 15), "Бази даних")]
+        );
+
+        assert!(diff_timetable(&stored, &[]).added.is_empty());
+        assert!(diff_timetable(&stored[..1], &fetched[..1]).is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::NaiveDate;
+
+use crate::models::attendance::AttendanceModel;
+
+pub fn attendance_view(attendance: &[AttendanceModel], since: Option<NaiveDate>) -> String {
+    if attendance.is_empty() {
+        return "Відміток присутності ще немає. Адміністратор може додати кнопку «Я тут» до нагадувань командою /attendance on".to_string();
+    }
+
+    let mut response = match since {
+        Some(since) => format!("📋 Відвідування з {}:\n", since.format("%d.%m.%Y")),
+        None => "📋 Відвідування:\n".to_string(),
+    };
+    let mut current_user = None;
+    for row in attendance {
+        if current_user != Some(row.user_id) {
+            current_user = Some(row.user_id);
+            response.push_str(&format!("\n{}\n", row.name));
+        }
+        response.push_str(&format!(
+            "• {} — {:.0}% ({}/{})\n",
+            row.class_name,
+            row.percentage(),
+            row.attended,
+            row.held
+        ));
+    }
+    response
+}
@@ -0,0 +1,80 @@
+use chrono::NaiveDateTime;
+
+use crate::models::deadline::{DeadlineModel, DeadlineSettingsModel};
+
+const DUE_FORMAT: &str = "%d.%m %H:%M";
+
+pub fn deadlines_view(deadlines: &[(DeadlineModel, i64)], now: NaiveDateTime) -> String {
+    if deadlines.is_empty() {
+        return "Немає активних дедлайнів 🎉".to_string();
+    }
+
+    let mut response = "📚 Дедлайни:\n".to_string();
+    for (deadline, _) in deadlines {
+        let left = (deadline.due_at - now).num_minutes();
+        response.push_str(&format!(
+            "\n#{} {}\n⏳ до {} (через {})\n",
+            deadline.id,
+            title(deadline),
+            deadline.due_at.format(DUE_FORMAT),
+            format_minutes(left)
+        ));
+    }
+    response.push_str("\nНатисніть на завдання, щоб позначити його виконаним");
+    response
+}
+
+pub fn deadline_reminder_view(deadline: &DeadlineModel, offset_minutes: i32) -> String {
+    format!(
+        "⏰ Залишилось {}: {}\nДедлайн: {}",
+        format_minutes(offset_minutes as i64),
+        title(deadline),
+        deadline.due_at.format(DUE_FORMAT)
+    )
+}
+
+pub fn deadline_added_view(deadline: &DeadlineModel) -> String {
+    format!(
+        "Дедлайн #{} додано: {} до {}",
+        deadline.id,
+        title(deadline),
+        deadline.due_at.format(DUE_FORMAT)
+    )
+}
+
+pub fn deadline_settings_view(settings: &DeadlineSettingsModel) -> String {
+    let offsets = if settings.offsets.is_empty() {
+        "вимкнено".to_string()
+    } else {
+        settings
+            .offsets
+            .iter()
+            .map(|offset| format!("за {}", format_minutes(*offset as i64)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!("⏰ Нагадування про дедлайни: {}", offsets)
+}
+
+fn title(deadline: &DeadlineModel) -> String {
+    match &deadline.class_name {
+        Some(class_name) => format!("{} ({})", deadline.title, class_name),
+        None => deadline.title.clone(),
+    }
+}
+
+/// `1 д 3 год`, `45 хв`.
+pub fn format_minutes(minutes: i64) -> String {
+    let minutes = minutes.max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+    let parts: Vec<String> = [(days, "д"), (hours, "год"), (minutes, "хв")]
+        .into_iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{} {}", value, unit))
+        .collect();
+    if parts.is_empty() {
+        "0 хв".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
@@ -1,4 +1,7 @@
+pub mod attendance_ui;
+pub mod deadline_ui;
 pub mod queue_ui;
 pub mod stats_ui;
+pub mod subscription_ui;
 pub mod timetable_ui;
 pub mod utils;
@@ -1,6 +1,10 @@
 use crate::{
+    api::games::fairness::hash_seed,
     bot::ui::utils::adapt_for_markdown,
-    models::queue::{QueueModel, QueueUserWithUserModel},
+    models::queue::{
+        QueueAction, QueueEventModel, QueueModel, QueuePrioritySettingsModel,
+        QueueUserWithUserModel,
+    },
 };
 
 pub enum QueueType {
@@ -13,8 +17,62 @@ pub fn title(name: &String) -> String {
     format!("\\>\\>\\> *{}* <<<\n\n", adapt_for_markdown(&name),)
 }
 
+/// Lines about when the queue opens or closes, shown under its title.
+fn schedule_status(queue: &QueueModel) -> String {
+    let mut lines = Vec::new();
+    if queue.is_archived {
+        lines.push("🗄 Черга в архіві".to_string());
+    } else if queue.is_closed {
+        lines.push("🔒 Черга закрита".to_string());
+    } else {
+        if let Some(opens_at) = queue.opens_at {
+            lines.push(format!(
+                "⏳ Відкриється {}",
+                opens_at.format("%d.%m о %H:%M")
+            ));
+        }
+        if let Some(closes_at) = queue.closes_at {
+            lines.push(format!(
+                "⏰ Закриється {}",
+                closes_at.format("%d.%m о %H:%M")
+            ));
+        }
+        if let Some(max_users) = queue.max_users {
+            lines.push(format!("👥 Місць: {}", max_users));
+        }
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    format!("{}\n\n", adapt_for_markdown(&lines.join("\n")))
+}
+
+/// The draw of a lottery queue: the seed hash while people join, the seed after.
+fn lottery_status(queue: &QueueModel) -> String {
+    let Some(seed) = &queue.lottery_seed else {
+        return String::new();
+    };
+    match queue.lottery_closes_at {
+        Some(closes_at) => format!(
+            "{}\n`{}`\n\n",
+            adapt_for_markdown(&format!(
+                "🎲 Жеребкування {}, хеш сіда:",
+                closes_at.format("%d.%m о %H:%M")
+            )),
+            hash_seed(seed)
+        ),
+        None => format!(
+            "{}\n`{}`\n\n",
+            adapt_for_markdown(&"🎲 Порядок визначено жеребкуванням, сід:".to_string()),
+            seed
+        ),
+    }
+}
+
 pub fn regular_queue(queue: &QueueModel, users: Vec<QueueUserWithUserModel>) -> String {
     let mut message = title(&queue.title);
+    message.push_str(&schedule_status(queue));
+    message.push_str(&lottery_status(queue));
     let required_characters = users.len().to_string().len();
     for (i, user) in users.iter().enumerate() {
         message.push_str(&format!(
@@ -30,6 +88,7 @@ pub fn regular_queue(queue: &QueueModel, users: Vec<QueueUserWithUserModel>) ->
 
 pub fn priority_queue(queue: &QueueModel, users: Vec<QueueUserWithUserModel>) -> String {
     let mut message = title(&queue.title);
+    message.push_str(&schedule_status(queue));
     let required_characters = users.len().to_string().len();
     for (i, user) in users.iter().enumerate() {
         let index = if user.is_frozen.unwrap_or(false) {
@@ -49,10 +108,41 @@ pub fn priority_queue(queue: &QueueModel, users: Vec<QueueUserWithUserModel>) ->
             adapt_for_markdown(&user.username),
             width = required_characters
         ));
+        if let Some(reason) = placement_reason(user) {
+            message.push_str(&format!("    _{}_\n", adapt_for_markdown(&reason)));
+        }
+    }
+    if let Some(subject) = &queue.subject {
+        message.push_str(&format!(
+            "\n{}",
+            adapt_for_markdown(&format!(
+                "Спершу ті, у кого менше відповідей у цій черзі, далі — менше захистів «{}» раніше",
+                subject
+            ))
+        ));
     }
     message
 }
 
+/// Why a member of a priority queue stands where they do.
+fn placement_reason(user: &QueueUserWithUserModel) -> Option<String> {
+    let weight = user.weight?;
+    let mut parts = Vec::new();
+    if let Some(priority) = user.priority.filter(|priority| *priority > 0) {
+        parts.push(format!("відповідей у цій черзі: {}", priority));
+    }
+    match (user.defences.unwrap_or(0), user.last_defended_at) {
+        (0, _) | (_, None) => parts.push("раніше захистів не було".to_string()),
+        (defences, Some(last_defended_at)) => parts.push(format!(
+            "захистів раніше: {}, останній {}, вага {:.2}",
+            defences,
+            last_defended_at.format("%d.%m"),
+            weight
+        )),
+    }
+    Some(parts.join("; "))
+}
+
 pub fn notification(user: &QueueUserWithUserModel, queue: &QueueModel) -> String {
     format!(
         "{} – твоя черга відповідати в черзі '{}'",
@@ -60,3 +150,89 @@ pub fn notification(user: &QueueUserWithUserModel, queue: &QueueModel) -> String
         adapt_for_markdown(&queue.title)
     )
 }
+
+pub fn action_label(action: QueueAction) -> &'static str {
+    match action {
+        QueueAction::Join => "приєднання до черги",
+        QueueAction::Leave => "вихід з черги",
+        QueueAction::Shuffle => "перемішування",
+        QueueAction::Skip => "пропуск ходу",
+        QueueAction::Done => "відповідь",
+        QueueAction::Freeze => "заморозка ❄️",
+        QueueAction::Delete => "видалення черги",
+        QueueAction::Move => "переміщення",
+        QueueAction::Swap => "обмін місцями",
+        QueueAction::Kick => "видалення з черги",
+        QueueAction::Add => "додавання до черги",
+        QueueAction::Draw => "жеребкування 🎲",
+    }
+}
+
+pub fn history_view(queue: &QueueModel, events: &[QueueEventModel]) -> String {
+    if events.is_empty() {
+        return format!("Черга «{}» ще не змінювалась", queue.title);
+    }
+
+    let mut message = format!("Історія черги «{}»:\n", queue.title);
+    for event in events {
+        message.push_str(&format!(
+            "\n{} {}: {}",
+            event.created_at.format("%d.%m %H:%M"),
+            event.actor_name.as_deref().unwrap_or("—"),
+            action_label(event.action)
+        ));
+        if let Some(target) = &event.target_name {
+            message.push_str(&format!(" — {}", target));
+        }
+        if !event.details.is_empty() {
+            message.push_str(&format!(" ({})", event.details));
+        }
+        if event.undone_at.is_some() {
+            message.push_str(" ↩️ скасовано");
+        }
+    }
+    message
+}
+
+pub fn deleted(queue: &QueueModel) -> String {
+    format!("Чергу «{}» видалено", queue.title)
+}
+
+pub fn schedule_view(queue: &QueueModel) -> String {
+    let format_time = |time: Option<chrono::NaiveDateTime>| {
+        time.map_or("—".to_string(), |time| {
+            time.format("%d.%m о %H:%M").to_string()
+        })
+    };
+    let state = if queue.is_archived {
+        "в архіві"
+    } else if queue.is_closed {
+        "закрита"
+    } else {
+        "відкрита"
+    };
+    format!(
+        "Черга «{}» {}\nВідкриття: {}\nЗакриття: {}\nМісць: {}\nАрхів після пари: {}",
+        queue.title,
+        state,
+        format_time(queue.opens_at),
+        format_time(queue.closes_at),
+        queue
+            .max_users
+            .map_or("без обмежень".to_string(), |max_users| max_users
+                .to_string()),
+        format_time(queue.archive_at)
+    )
+}
+
+pub fn priority_settings_view(settings: &QueuePrioritySettingsModel) -> String {
+    let half_life = if settings.half_life_days > 0 {
+        format!("вдвічі менше через {} дн.", settings.half_life_days)
+    } else {
+        "без згасання".to_string()
+    };
+    format!(
+        "⚖️ Черги з пріоритетом враховують захисти з того ж предмета за {} дн.\nВага захисту: {}",
+        settings.window_days, half_life
+    )
+}
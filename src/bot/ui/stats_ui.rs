@@ -1,4 +1,4 @@
-use crate::models::stats::{FullStats, GroupStats};
+use crate::models::stats::{FullStats, GambleModel, GroupStats};
 use crate::models::user::UserStatsModel;
 
 use super::utils::adapt_for_markdown;
@@ -171,3 +171,21 @@ pub fn generate_lose_message(bet_amount: i32, new_balance: i32) -> String {
         .replace("{bet_amount}", &bet_amount.to_string())
         .replace("{new_balance}", &new_balance.to_string())
 }
+
+pub fn wheel_spinning(cost: i32) -> String {
+    if cost == 0 {
+        "🎡 Безкоштовне обертання! Колесо крутиться...".to_string()
+    } else {
+        format!("🎡 Ти заплатив {} поваги. Колесо крутиться...", cost)
+    }
+}
+
+pub fn wheel_result(gamble: &GambleModel) -> String {
+    let outcome = gamble.outcome.as_deref().unwrap_or("❓");
+    let summary = match gamble.change {
+        change if change > 0 => format!("Ти виграв {} поваги!", change),
+        change if change < 0 => format!("Ти втратив {} поваги.", -change),
+        _ => "Твій баланс не змінився.".to_string(),
+    };
+    format!("🎡 Колесо зупинилося на: {}\n\n{}", outcome, summary)
+}
@@ -0,0 +1,66 @@
+use crate::models::{
+    queue::QueueModel,
+    subscription::{QuietHoursModel, SubscriptionKind, SubscriptionModel},
+    timetable::TimetableEntryModel,
+};
+
+use super::timetable_ui::class_type_identifier;
+
+pub fn kind_label(kind: SubscriptionKind) -> &'static str {
+    match kind {
+        SubscriptionKind::Lessons => "нагадування про пари",
+        SubscriptionKind::Queue => "ваша черга",
+        SubscriptionKind::Deadlines => "дедлайни",
+    }
+}
+
+pub fn subscriptions_view(
+    subscriptions: &[SubscriptionModel],
+    quiet_hours: Option<&QuietHoursModel>,
+) -> String {
+    let mut response = if subscriptions.is_empty() {
+        "У вас немає підписок. Надішліть /subscribe у групі, щоб отримувати її сповіщення в особисті\n"
+            .to_string()
+    } else {
+        let mut response = "📬 Ваші підписки:\n".to_string();
+        let mut current_chat = None;
+        for subscription in subscriptions {
+            if current_chat != Some(subscription.chat_id) {
+                current_chat = Some(subscription.chat_id);
+                response.push_str(&format!("\n{}:\n", subscription.chat_title));
+            }
+            response.push_str(&format!("• {}\n", kind_label(subscription.kind)));
+        }
+        response
+    };
+
+    match quiet_hours {
+        Some(quiet_hours) => response.push_str(&format!(
+            "\n🌙 Тихі години: {}–{}",
+            quiet_hours.starts_at.format("%H:%M"),
+            quiet_hours.ends_at.format("%H:%M")
+        )),
+        None => response.push_str("\n🌙 Тихі години: не встановлено"),
+    }
+    response
+}
+
+pub fn lesson_notification(entry: &TimetableEntryModel) -> String {
+    let mut response = format!(
+        "🔔 {} {} о {}",
+        class_type_identifier(&entry.class_type),
+        entry.class_name,
+        entry.class_time.format("%H:%M")
+    );
+    if !entry.subgroup.is_empty() {
+        response.push_str(&format!(" (підгрупа {})", entry.subgroup));
+    }
+    if let Some(link) = &entry.link {
+        response.push_str(&format!("\n{}", link));
+    }
+    response
+}
+
+pub fn queue_notification(queue: &QueueModel) -> String {
+    format!("🙋 Ваша черга відповідати в черзі '{}'", queue.title)
+}
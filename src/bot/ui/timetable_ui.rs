@@ -1,8 +1,16 @@
 use std::collections::{BTreeMap, HashMap};
 
+use chrono::NaiveTime;
+
 use crate::{
-    bot::timetable::{Day, Week},
-    models::timetable::TimetableEntryModel,
+    bot::timetable::{calendar::AcademicCalendar, sync::TimetableDiff, Day, Week},
+    models::{
+        chat::HolidayModel,
+        timetable::{
+            ReminderSettingsModel, TimetableEntryModel, TimetableModel, TimetableOverrideModel,
+        },
+        user::UserModel,
+    },
 };
 
 use super::utils::adapt_for_markdown;
@@ -80,21 +88,79 @@ pub fn week_view(entries: Vec<TimetableEntryModel>) -> String {
 
 pub fn edit_view(entries: Vec<TimetableEntryModel>) -> String {
     let mut response = String::new();
-    let mut day: Day = Day::Mon;
-    let mut week: Week = Week::First;
+    let mut current: Option<(i32, i32)> = None;
     for entry in entries {
-        if entry.week == u8::from(week) as i32 {
-            response.push_str(&format!("\n*📅 {}*\n", week));
-            week = week.next();
-            day = Day::Mon;
+        if current.map(|(week, _)| week) != Some(entry.week) {
+            response.push_str(&format!("\n*📅 {}*\n", stored_week(entry.week)));
         }
-        if entry.day == u8::from(day) as i32 {
-            response.push_str(&format!("\n*{}*\n", day.to_string()));
-            day = day.next();
+        if current != Some((entry.week, entry.day)) {
+            response.push_str(&format!("\n*{}*\n", Day::from(entry.day)));
         }
+        current = Some((entry.week, entry.day));
         response.push_str(&entry_row(&entry, true));
     }
-    return response;
+    if response.is_empty() {
+        response = adapt_for_markdown(
+            &"Розклад порожній. Додайте пару кнопкою нижче або імпортуйте розклад за допомогою команди /import\n".to_string(),
+        );
+    }
+    response
+}
+
+/// Weeks are stored as `0` and `1`.
+fn stored_week(week: i32) -> Week {
+    match week {
+        0 => Week::First,
+        _ => Week::Second,
+    }
+}
+
+/// Short label of an entry for editor buttons, e.g. `1 Пн 08:30 Алгебра`.
+pub fn entry_button_label(entry: &TimetableEntryModel) -> String {
+    let day: &str = Day::from(entry.day).into();
+    let short_day: String = day.chars().take(2).collect();
+    let short_name: String = entry.class_name.chars().take(20).collect();
+    format!(
+        "{} {} {} {}",
+        entry.week + 1,
+        short_day,
+        entry.class_time.format("%H:%M"),
+        short_name
+    )
+}
+
+pub fn entry_editor_view(
+    entry: &TimetableEntryModel,
+    overrides: &[TimetableOverrideModel],
+) -> String {
+    let mut response = format!(
+        "{} {}\n{}, {}, {}\nПосилання: {}\n",
+        class_type_identifier(&entry.class_type),
+        entry.class_name,
+        stored_week(entry.week),
+        Day::from(entry.day),
+        entry.class_time.format("%H:%M"),
+        entry.link.as_deref().unwrap_or("немає")
+    );
+    if !overrides.is_empty() {
+        response.push_str("\nРазові зміни:\n");
+        for override_ in overrides {
+            response.push_str(&format!("{}\n", override_row(override_)));
+        }
+    }
+    response
+}
+
+pub fn override_row(override_: &TimetableOverrideModel) -> String {
+    let class_date = override_.class_date.format("%Y-%m-%d");
+    match override_.moved_to {
+        Some(moved_to) => format!(
+            "{} перенесено на {}",
+            class_date,
+            moved_to.format("%Y-%m-%d %H:%M")
+        ),
+        None => format!("{} скасовано", class_date),
+    }
 }
 
 pub fn entry_view(entry: Option<TimetableEntryModel>) -> String {
@@ -104,15 +170,68 @@ pub fn entry_view(entry: Option<TimetableEntryModel>) -> String {
     let entry = entry.unwrap();
     let identifier = class_type_identifier(&entry.class_type);
     let time = entry.class_time.format("%H:%M").to_string();
+    let subgroup = if entry.subgroup.is_empty() {
+        String::new()
+    } else {
+        format!("\nПідгрупа: {}", entry.subgroup)
+    };
     adapt_for_markdown(&format!(
-        "🔔 *> НАГАДУВАННЯ* < 🔔\n\n{} {}\nПочаток: {} {}\n\nПосилання на конференцію ⬇️",
+        "🔔 *> НАГАДУВАННЯ* < 🔔\n\n{} {}{}\nПочаток: {} {}\n\nПосилання на конференцію ⬇️",
         identifier,
         entry.class_name,
+        subgroup,
         time,
         get_time_emoji(&time)
     ))
 }
 
+pub fn subgroups_view(
+    subgroups: &[(TimetableModel, i64)],
+    current: Option<&TimetableModel>,
+) -> String {
+    if subgroups.is_empty() {
+        return "Підгруп ще немає. Імпортуйте розклад підгрупи командою /import <група> <підгрупа>"
+            .to_string();
+    }
+
+    let mut response = "👥 Підгрупи:\n".to_string();
+    for (subgroup, members) in subgroups {
+        response.push_str(&format!("• {} — учасників: {}\n", subgroup.name, members));
+    }
+    match current {
+        Some(current) => response.push_str(&format!(
+            "\nВи в підгрупі {}. Вийти: /subgroup off",
+            current.name
+        )),
+        None => response.push_str("\nПриєднатися: /subgroup <назва>"),
+    }
+    response
+}
+
+/// Mentions of the subgroup's members, appended to its lesson reminders.
+pub fn subgroup_mentions(members: &[UserModel]) -> String {
+    let mentions: Vec<String> = members
+        .iter()
+        .map(|member| {
+            if member.username.is_empty() {
+                let name: String = member
+                    .name
+                    .chars()
+                    .filter(|c| !"[]()*`\\".contains(*c))
+                    .collect();
+                format!(
+                    "[{}](tg://user?id={})",
+                    adapt_for_markdown(&name),
+                    member.account_id
+                )
+            } else {
+                adapt_for_markdown(&format!("@{}", member.username))
+            }
+        })
+        .collect();
+    format!("\n\n👥 {}", mentions.join(" "))
+}
+
 pub fn update_link_view(entry: &TimetableEntryModel) -> String {
     adapt_for_markdown(&format!(
         "Надішліть посилання для пари *{} {} {}*\n",
@@ -142,8 +261,12 @@ fn entry_row(entry: &TimetableEntryModel, edit: bool) -> String {
     }
     let formatted_time = entry.class_time.format("%H:%M").to_string();
     adapt_for_markdown(&format!(
-        "{} {} - {} {}\n",
-        identifier, formatted_time, link, edit_link
+        "{} {} - {}{} {}\n",
+        identifier,
+        formatted_time,
+        link,
+        subgroup_label(entry),
+        edit_link
     ))
 }
 
@@ -157,7 +280,161 @@ fn entry_row_no_time(entry: &TimetableEntryModel) -> String {
     if let Some(entry_link) = &entry.link {
         link = format!("[{}]({})", short_name, entry_link);
     }
-    adapt_for_markdown(&format!("{}", link))
+    adapt_for_markdown(&format!("{}{}", link, subgroup_label(entry)))
+}
+
+fn subgroup_label(entry: &TimetableEntryModel) -> String {
+    if entry.subgroup.is_empty() {
+        String::new()
+    } else {
+        format!(" · 👥{}", entry.subgroup)
+    }
+}
+
+pub fn calendar_view(calendar: &AcademicCalendar) -> String {
+    let today = calendar.today();
+    let mut response = format!(
+        "🗓 Часовий пояс: {}\nПочаток семестру: {}\nЗараз: {}\n",
+        calendar.timezone,
+        calendar
+            .semester_start
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "не вказано (/semester_start)".to_string()),
+        calendar.week_of(today)
+    );
+
+    if calendar.holidays.is_empty() {
+        response.push_str("\nКанікул не додано (/add_holiday)");
+    } else {
+        response.push_str("\nКанікули:\n");
+        for holiday in calendar.holidays.iter() {
+            response.push_str(&format!("{}\n", holiday_row(holiday)));
+        }
+    }
+
+    response
+}
+
+pub fn holiday_row(holiday: &HolidayModel) -> String {
+    let mut row = format!(
+        "#{} {} — {}",
+        holiday.id,
+        holiday.starts_on.format("%Y-%m-%d"),
+        holiday.ends_on.format("%Y-%m-%d")
+    );
+    if !holiday.title.is_empty() {
+        row.push_str(&format!(" {}", holiday.title));
+    }
+    row
+}
+
+pub fn reminder_settings_view(settings: &ReminderSettingsModel) -> String {
+    let offsets = if settings.offsets.is_empty() {
+        "вимкнено".to_string()
+    } else {
+        settings
+            .offsets
+            .iter()
+            .map(|offset| format!("{} хв", offset))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let muted = if settings.muted_class_types.is_empty() {
+        "немає".to_string()
+    } else {
+        settings
+            .muted_class_types
+            .iter()
+            .map(|class_type| class_type_label(class_type).trim_end_matches(':'))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let digest = settings
+        .digest_time
+        .map(|time| time.format("%H:%M").to_string())
+        .unwrap_or_else(|| "вимкнено".to_string());
+
+    let attendance = if settings.attendance {
+        "увімкнено"
+    } else {
+        "вимкнено"
+    };
+
+    format!(
+        "🔔 Нагадування до пари: {}\nБез нагадувань: {}\nРанковий розклад: {}\nВідмітка присутності: {}",
+        offsets, muted, digest, attendance
+    )
+}
+
+/// Announcement of the changes found when the timetable was re-synced.
+pub fn timetable_diff_view(diff: &TimetableDiff, subgroup: &str) -> String {
+    let title = if subgroup.is_empty() {
+        "🔄 Розклад оновлено".to_string()
+    } else {
+        format!("🔄 Розклад підгрупи {} оновлено", subgroup)
+    };
+    let mut sections = vec![title];
+    if !diff.added.is_empty() {
+        let rows: Vec<String> = diff
+            .added
+            .iter()
+            .map(|entry| {
+                format!(
+                    "➕ {} {}",
+                    slot(
+                        entry.week as i32,
+                        u8::from(entry.day) as i32,
+                        entry.class_time
+                    ),
+                    entry.class_name
+                )
+            })
+            .collect();
+        sections.push(format!("Додано:\n{}", rows.join("\n")));
+    }
+    if !diff.moved.is_empty() {
+        let rows: Vec<String> = diff
+            .moved
+            .iter()
+            .map(|(entry, moved)| {
+                format!(
+                    "🔁 {}: {} → {}",
+                    entry.class_name,
+                    slot(entry.week, entry.day, entry.class_time),
+                    slot(
+                        moved.week as i32,
+                        u8::from(moved.day) as i32,
+                        moved.class_time
+                    )
+                )
+            })
+            .collect();
+        sections.push(format!("Перенесено:\n{}", rows.join("\n")));
+    }
+    if !diff.removed.is_empty() {
+        let rows: Vec<String> = diff
+            .removed
+            .iter()
+            .map(|entry| {
+                format!(
+                    "➖ {} {}",
+                    slot(entry.week, entry.day, entry.class_time),
+                    entry.class_name
+                )
+            })
+            .collect();
+        sections.push(format!("Вилучено:\n{}", rows.join("\n")));
+    }
+    sections.join("\n\n")
+}
+
+fn slot(week: i32, day: i32, class_time: NaiveTime) -> String {
+    format!(
+        "{} тиждень, {} {}",
+        week + 1,
+        Day::from(day),
+        class_time.format("%H:%M")
+    )
 }
 
 pub fn class_type_identifier(class_type: &str) -> &str {
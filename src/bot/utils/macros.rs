@@ -1,9 +1,13 @@
 #[macro_export]
 macro_rules! delete_message {
     ($state:ident, $msg:ident) => {
-        $state.sender.send(crate::Event::DeleteMessage {
+        delete_message!($state, $msg, crate::models::cleanup::CleanupKind::General);
+    };
+    ($state:ident, $msg:ident, $kind:expr) => {
+        $state.events.send(crate::Event::DeleteMessage {
             chat_id: $msg.chat.id,
             message_id: $msg.id,
+            kind: $kind,
         })?;
     };
 }
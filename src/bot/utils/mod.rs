@@ -1,5 +1,6 @@
 pub mod macros;
 pub mod params;
+pub mod permissions;
 pub mod random;
 pub mod reply_markup_builder;
 pub mod time;
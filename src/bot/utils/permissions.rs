@@ -0,0 +1,27 @@
+use teloxide::{
+    prelude::Requester,
+    types::{ChatId, Message, UserId},
+    Bot,
+};
+
+/// Whether the author of the message is an owner or administrator of the chat.
+pub async fn is_privileged(bot: &Bot, msg: &Message) -> anyhow::Result<bool> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(false);
+    };
+    is_user_privileged(bot, msg.chat.id, user.id).await
+}
+
+/// Like [`is_privileged`], for updates without a message such as button presses.
+/// Everyone manages their own private chat with the bot.
+pub async fn is_user_privileged(
+    bot: &Bot,
+    chat_id: ChatId,
+    user_id: UserId,
+) -> anyhow::Result<bool> {
+    if chat_id.is_user() {
+        return Ok(true);
+    }
+    let chat_member = bot.get_chat_member(chat_id, user_id).await?;
+    Ok(chat_member.is_privileged())
+}
@@ -1,8 +1,18 @@
-use chrono::FixedOffset;
+use chrono_tz::Tz;
 
-pub fn get_current_time() -> chrono::DateTime<FixedOffset> {
-    let timezone_offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
-    let now = chrono::Utc::now().with_timezone(&timezone_offset);
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::Kyiv;
 
-    now
+pub fn parse_timezone(timezone: &str) -> Tz {
+    timezone.parse::<Tz>().unwrap_or_else(|_| {
+        tracing::warn!(
+            "Unknown timezone '{}', falling back to {}",
+            timezone,
+            DEFAULT_TIMEZONE
+        );
+        DEFAULT_TIMEZONE
+    })
+}
+
+pub fn get_local_time(timezone: &str) -> chrono::DateTime<Tz> {
+    chrono::Utc::now().with_timezone(&parse_timezone(timezone))
 }
@@ -0,0 +1,47 @@
+use std::env;
+
+/// Limits and exchange rate of the WebApp clicker.
+#[derive(Debug, Clone)]
+pub struct ClickerConfig {
+    pub clicks_per_coin: i64,
+    pub daily_click_cap: i64,
+    pub max_clicks_per_second: i64,
+}
+
+impl Default for ClickerConfig {
+    fn default() -> Self {
+        Self {
+            clicks_per_coin: 10,
+            daily_click_cap: 1000,
+            max_clicks_per_second: 15,
+        }
+    }
+}
+
+impl ClickerConfig {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            clicks_per_coin: env_or("CLICKER_CLICKS_PER_COIN", default.clicks_per_coin).max(1),
+            daily_click_cap: env_or("CLICKER_DAILY_CAP", default.daily_click_cap),
+            max_clicks_per_second: env_or(
+                "CLICKER_MAX_CLICKS_PER_SECOND",
+                default.max_clicks_per_second,
+            ),
+        }
+    }
+}
+
+fn env_or(name: &str, default: i64) -> i64 {
+    match env::var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Environment variable {} is not a number, using default value of {}",
+                name,
+                default
+            );
+            default
+        }),
+        Err(_) => default,
+    }
+}
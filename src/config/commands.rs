@@ -16,6 +16,12 @@ pub enum Command {
     #[command(description = "Відкрити довідку користувача")]
     Help,
 
+    #[command(description = "Налаштувати затримку видалення повідомлень бота")]
+    CleanupDelay,
+
+    #[command(description = "Видалити повідомлення бота зараз")]
+    Clear,
+
     // Queues
     #[command(description = "Створити чергу")]
     Queue,
@@ -26,6 +32,30 @@ pub enum Command {
     #[command(description = "Створити чергу з пріоритетом")]
     PriorityQueue,
 
+    #[command(description = "Створити чергу з жеребкуванням серед тих, хто встигне приєднатися")]
+    Lottery,
+
+    #[command(description = "Перемістити учасника черги на іншу позицію")]
+    QueueMove,
+
+    #[command(description = "Поміняти місцями двох учасників черги")]
+    QueueSwap,
+
+    #[command(description = "Видалити учасника з черги")]
+    QueueKick,
+
+    #[command(description = "Додати учасника до черги")]
+    QueueAdd,
+
+    #[command(description = "Показати історію змін черги")]
+    QueueHistory,
+
+    #[command(description = "Налаштувати відкриття, закриття та ліміт черги")]
+    QueueSchedule,
+
+    #[command(description = "Налаштувати вагу попередніх захистів у чергах з пріоритетом")]
+    QueuePriority,
+
     // Schedule
     #[command(description = "Імпортувати існуюючий розклад")]
     Import,
@@ -33,6 +63,15 @@ pub enum Command {
     #[command(description = "Редагувати розклад")]
     EditTimetable,
 
+    #[command(description = "Експортувати розклад у календар (.ics)")]
+    ExportIcs,
+
+    #[command(description = "Показати підгрупи або приєднатися до підгрупи")]
+    Subgroup,
+
+    #[command(description = "Видалити підгрупу з її розкладом")]
+    RemoveSubgroup,
+
     #[command(description = "Показати розклад на сьогодні")]
     Today,
 
@@ -48,6 +87,62 @@ pub enum Command {
     #[command(description = "Показати наступне заняття")]
     Next,
 
+    #[command(description = "Показати календар семестру")]
+    Calendar,
+
+    #[command(description = "Встановити часовий пояс чату")]
+    Timezone,
+
+    #[command(description = "Встановити дату початку семестру")]
+    SemesterStart,
+
+    #[command(description = "Додати канікули")]
+    AddHoliday,
+
+    #[command(description = "Видалити канікули")]
+    RemoveHoliday,
+
+    #[command(description = "Показати налаштування нагадувань")]
+    Reminders,
+
+    #[command(description = "За скільки хвилин нагадувати про пару")]
+    ReminderOffsets,
+
+    #[command(description = "Вимкнути або увімкнути нагадування для типу пар")]
+    MuteClassType,
+
+    #[command(description = "Час ранкового розкладу")]
+    Digest,
+
+    #[command(description = "Відвідування пар")]
+    Attendance,
+
+    // Deadlines
+    #[command(description = "Додати дедлайн")]
+    Deadline,
+
+    #[command(description = "Показати дедлайни")]
+    Deadlines,
+
+    #[command(description = "Видалити дедлайн")]
+    RemoveDeadline,
+
+    #[command(description = "Коли нагадувати про дедлайни")]
+    DeadlineReminders,
+
+    // Subscriptions
+    #[command(description = "Отримувати сповіщення групи в особисті")]
+    Subscribe,
+
+    #[command(description = "Скасувати підписку на сповіщення")]
+    Unsubscribe,
+
+    #[command(description = "Показати свої підписки")]
+    Subscriptions,
+
+    #[command(description = "Тихі години для особистих сповіщень")]
+    QuietHours,
+
     // Stats
     #[command(description = "Показати статистику")]
     Stats,
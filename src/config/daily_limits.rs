@@ -0,0 +1,50 @@
+use std::env;
+
+/// Rules used by the nightly reset to recompute each member's reaction allowance.
+#[derive(Debug, Clone)]
+pub struct DailyLimitConfig {
+    pub base_limit: i32,
+    pub message_bonus: i32,
+    pub message_bonus_cap: i32,
+    pub streak_bonus: i32,
+    pub streak_bonus_cap: i32,
+}
+
+impl Default for DailyLimitConfig {
+    fn default() -> Self {
+        Self {
+            base_limit: 100,
+            message_bonus: 1,
+            message_bonus_cap: 50,
+            streak_bonus: 10,
+            streak_bonus_cap: 70,
+        }
+    }
+}
+
+impl DailyLimitConfig {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            base_limit: env_or("DAILY_LIMIT_BASE", default.base_limit),
+            message_bonus: env_or("DAILY_LIMIT_MESSAGE_BONUS", default.message_bonus),
+            message_bonus_cap: env_or("DAILY_LIMIT_MESSAGE_BONUS_CAP", default.message_bonus_cap),
+            streak_bonus: env_or("DAILY_LIMIT_STREAK_BONUS", default.streak_bonus),
+            streak_bonus_cap: env_or("DAILY_LIMIT_STREAK_BONUS_CAP", default.streak_bonus_cap),
+        }
+    }
+}
+
+fn env_or(name: &str, default: i32) -> i32 {
+    match env::var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Environment variable {} is not a number, using default value of {}",
+                name,
+                default
+            );
+            default
+        }),
+        Err(_) => default,
+    }
+}
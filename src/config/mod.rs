@@ -1,2 +1,4 @@
+pub mod clicker;
 pub mod commands;
+pub mod daily_limits;
 pub mod state;
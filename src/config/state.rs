@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
 use teloxide::{
-    dispatching::dialogue::InMemStorage,
     prelude::Dialogue,
     types::{ChatId, MessageId, UserId},
 };
 
-#[derive(Clone, Default)]
+use crate::redis::dialogue::RedisDialogueStorage;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub enum StateMachine {
     #[default]
     Start,
@@ -20,6 +22,21 @@ pub enum StateMachine {
         message_id: MessageId,
         user_id: UserId,
     },
+    ReceiveTimetableEdit {
+        edit: TimetableEdit,
+        user_id: UserId,
+    },
+}
+
+/// Timetable change that waits for a text reply from the user who started it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum TimetableEdit {
+    AddEntry,
+    Rename { entry_id: i32 },
+    Retime { entry_id: i32 },
+    Link { entry_id: i32 },
+    Cancel { entry_id: i32 },
+    Move { entry_id: i32 },
 }
 
-pub type BotDialogue = Dialogue<StateMachine, InMemStorage<StateMachine>>;
+pub type BotDialogue = Dialogue<StateMachine, RedisDialogueStorage>;
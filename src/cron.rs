@@ -1,15 +1,83 @@
 use tokio_cron_scheduler::{Job, JobScheduler};
 
-use crate::{bot::timetable::schedule::timetable_notifications, state::State};
+use crate::{
+    api::clicker::flush_clicks,
+    bot::{
+        deadlines::reminders::{deadline_reminders, prune_past_deadlines},
+        events::report_event_stats,
+        queues::{lottery::queue_lotteries, schedule::queue_schedules},
+        stats::daily_reset::daily_limit_reset,
+        timetable::{
+            schedule::{prune_notifications, timetable_notifications},
+            sync::resync_timetables,
+        },
+    },
+    state::State,
+};
 
 pub async fn cron_loop(state: State) -> anyhow::Result<()> {
     let scheduler = JobScheduler::new().await?;
 
+    let notifications_state = state.clone();
     let notifications = Job::new_async("0 * * * * *", move |_uuid, _lock| {
-        Box::pin(timetable_notifications(state.clone()))
+        Box::pin(timetable_notifications(notifications_state.clone()))
+    })?;
+
+    let prune_state = state.clone();
+    let prune = Job::new_async("0 0 4 * * *", move |_uuid, _lock| {
+        Box::pin(prune_notifications(prune_state.clone()))
+    })?;
+
+    let deadlines_state = state.clone();
+    let deadlines = Job::new_async("0 * * * * *", move |_uuid, _lock| {
+        Box::pin(deadline_reminders(deadlines_state.clone()))
+    })?;
+
+    let prune_deadlines_state = state.clone();
+    let prune_deadlines = Job::new_async("0 10 4 * * *", move |_uuid, _lock| {
+        Box::pin(prune_past_deadlines(prune_deadlines_state.clone()))
+    })?;
+
+    let queue_schedules_state = state.clone();
+    let queue_schedules = Job::new_async("0 * * * * *", move |_uuid, _lock| {
+        Box::pin(queue_schedules(queue_schedules_state.clone()))
+    })?;
+
+    let queue_lotteries_state = state.clone();
+    let queue_lotteries = Job::new_async("*/10 * * * * *", move |_uuid, _lock| {
+        Box::pin(queue_lotteries(queue_lotteries_state.clone()))
+    })?;
+
+    let resync_state = state.clone();
+    let resync = Job::new_async("0 30 */6 * * *", move |_uuid, _lock| {
+        Box::pin(resync_timetables(resync_state.clone()))
+    })?;
+
+    let daily_reset_state = state.clone();
+    let daily_reset = Job::new_async("0 */5 * * * *", move |_uuid, _lock| {
+        Box::pin(daily_limit_reset(daily_reset_state.clone()))
+    })?;
+
+    let clicker_state = state.clone();
+    let clicker_flush = Job::new_async("*/30 * * * * *", move |_uuid, _lock| {
+        Box::pin(flush_clicks(clicker_state.clone()))
+    })?;
+
+    let event_stats_state = state.clone();
+    let event_stats = Job::new_async("0 */5 * * * *", move |_uuid, _lock| {
+        Box::pin(report_event_stats(event_stats_state.clone()))
     })?;
 
     scheduler.add(notifications).await?;
+    scheduler.add(prune).await?;
+    scheduler.add(resync).await?;
+    scheduler.add(deadlines).await?;
+    scheduler.add(prune_deadlines).await?;
+    scheduler.add(queue_schedules).await?;
+    scheduler.add(queue_lotteries).await?;
+    scheduler.add(daily_reset).await?;
+    scheduler.add(clicker_flush).await?;
+    scheduler.add(event_stats).await?;
 
     scheduler.start().await?;
 
@@ -11,10 +11,11 @@ pub mod state;
 use std::env;
 
 use bot::handler::handler;
-use config::{commands::Command, state::StateMachine};
+use config::commands::Command;
 use dotenvy::dotenv;
+use redis::dialogue::RedisDialogueStorage;
 use state::{AppState, Event, State};
-use teloxide::{dispatching::dialogue::InMemStorage, prelude::*};
+use teloxide::prelude::*;
 use tokio::signal;
 use tracing_subscriber::EnvFilter;
 
@@ -29,6 +30,12 @@ async fn main() {
     tracing::info!("Starting app");
 
     let db = repositories::setup::connect_db().await;
+
+    if env::args().any(|arg| arg == "--migrate-only") {
+        tracing::info!("Started with --migrate-only, exiting after migrations");
+        return;
+    }
+
     let redis = redis::setup::RedisStore::from_env();
 
     let state = AppState::new(db, redis);
@@ -51,7 +58,7 @@ async fn main() {
 
     let mut dispatcher = Dispatcher::builder(bot, handler())
         .dependencies(dptree::deps![
-            InMemStorage::<StateMachine>::new(),
+            RedisDialogueStorage::new(&state.redis),
             state.clone()
         ])
         .error_handler(LoggingErrorHandler::with_custom_text("An error occurred"))
@@ -69,23 +76,11 @@ async fn main() {
         tracing::error!("Failed to start cron loop: {:?}", e);
     }
 
-    let mut recv = state.sender.subscribe();
-
-    tokio::spawn(async move {
-        tracing::info!("Enabled graceful shutdown. Press Ctrl+C to exit");
-        signal::ctrl_c().await.unwrap();
-        state.sender.send(Event::Exit).unwrap();
-    });
+    tracing::info!("Enabled graceful shutdown. Press Ctrl+C to exit");
+    signal::ctrl_c().await.unwrap();
 
-    loop {
-        tokio::select! {
-            Ok(event) = recv.recv() => {
-                if let Event::Exit = event {
-                    tracing::info!("Received shutdown signal");
-                    _ = shutdown_token.shutdown();
-                    std::process::exit(0);
-                }
-            }
-        }
-    }
+    tracing::info!("Received shutdown signal");
+    _ = state.events.send(Event::Exit);
+    _ = shutdown_token.shutdown();
+    std::process::exit(0);
 }
@@ -0,0 +1,34 @@
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::FromRow;
+
+/// A held lesson users can check in to.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct LessonOccurrenceModel {
+    pub id: i32,
+    pub chat_id: i64,
+    pub entry_id: Option<i32>,
+    pub class_name: String,
+    pub class_date: NaiveDate,
+    pub class_time: NaiveTime,
+}
+
+/// Attendance of a chat member for one subject.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct AttendanceModel {
+    pub user_id: i32,
+    pub name: String,
+    pub username: String,
+    pub class_name: String,
+    /// Lessons of the subject that were held.
+    pub held: i64,
+    pub attended: i64,
+}
+
+impl AttendanceModel {
+    pub fn percentage(&self) -> f64 {
+        if self.held == 0 {
+            return 0.0;
+        }
+        self.attended as f64 * 100.0 / self.held as f64
+    }
+}
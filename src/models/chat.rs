@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -7,4 +8,15 @@ pub struct ChatModel {
     pub chat_id: i64,
     pub title: String,
     pub description: Option<String>,
+    pub timezone: String,
+}
+
+/// Date range (inclusive) with no classes and no timetable notifications.
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize)]
+pub struct HolidayModel {
+    pub id: i32,
+    pub chat_id: i64,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub title: String,
 }
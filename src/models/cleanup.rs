@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+/// What a message scheduled for deletion belongs to; chats can set a delay per kind.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupKind {
+    General,
+    Stats,
+    Timetable,
+    Gamble,
+}
+
+impl CleanupKind {
+    pub const ALL: [CleanupKind; 4] = [
+        CleanupKind::General,
+        CleanupKind::Stats,
+        CleanupKind::Timetable,
+        CleanupKind::Gamble,
+    ];
+}
+
+impl From<CleanupKind> for String {
+    fn from(kind: CleanupKind) -> Self {
+        match kind {
+            CleanupKind::General => "general".to_string(),
+            CleanupKind::Stats => "stats".to_string(),
+            CleanupKind::Timetable => "timetable".to_string(),
+            CleanupKind::Gamble => "gamble".to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for CleanupKind {
+    type Error = anyhow::Error;
+
+    fn try_from(kind: &str) -> Result<Self, Self::Error> {
+        match kind {
+            "general" => Ok(CleanupKind::General),
+            "stats" => Ok(CleanupKind::Stats),
+            "timetable" => Ok(CleanupKind::Timetable),
+            "gamble" => Ok(CleanupKind::Gamble),
+            _ => Err(anyhow::anyhow!("Unknown cleanup kind: {}", kind)),
+        }
+    }
+}
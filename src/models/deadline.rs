@@ -0,0 +1,32 @@
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct DeadlineModel {
+    pub id: i32,
+    pub chat_id: i64,
+    pub title: String,
+    /// Class of the timetable the task belongs to, if any.
+    pub class_name: Option<String>,
+    /// Chat-local date and time the task is due.
+    pub due_at: NaiveDateTime,
+    /// Telegram account of the user who added the task.
+    pub created_by: i64,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct DeadlineSettingsModel {
+    pub chat_id: i64,
+    /// Minutes before the due date at which a reminder is sent.
+    pub offsets: Vec<i32>,
+}
+
+impl DeadlineSettingsModel {
+    pub fn default_for(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            offsets: vec![1440, 180],
+        }
+    }
+}
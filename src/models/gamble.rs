@@ -3,6 +3,9 @@ use teloxide::types::MessageId;
 #[derive(Debug, Clone, PartialEq)]
 pub enum GambleType {
     Bet,
+    Wheel,
+    Slots,
+    Roulette,
     Unknown,
 }
 
@@ -10,6 +13,9 @@ impl From<GambleType> for String {
     fn from(gamble_type: GambleType) -> Self {
         match gamble_type {
             GambleType::Bet => "bet".to_string(),
+            GambleType::Wheel => "wheel".to_string(),
+            GambleType::Slots => "slots".to_string(),
+            GambleType::Roulette => "roulette".to_string(),
             GambleType::Unknown => "unknown".to_string(),
         }
     }
@@ -19,6 +25,9 @@ impl From<&str> for GambleType {
     fn from(gamble_type: &str) -> Self {
         match gamble_type {
             "bet" => GambleType::Bet,
+            "wheel" => GambleType::Wheel,
+            "slots" => GambleType::Slots,
+            "roulette" => GambleType::Roulette,
             _ => GambleType::Unknown,
         }
     }
@@ -32,4 +41,29 @@ pub struct GambleDto {
     pub change: i32,
     pub bet: i32,
     pub gamble_type: GambleType,
+    pub outcome: Option<String>,
+}
+
+/// A game played on the server whose result was derived from a committed seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FairGambleDto {
+    pub user_id: i32,
+    pub bet: i32,
+    pub change: i32,
+    pub is_win: bool,
+    pub gamble_type: GambleType,
+    pub outcome: String,
+    pub server_seed: String,
+    pub client_seed: String,
+}
+
+/// A wheel spin with the outcome decided by the bot. `used_free_spin` pays for
+/// it with a free spin, `won_free_spin` grants one and `limit_boost` raises
+/// today's reaction limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WheelSpinDto {
+    pub gamble: GambleDto,
+    pub used_free_spin: bool,
+    pub won_free_spin: bool,
+    pub limit_boost: i32,
 }
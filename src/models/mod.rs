@@ -1,6 +1,10 @@
+pub mod attendance;
 pub mod chat;
+pub mod cleanup;
+pub mod deadline;
 pub mod gamble;
 pub mod queue;
 pub mod stats;
+pub mod subscription;
 pub mod timetable;
 pub mod user;
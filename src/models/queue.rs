@@ -1,4 +1,5 @@
 use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
 #[derive(Debug, Clone, PartialEq, FromRow)]
@@ -11,6 +12,24 @@ pub struct QueueModel {
     pub is_priority: bool,
     pub is_deleted: bool,
     pub created_at: NaiveDateTime,
+    /// Chat-local time before which joins are rejected.
+    pub opens_at: Option<NaiveDateTime>,
+    /// Chat-local time at which the queue closes.
+    pub closes_at: Option<NaiveDateTime>,
+    /// The queue closes once this many users joined.
+    pub max_users: Option<i32>,
+    /// Lesson the queue is held at.
+    pub entry_id: Option<i32>,
+    /// Chat-local end of that lesson, after which the queue is archived.
+    pub archive_at: Option<NaiveDateTime>,
+    pub is_closed: bool,
+    pub is_archived: bool,
+    /// Seed of the draw that orders a lottery queue.
+    pub lottery_seed: Option<String>,
+    /// Chat-local end of the lottery window; `None` once the draw is done.
+    pub lottery_closes_at: Option<NaiveDateTime>,
+    /// Priority queues with the same subject share their history.
+    pub subject: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, FromRow)]
@@ -36,4 +55,121 @@ pub struct QueueUserWithUserModel {
     pub account_id: i64,
     pub chat_id_user: i64,
     pub name: String,
+    /// History weight the member was last ordered by in a priority queue.
+    pub weight: Option<f64>,
+    /// Defences of the subject counted in that weight.
+    pub defences: Option<i32>,
+    /// Chat-local time of the latest of them.
+    pub last_defended_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuePrioritySettingsModel {
+    pub chat_id: i64,
+    /// How many days back defences count.
+    pub window_days: i32,
+    /// A defence counts half as much after this many days; 0 disables decay.
+    pub half_life_days: i32,
+}
+
+impl QueuePrioritySettingsModel {
+    pub fn default_for(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            window_days: 90,
+            half_life_days: 30,
+        }
+    }
+}
+
+/// Changes to a queue, as stored in its history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueueAction {
+    Join,
+    Leave,
+    Shuffle,
+    Skip,
+    Done,
+    Freeze,
+    Delete,
+    Move,
+    Swap,
+    Kick,
+    Add,
+    Draw,
+}
+
+impl From<QueueAction> for String {
+    fn from(action: QueueAction) -> Self {
+        match action {
+            QueueAction::Join => "join".to_string(),
+            QueueAction::Leave => "leave".to_string(),
+            QueueAction::Shuffle => "shuffle".to_string(),
+            QueueAction::Skip => "skip".to_string(),
+            QueueAction::Done => "done".to_string(),
+            QueueAction::Freeze => "freeze".to_string(),
+            QueueAction::Delete => "delete".to_string(),
+            QueueAction::Move => "move".to_string(),
+            QueueAction::Swap => "swap".to_string(),
+            QueueAction::Kick => "kick".to_string(),
+            QueueAction::Add => "add".to_string(),
+            QueueAction::Draw => "draw".to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for QueueAction {
+    type Error = anyhow::Error;
+
+    fn try_from(action: &str) -> Result<Self, Self::Error> {
+        match action {
+            "join" => Ok(QueueAction::Join),
+            "leave" => Ok(QueueAction::Leave),
+            "shuffle" => Ok(QueueAction::Shuffle),
+            "skip" => Ok(QueueAction::Skip),
+            "done" => Ok(QueueAction::Done),
+            "freeze" => Ok(QueueAction::Freeze),
+            "delete" => Ok(QueueAction::Delete),
+            "move" => Ok(QueueAction::Move),
+            "swap" => Ok(QueueAction::Swap),
+            "kick" => Ok(QueueAction::Kick),
+            "add" => Ok(QueueAction::Add),
+            "draw" => Ok(QueueAction::Draw),
+            _ => Err(anyhow::anyhow!("Unknown queue action: {}", action)),
+        }
+    }
+}
+
+/// The state of a queue before a change. Members are stored in order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    pub is_mixed: Option<bool>,
+    #[serde(default)]
+    pub is_closed: bool,
+    pub users: Vec<QueueSnapshotUser>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueSnapshotUser {
+    pub user_id: i32,
+    pub priority: Option<i32>,
+    pub is_frozen: Option<bool>,
+}
+
+/// An entry of the queue history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueEventModel {
+    pub id: i32,
+    pub queue_id: i32,
+    pub chat_id: i64,
+    pub actor_id: Option<i32>,
+    pub actor_name: Option<String>,
+    pub action: QueueAction,
+    pub target_name: Option<String>,
+    pub details: String,
+    /// Chat-local time of the change.
+    pub created_at: NaiveDateTime,
+    pub undone_at: Option<NaiveDateTime>,
+    /// Changes logged before snapshots were kept can't be undone.
+    pub snapshot: Option<QueueSnapshot>,
 }
@@ -1,7 +1,8 @@
 use chrono::NaiveDateTime;
+use serde::Serialize;
 use sqlx::prelude::FromRow;
 
-#[derive(Debug, Clone, PartialEq, FromRow)]
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
 pub struct GambleModel {
     pub id: i32,
     pub user_id: i32,
@@ -10,16 +11,20 @@ pub struct GambleModel {
     pub bet: i32,
     pub change: i32,
     pub is_win: bool,
+    pub outcome: Option<String>,
+    pub server_seed: Option<String>,
+    pub client_seed: Option<String>,
     pub created_at: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, PartialEq, FromRow)]
 pub struct GroupMemberStat {
+    pub user_id: i32,
     pub username: String,
     pub balance: i32,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct FullStats {
     pub user_id: i32,
     pub balance: i32,
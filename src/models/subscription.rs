@@ -0,0 +1,66 @@
+use chrono::NaiveTime;
+use sqlx::FromRow;
+
+/// Notifications of a group that a user can receive in their private chat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubscriptionKind {
+    Lessons,
+    Queue,
+    Deadlines,
+}
+
+impl SubscriptionKind {
+    pub const ALL: [SubscriptionKind; 3] = [
+        SubscriptionKind::Lessons,
+        SubscriptionKind::Queue,
+        SubscriptionKind::Deadlines,
+    ];
+}
+
+impl From<SubscriptionKind> for String {
+    fn from(kind: SubscriptionKind) -> Self {
+        match kind {
+            SubscriptionKind::Lessons => "lessons".to_string(),
+            SubscriptionKind::Queue => "queue".to_string(),
+            SubscriptionKind::Deadlines => "deadlines".to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for SubscriptionKind {
+    type Error = anyhow::Error;
+
+    fn try_from(kind: &str) -> Result<Self, Self::Error> {
+        match kind {
+            "lessons" => Ok(SubscriptionKind::Lessons),
+            "queue" => Ok(SubscriptionKind::Queue),
+            "deadlines" => Ok(SubscriptionKind::Deadlines),
+            _ => Err(anyhow::anyhow!("Unknown subscription kind: {}", kind)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionModel {
+    pub account_id: i64,
+    pub chat_id: i64,
+    pub chat_title: String,
+    pub kind: SubscriptionKind,
+}
+
+/// Hours in which private notifications are delivered without sound. The range
+/// may wrap past midnight, e.g. 23:00–08:00.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct QuietHoursModel {
+    pub account_id: i64,
+    pub starts_at: NaiveTime,
+    pub ends_at: NaiveTime,
+}
+
+/// A subscriber of a group's notifications with their quiet hours, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriberModel {
+    pub account_id: i64,
+    pub chat_title: String,
+    pub quiet_hours: Option<QuietHoursModel>,
+}
@@ -1,4 +1,4 @@
-use chrono::NaiveTime;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -6,6 +6,14 @@ use sqlx::FromRow;
 pub struct TimetableModel {
     pub id: i32,
     pub chat_id: i64,
+    /// Empty for the timetable shared by the whole chat, otherwise the subgroup.
+    pub name: String,
+}
+
+impl TimetableModel {
+    pub fn is_subgroup(&self) -> bool {
+        !self.name.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize)]
@@ -18,4 +26,53 @@ pub struct TimetableEntryModel {
     pub class_type: String,
     pub class_time: NaiveTime,
     pub link: Option<String>,
+    /// Name of the subgroup the lesson is for; empty when it is for everyone.
+    #[serde(default)]
+    pub subgroup: String,
+}
+
+/// One-off change of a recurring entry on a single date.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct TimetableOverrideModel {
+    pub id: i32,
+    pub chat_id: i64,
+    pub entry_id: i32,
+    pub class_date: NaiveDate,
+    /// Chat-local date and time of the moved lesson, `None` if it is cancelled.
+    pub moved_to: Option<NaiveDateTime>,
+}
+
+/// Where the chat's timetable was imported from, so it can be fetched again.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct TimetableSourceModel {
+    pub chat_id: i64,
+    /// Only `kpi` for now; file imports are not re-synced.
+    pub source: String,
+    pub group_name: String,
+    /// Subgroup the schedule is imported into; empty for the whole chat.
+    pub subgroup: String,
+    pub synced_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct ReminderSettingsModel {
+    pub chat_id: i64,
+    /// Minutes before the start of a lesson at which a reminder is sent.
+    pub offsets: Vec<i32>,
+    pub muted_class_types: Vec<String>,
+    pub digest_time: Option<NaiveTime>,
+    /// Whether lesson reminders carry an "I'm here" button.
+    pub attendance: bool,
+}
+
+impl ReminderSettingsModel {
+    pub fn default_for(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            offsets: vec![3],
+            muted_class_types: Vec::new(),
+            digest_time: None,
+            attendance: false,
+        }
+    }
 }
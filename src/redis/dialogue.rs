@@ -0,0 +1,79 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use r2d2_redis::{redis::Commands, RedisConnectionManager};
+use serde::{de::DeserializeOwned, Serialize};
+use teloxide::{dispatching::dialogue::Storage, types::ChatId};
+
+use super::setup::RedisStore;
+
+pub const DIALOGUE_KEY_PREFIX: &str = "dialogue:";
+/// Dialogues untouched for this long are dropped, e.g. a link that was never sent.
+const DIALOGUE_TTL_SECS: usize = 60 * 60;
+
+type StorageFuture<T> = Pin<Box<dyn Future<Output = anyhow::Result<T>> + Send>>;
+
+/// Dialogue storage sharing the connection pool of [`RedisStore`], so that
+/// dialogues in progress survive a restart of the bot.
+pub struct RedisDialogueStorage {
+    pool: r2d2_redis::r2d2::Pool<RedisConnectionManager>,
+}
+
+impl RedisDialogueStorage {
+    pub fn new(store: &RedisStore) -> Arc<Self> {
+        Arc::new(Self {
+            pool: store.pool.clone(),
+        })
+    }
+
+    fn get_connection(&self) -> anyhow::Result<r2d2::PooledConnection<RedisConnectionManager>> {
+        self.pool.get().map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+fn dialogue_key(chat_id: ChatId) -> String {
+    format!("{}{}", DIALOGUE_KEY_PREFIX, chat_id)
+}
+
+impl<D> Storage<D> for RedisDialogueStorage
+where
+    D: Serialize + DeserializeOwned + Send + 'static,
+{
+    type Error = anyhow::Error;
+
+    fn remove_dialogue(self: Arc<Self>, chat_id: ChatId) -> StorageFuture<()>
+    where
+        D: Send + 'static,
+    {
+        Box::pin(async move {
+            let mut con = self.get_connection()?;
+            let _: () = con.del(dialogue_key(chat_id))?;
+
+            Ok(())
+        })
+    }
+
+    fn update_dialogue(self: Arc<Self>, chat_id: ChatId, dialogue: D) -> StorageFuture<()>
+    where
+        D: Send + 'static,
+    {
+        Box::pin(async move {
+            let mut con = self.get_connection()?;
+            let serialized = serde_json::to_string(&dialogue)?;
+            let _: () = con.set_ex(dialogue_key(chat_id), serialized, DIALOGUE_TTL_SECS)?;
+
+            Ok(())
+        })
+    }
+
+    fn get_dialogue(self: Arc<Self>, chat_id: ChatId) -> StorageFuture<Option<D>> {
+        Box::pin(async move {
+            let mut con = self.get_connection()?;
+            let serialized: Option<String> = con.get(dialogue_key(chat_id))?;
+
+            match serialized {
+                Some(serialized) => Ok(Some(serde_json::from_str(&serialized)?)),
+                None => Ok(None),
+            }
+        })
+    }
+}
@@ -1,9 +1,13 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
 use r2d2_redis::redis::Commands;
 use setup::RedisStore;
 use teloxide::types::{ChatId, Message, MessageId, UserId};
 
 use crate::models::{chat::ChatModel, timetable::TimetableEntryModel, user::UserModel};
 
+pub mod dialogue;
 pub mod setup;
 
 #[rustfmt::skip]
@@ -20,15 +24,48 @@ pub trait RedisCache {
     fn get_chat(&self, chat_id: ChatId) -> anyhow::Result<ChatModel>;
     fn store_chat_ids(&self, chat_ids: Vec<ChatId>) -> anyhow::Result<()>;
     fn get_all_chat_ids(&self) -> anyhow::Result<Vec<ChatId>>;
+    fn count_click_burst(&self, user_id: i32, second: i64, clicks: i64) -> anyhow::Result<i64>;
+    fn add_daily_clicks(&self, user_id: i32, date: NaiveDate, clicks: i64, cap: i64) -> anyhow::Result<(i64, i64)>;
+    fn add_pending_clicks(&self, user_id: i32, clicks: i64) -> anyhow::Result<()>;
+    fn take_pending_clicks(&self) -> anyhow::Result<(String, Vec<(i32, i64)>)>;
+    fn clear_flushed_clicks(&self) -> anyhow::Result<()>;
+    fn schedule_deletion(&self, chat_id: ChatId, message_id: MessageId, due_at: i64) -> anyhow::Result<()>;
+    fn take_due_deletions(&self, now: i64, limit: isize) -> anyhow::Result<Vec<(ChatId, MessageId)>>;
+    fn expedite_deletions(&self, chat_id: ChatId) -> anyhow::Result<usize>;
+    fn push_dead_letter(&self, entry: &str) -> anyhow::Result<()>;
 }
 
+/// Keys holding state rather than cache, kept when the cache is cleared on startup.
+#[cfg(not(debug_assertions))]
+const PERSISTENT_KEY_PREFIXES: &[&str] = &[
+    dialogue::DIALOGUE_KEY_PREFIX,
+    "clicker_",
+    PENDING_DELETIONS_KEY,
+    DEAD_LETTER_KEY,
+];
+/// Sorted set of `"{chat_id}:{message_id}"` scored by the unix time they are due.
+const PENDING_DELETIONS_KEY: &str = "pending_deletions";
+/// Events that kept failing, newest first; inspect with `LRANGE dead_letter_events 0 -1`.
+const DEAD_LETTER_KEY: &str = "dead_letter_events";
+const MAX_DEAD_LETTERS: isize = 1000;
+const PENDING_CLICKS_KEY: &str = "clicker_pending";
+const FLUSHING_CLICKS_KEY: &str = "clicker_flushing";
+/// Id of the batch in `FLUSHING_CLICKS_KEY`, recorded in Postgres once credited.
+const FLUSHING_BATCH_KEY: &str = "clicker_flushing_batch";
+
 impl RedisCache for RedisStore {
     fn clear_all_cache(&self) -> anyhow::Result<()> {
         #[cfg(not(debug_assertions))]
         {
-            use r2d2_redis::redis::ConnectionLike;
             let mut con = self.get_connection()?;
-            con.req_packed_command(&redis::cmd("FLUSHALL").get_packed_command())?;
+            let keys: Vec<String> = con.keys("*")?;
+            for key in keys.iter().filter(|key| {
+                !PERSISTENT_KEY_PREFIXES
+                    .iter()
+                    .any(|prefix| key.starts_with(prefix))
+            }) {
+                let _: () = con.del(key)?;
+            }
             tracing::info!("All cache has been deleted");
         }
         Ok(())
@@ -151,4 +188,129 @@ impl RedisCache for RedisStore {
 
         Ok(chat_ids)
     }
+    /// Adds `clicks` to the per-second bucket of the user and returns the bucket total.
+    fn count_click_burst(&self, user_id: i32, second: i64, clicks: i64) -> anyhow::Result<i64> {
+        let mut con = self.get_connection()?;
+        let key = format!("clicker_rate:{}:{}", user_id, second);
+        let total: i64 = con.incr(&key, clicks)?;
+        let _: () = con.expire(&key, 2)?;
+
+        Ok(total)
+    }
+    /// Counts clicks towards the daily cap. Returns how many of them were accepted
+    /// and the total accepted for `date`.
+    fn add_daily_clicks(
+        &self,
+        user_id: i32,
+        date: NaiveDate,
+        clicks: i64,
+        cap: i64,
+    ) -> anyhow::Result<(i64, i64)> {
+        let mut con = self.get_connection()?;
+        let key = format!("clicker_daily:{}:{}", user_id, date);
+        let total: i64 = con.incr(&key, clicks)?;
+        let _: () = con.expire(&key, 2 * 24 * 60 * 60)?;
+
+        let overflow = (total - cap).clamp(0, clicks);
+        if overflow > 0 {
+            let _: i64 = con.decr(&key, overflow)?;
+        }
+
+        Ok((clicks - overflow, total - overflow))
+    }
+    fn add_pending_clicks(&self, user_id: i32, clicks: i64) -> anyhow::Result<()> {
+        let mut con = self.get_connection()?;
+        let _: i64 = con.hincr(PENDING_CLICKS_KEY, user_id, clicks)?;
+
+        Ok(())
+    }
+    /// Moves pending clicks aside for flushing and returns them with the id of
+    /// the batch. A batch left by a failed flush is returned again, with the
+    /// same id, instead of taking a new one.
+    fn take_pending_clicks(&self) -> anyhow::Result<(String, Vec<(i32, i64)>)> {
+        let mut con = self.get_connection()?;
+        if !con.exists(FLUSHING_CLICKS_KEY)? {
+            if !con.exists(PENDING_CLICKS_KEY)? {
+                return Ok((String::new(), Vec::new()));
+            }
+            let _: () = con.del(FLUSHING_BATCH_KEY)?;
+            let _: () = con.rename(PENDING_CLICKS_KEY, FLUSHING_CLICKS_KEY)?;
+        }
+        let new_batch_id = format!("{:032x}", rand::random::<u128>());
+        let _: bool = con.set_nx(FLUSHING_BATCH_KEY, new_batch_id)?;
+        let batch_id: String = con.get(FLUSHING_BATCH_KEY)?;
+        let pending: HashMap<i32, i64> = con.hgetall(FLUSHING_CLICKS_KEY)?;
+
+        Ok((batch_id, pending.into_iter().collect()))
+    }
+    fn clear_flushed_clicks(&self) -> anyhow::Result<()> {
+        let mut con = self.get_connection()?;
+        let _: () = con.del(&[FLUSHING_CLICKS_KEY, FLUSHING_BATCH_KEY])?;
+
+        Ok(())
+    }
+    fn schedule_deletion(
+        &self,
+        chat_id: ChatId,
+        message_id: MessageId,
+        due_at: i64,
+    ) -> anyhow::Result<()> {
+        let mut con = self.get_connection()?;
+        let member = format!("{}:{}", chat_id, message_id);
+        let _: () = con.zadd(PENDING_DELETIONS_KEY, member, due_at)?;
+
+        Ok(())
+    }
+    /// Removes and returns deletions due by `now`. Only entries this call managed to
+    /// remove are returned, so concurrent workers never delete a message twice.
+    fn take_due_deletions(
+        &self,
+        now: i64,
+        limit: isize,
+    ) -> anyhow::Result<Vec<(ChatId, MessageId)>> {
+        let mut con = self.get_connection()?;
+        let members: Vec<String> =
+            con.zrangebyscore_limit(PENDING_DELETIONS_KEY, "-inf", now, 0, limit)?;
+
+        let mut due = Vec::new();
+        for member in members {
+            let removed: i64 = con.zrem(PENDING_DELETIONS_KEY, &member)?;
+            if removed == 0 {
+                continue;
+            }
+            let parsed = member.split_once(':').and_then(|(chat_id, message_id)| {
+                Some((
+                    ChatId(chat_id.parse().ok()?),
+                    MessageId(message_id.parse().ok()?),
+                ))
+            });
+            match parsed {
+                Some(deletion) => due.push(deletion),
+                None => tracing::warn!("Dropping malformed pending deletion: {}", member),
+            }
+        }
+
+        Ok(due)
+    }
+    /// Makes every pending deletion of the chat due immediately.
+    fn expedite_deletions(&self, chat_id: ChatId) -> anyhow::Result<usize> {
+        let mut con = self.get_connection()?;
+        let prefix = format!("{}:", chat_id);
+        let members: Vec<String> = con.zrange(PENDING_DELETIONS_KEY, 0, -1)?;
+
+        let mut expedited = 0;
+        for member in members.iter().filter(|member| member.starts_with(&prefix)) {
+            let _: () = con.zadd(PENDING_DELETIONS_KEY, member, 0)?;
+            expedited += 1;
+        }
+
+        Ok(expedited)
+    }
+    fn push_dead_letter(&self, entry: &str) -> anyhow::Result<()> {
+        let mut con = self.get_connection()?;
+        let _: () = con.lpush(DEAD_LETTER_KEY, entry)?;
+        let _: () = con.ltrim(DEAD_LETTER_KEY, 0, MAX_DEAD_LETTERS - 1)?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,266 @@
+use anyhow::Context;
+use chrono::NaiveDate;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use teloxide::types::ChatId;
+
+use crate::models::{
+    attendance::{AttendanceModel, LessonOccurrenceModel},
+    timetable::TimetableEntryModel,
+};
+
+fn occurrence_from_row(row: &PgRow) -> LessonOccurrenceModel {
+    LessonOccurrenceModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        entry_id: row.get("entry_id"),
+        class_name: row.get("class_name"),
+        class_date: row.get("class_date"),
+        class_time: row.get("class_time"),
+    }
+}
+
+/// Records that the lesson takes place on `class_date`. Several reminders of the
+/// same lesson share one occurrence.
+pub async fn record_occurrence(
+    pool: &PgPool,
+    chat_id: ChatId,
+    entry: &TimetableEntryModel,
+    class_date: NaiveDate,
+) -> anyhow::Result<LessonOccurrenceModel> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO lesson_occurrences (chat_id, entry_id, class_name, class_date, class_time)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (chat_id, entry_id, class_date) DO UPDATE
+        SET class_name = EXCLUDED.class_name, class_time = EXCLUDED.class_time
+        RETURNING id, chat_id, entry_id, class_name, class_date, class_time
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(entry.id)
+    .bind(&entry.class_name)
+    .bind(class_date)
+    .bind(entry.class_time)
+    .fetch_one(pool)
+    .await
+    .context(format!(
+        "Failed to record lesson occurrence of entry id: {}",
+        entry.id
+    ))?;
+
+    Ok(occurrence_from_row(&row))
+}
+
+pub async fn get_occurrence(
+    pool: &PgPool,
+    occurrence_id: i32,
+) -> anyhow::Result<Option<LessonOccurrenceModel>> {
+    let occurrence = sqlx::query(
+        r#"
+        SELECT id, chat_id, entry_id, class_name, class_date, class_time
+        FROM lesson_occurrences
+        WHERE id = $1
+        "#,
+    )
+    .bind(occurrence_id)
+    .fetch_optional(pool)
+    .await
+    .context(format!(
+        "Failed to query lesson occurrence id: {}",
+        occurrence_id
+    ))?
+    .map(|row| occurrence_from_row(&row));
+
+    Ok(occurrence)
+}
+
+/// Marks the user as present. Returns `false` if they already checked in.
+pub async fn check_in(pool: &PgPool, occurrence_id: i32, user_id: i32) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO attendance (occurrence_id, user_id)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(occurrence_id)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to check in to lesson occurrence id: {}",
+        occurrence_id
+    ))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn count_attendance(pool: &PgPool, occurrence_id: i32) -> anyhow::Result<i64> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS attended
+        FROM attendance
+        WHERE occurrence_id = $1
+        "#,
+    )
+    .bind(occurrence_id)
+    .fetch_one(pool)
+    .await
+    .context(format!(
+        "Failed to count attendance of lesson occurrence id: {}",
+        occurrence_id
+    ))?;
+
+    Ok(row.get("attended"))
+}
+
+/// Attendance of every chat member per subject for lessons held since `since`,
+/// or for all lessons when it is `None`. Lessons of a subgroup count only for
+/// its members.
+pub async fn get_attendance(
+    pool: &PgPool,
+    chat_id: ChatId,
+    since: Option<NaiveDate>,
+) -> anyhow::Result<Vec<AttendanceModel>> {
+    let attendance = sqlx::query(
+        r#"
+        SELECT u.id AS user_id, u.name, u.username, o.class_name,
+            COUNT(o.id) AS held, COUNT(a.user_id) AS attended
+        FROM users u
+        JOIN lesson_occurrences o ON o.chat_id = u.chat_id
+        LEFT JOIN timetable_entries te ON te.id = o.entry_id
+        LEFT JOIN timetables tt ON tt.id = te.timetable_id
+        LEFT JOIN subgroup_members sm ON sm.user_id = u.id
+        LEFT JOIN attendance a ON a.occurrence_id = o.id AND a.user_id = u.id
+        WHERE u.chat_id = $1 AND ($2::DATE IS NULL OR o.class_date >= $2)
+            AND (tt.id IS NULL OR tt.name = '' OR sm.timetable_id = tt.id)
+        GROUP BY u.id, u.name, u.username, o.class_name
+        ORDER BY u.name, u.id, o.class_name
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .context(format!(
+        "Failed to query attendance of chat_id: {}",
+        chat_id
+    ))?
+    .into_iter()
+    .map(|row| AttendanceModel {
+        user_id: row.get("user_id"),
+        name: row.get("name"),
+        username: row.get("username"),
+        class_name: row.get("class_name"),
+        held: row.get("held"),
+        attended: row.get("attended"),
+    })
+    .collect();
+
+    Ok(attendance)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::repositories::setup::MIGRATOR;
+
+    /// Needs a database: `cargo test -- --ignored` with `DATABASE_URL` set.
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_subgroup_attendance() {
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+        let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+
+        let chat_id = -1_000_000_000_102;
+        sqlx::query("DELETE FROM chats WHERE chat_id = $1")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO chats (chat_id) VALUES ($1)")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Each subgroup has one member and a lab of the same subject on the same day.
+        let class_date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let mut members = Vec::new();
+        let mut occurrences = Vec::new();
+        for (account_id, subgroup) in [(chat_id - 1, "1"), (chat_id - 2, "2")] {
+            let timetable_id: i32 =
+                sqlx::query("INSERT INTO timetables (chat_id, name) VALUES ($1, $2) RETURNING id")
+                    .bind(chat_id)
+                    .bind(subgroup)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+                    .get("id");
+            let entry_id: i32 = sqlx::query(
+                r#"
+                INSERT INTO timetable_entries
+                    (timetable_id, week, day, class_name, class_type, class_time)
+                VALUES ($1, 0, 0, 'Бази даних', 'Лаб', '10:25')
+                RETURNING id
+                "#,
+            )
+            .bind(timetable_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+            .get("id");
+            let user_id: i32 = sqlx::query(
+                "INSERT INTO users (account_id, chat_id, name) VALUES ($1, $2, $3) RETURNING id",
+            )
+            .bind(account_id)
+            .bind(chat_id)
+            .bind(subgroup)
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+            .get("id");
+            sqlx::query("INSERT INTO subgroup_members (user_id, timetable_id) VALUES ($1, $2)")
+                .bind(user_id)
+                .bind(timetable_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+
+            let entry = TimetableEntryModel {
+                id: entry_id,
+                week: 0,
+                day: 0,
+                timetable_id,
+                class_name: "Бази даних".to_string(),
+                class_type: "Лаб".to_string(),
+                class_time: NaiveTime::from_hms_opt(10, 25, 0).unwrap(),
+                link: None,
+                subgroup: subgroup.to_string(),
+            };
+            let occurrence = record_occurrence(&pool, ChatId(chat_id), &entry, class_date)
+                .await
+                .unwrap();
+            members.push(user_id);
+            occurrences.push(occurrence.id);
+        }
+        check_in(&pool, occurrences[0], members[0]).await.unwrap();
+
+        let attendance = get_attendance(&pool, ChatId(chat_id), None).await.unwrap();
+
+        sqlx::query("DELETE FROM chats WHERE chat_id = $1")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let counts: Vec<(i32, i64, i64)> = attendance
+            .iter()
+            .map(|row| (row.user_id, row.held, row.attended))
+            .collect();
+        assert_eq!(counts, vec![(members[0], 1, 1), (members[1], 1, 0)]);
+    }
+}
@@ -1,8 +1,17 @@
 use anyhow::Context;
+use chrono::NaiveDate;
+use rand::RngCore;
 use sqlx::{PgPool, Row};
 use teloxide::types::ChatId;
 
-use crate::models::chat::ChatModel;
+use crate::bot::{
+    timetable::calendar::AcademicCalendar,
+    utils::time::{parse_timezone, DEFAULT_TIMEZONE},
+};
+use crate::models::{
+    chat::{ChatModel, HolidayModel},
+    cleanup::CleanupKind,
+};
 use crate::redis::RedisCache;
 use crate::state::State;
 
@@ -21,7 +30,7 @@ pub async fn create_chat_if_not_exists(
 
     let existing_chat = sqlx::query(
         r#"
-        SELECT id, chat_id, title, description
+        SELECT id, chat_id, title, description, timezone
         FROM chats
         WHERE chat_id = $1
         "#,
@@ -36,6 +45,7 @@ pub async fn create_chat_if_not_exists(
         chat_id: row.get("chat_id"),
         title: row.get("title"),
         description: row.get("description"),
+        timezone: row.get("timezone"),
     });
 
     if let Some(existing_chat) = existing_chat {
@@ -51,7 +61,7 @@ pub async fn create_chat_if_not_exists(
         r#"
         INSERT INTO chats (chat_id, title, description)
         VALUES ($1, $2, $3)
-        RETURNING id, chat_id, title, description
+        RETURNING id, chat_id, title, description, timezone
         "#,
     )
     .bind(&chat.id.0)
@@ -66,6 +76,7 @@ pub async fn create_chat_if_not_exists(
         chat_id: new_chat.get("chat_id"),
         title: new_chat.get("title"),
         description: new_chat.get("description"),
+        timezone: new_chat.get("timezone"),
     };
 
     tracing::debug!("New chat created with database ID: {}", new_chat.id);
@@ -78,7 +89,7 @@ pub async fn create_chat_if_not_exists(
 pub async fn get_chat_ids(pool: &PgPool) -> anyhow::Result<Vec<ChatId>> {
     let chats = sqlx::query(
         r#"
-        SELECT id, chat_id, title, description
+        SELECT id, chat_id, title, description, timezone
         FROM chats
         "#,
     )
@@ -93,9 +104,328 @@ pub async fn get_chat_ids(pool: &PgPool) -> anyhow::Result<Vec<ChatId>> {
             chat_id: row.get("chat_id"),
             title: row.get("title"),
             description: row.get("description"),
+            timezone: row.get("timezone"),
         })
         .filter_map(|chat| Some(ChatId(chat.chat_id)))
         .collect();
 
     Ok(chat_ids)
 }
+
+pub async fn get_chats(pool: &PgPool) -> anyhow::Result<Vec<ChatModel>> {
+    let chats = sqlx::query(
+        r#"
+        SELECT id, chat_id, title, description, timezone
+        FROM chats
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to query all chats")?
+    .into_iter()
+    .map(|row| ChatModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        title: row.get("title"),
+        description: row.get("description"),
+        timezone: row.get("timezone"),
+    })
+    .collect();
+
+    Ok(chats)
+}
+
+pub async fn get_chat_timezone(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<String> {
+    let timezone = sqlx::query(
+        r#"
+        SELECT timezone
+        FROM chats
+        WHERE chat_id = $1
+        "#,
+    )
+    .bind(chat_id.0)
+    .fetch_one(pool)
+    .await
+    .context(format!("Failed to query timezone of chat_id: {}", chat_id))?
+    .get("timezone");
+
+    Ok(timezone)
+}
+
+pub async fn get_cleanup_delay(
+    pool: &PgPool,
+    chat_id: ChatId,
+    kind: CleanupKind,
+) -> anyhow::Result<Option<i32>> {
+    let delay = sqlx::query(
+        r#"
+        SELECT delay_secs
+        FROM chat_cleanup_delays
+        WHERE chat_id = $1 AND kind = $2
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(String::from(kind))
+    .fetch_optional(pool)
+    .await
+    .context(format!(
+        "Failed to query cleanup delay of chat_id: {}",
+        chat_id
+    ))?
+    .map(|row| row.get("delay_secs"));
+
+    Ok(delay)
+}
+
+pub async fn set_cleanup_delay(
+    pool: &PgPool,
+    chat_id: ChatId,
+    kind: CleanupKind,
+    delay_secs: i32,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO chat_cleanup_delays (chat_id, kind, delay_secs)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (chat_id, kind) DO UPDATE
+        SET delay_secs = EXCLUDED.delay_secs
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(String::from(kind))
+    .bind(delay_secs)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to set cleanup delay of chat_id: {}",
+        chat_id
+    ))?;
+
+    Ok(())
+}
+
+pub async fn get_calendar(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<AcademicCalendar> {
+    let chat = sqlx::query(
+        r#"
+        SELECT timezone, semester_start
+        FROM chats
+        WHERE chat_id = $1
+        "#,
+    )
+    .bind(chat_id.0)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to query calendar of chat_id: {}", chat_id))?;
+
+    let (timezone, semester_start) = match chat {
+        Some(row) => (
+            parse_timezone(row.get::<String, _>("timezone").as_str()),
+            row.get("semester_start"),
+        ),
+        None => (DEFAULT_TIMEZONE, None),
+    };
+
+    Ok(AcademicCalendar {
+        timezone,
+        semester_start,
+        holidays: get_holidays(pool, chat_id).await?,
+    })
+}
+
+pub async fn set_timezone(pool: &PgPool, chat_id: ChatId, timezone: &str) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE chats
+        SET timezone = $1
+        WHERE chat_id = $2
+        "#,
+    )
+    .bind(timezone)
+    .bind(chat_id.0)
+    .execute(pool)
+    .await
+    .context(format!("Failed to set timezone of chat_id: {}", chat_id))?;
+
+    Ok(())
+}
+
+pub async fn set_semester_start(
+    pool: &PgPool,
+    chat_id: ChatId,
+    semester_start: NaiveDate,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE chats
+        SET semester_start = $1
+        WHERE chat_id = $2
+        "#,
+    )
+    .bind(semester_start)
+    .bind(chat_id.0)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to set semester start of chat_id: {}",
+        chat_id
+    ))?;
+
+    Ok(())
+}
+
+pub async fn get_holidays(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<Vec<HolidayModel>> {
+    let holidays = sqlx::query(
+        r#"
+        SELECT id, chat_id, starts_on, ends_on, title
+        FROM chat_holidays
+        WHERE chat_id = $1
+        ORDER BY starts_on
+        "#,
+    )
+    .bind(chat_id.0)
+    .fetch_all(pool)
+    .await
+    .context(format!("Failed to query holidays of chat_id: {}", chat_id))?
+    .into_iter()
+    .map(|row| HolidayModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        starts_on: row.get("starts_on"),
+        ends_on: row.get("ends_on"),
+        title: row.get("title"),
+    })
+    .collect();
+
+    Ok(holidays)
+}
+
+pub async fn add_holiday(
+    pool: &PgPool,
+    chat_id: ChatId,
+    starts_on: NaiveDate,
+    ends_on: NaiveDate,
+    title: &str,
+) -> anyhow::Result<HolidayModel> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO chat_holidays (chat_id, starts_on, ends_on, title)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, chat_id, starts_on, ends_on, title
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(starts_on)
+    .bind(ends_on)
+    .bind(title)
+    .fetch_one(pool)
+    .await
+    .context(format!("Failed to add holiday to chat_id: {}", chat_id))?;
+
+    Ok(HolidayModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        starts_on: row.get("starts_on"),
+        ends_on: row.get("ends_on"),
+        title: row.get("title"),
+    })
+}
+
+pub async fn remove_holiday(pool: &PgPool, chat_id: ChatId, id: i32) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM chat_holidays
+        WHERE id = $1 AND chat_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(chat_id.0)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to remove holiday {} of chat_id: {}",
+        id, chat_id
+    ))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+fn generate_calendar_token() -> String {
+    let mut token = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+/// Token of the chat's timetable feed, created on first use.
+pub async fn get_or_create_calendar_token(
+    pool: &PgPool,
+    chat_id: ChatId,
+) -> anyhow::Result<String> {
+    let token = sqlx::query(
+        r#"
+        UPDATE chats
+        SET calendar_token = COALESCE(calendar_token, $1)
+        WHERE chat_id = $2
+        RETURNING calendar_token
+        "#,
+    )
+    .bind(generate_calendar_token())
+    .bind(chat_id.0)
+    .fetch_one(pool)
+    .await
+    .context(format!(
+        "Failed to get calendar token of chat_id: {}",
+        chat_id
+    ))?
+    .get("calendar_token");
+
+    Ok(token)
+}
+
+/// Replaces the feed token, so previously shared feed links stop working.
+pub async fn rotate_calendar_token(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<String> {
+    let token = sqlx::query(
+        r#"
+        UPDATE chats
+        SET calendar_token = $1
+        WHERE chat_id = $2
+        RETURNING calendar_token
+        "#,
+    )
+    .bind(generate_calendar_token())
+    .bind(chat_id.0)
+    .fetch_one(pool)
+    .await
+    .context(format!(
+        "Failed to rotate calendar token of chat_id: {}",
+        chat_id
+    ))?
+    .get("calendar_token");
+
+    Ok(token)
+}
+
+pub async fn get_chat_by_calendar_token(
+    pool: &PgPool,
+    token: &str,
+) -> anyhow::Result<Option<ChatModel>> {
+    let chat = sqlx::query(
+        r#"
+        SELECT id, chat_id, title, description, timezone
+        FROM chats
+        WHERE calendar_token = $1
+        "#,
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to query chat by calendar token")?
+    .map(|row| ChatModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        title: row.get("title"),
+        description: row.get("description"),
+        timezone: row.get("timezone"),
+    });
+
+    Ok(chat)
+}
@@ -0,0 +1,296 @@
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use teloxide::types::{ChatId, UserId};
+
+use crate::models::deadline::{DeadlineModel, DeadlineSettingsModel};
+
+/// How long past deadlines are kept before they are pruned.
+const PAST_RETENTION_DAYS: i32 = 30;
+
+fn deadline_from_row(row: &PgRow) -> DeadlineModel {
+    DeadlineModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        title: row.get("title"),
+        class_name: row.get("class_name"),
+        due_at: row.get("due_at"),
+        created_by: row.get("created_by"),
+        created_at: row.get("created_at"),
+    }
+}
+
+pub async fn add_deadline(
+    pool: &PgPool,
+    chat_id: ChatId,
+    title: &str,
+    class_name: Option<&str>,
+    due_at: NaiveDateTime,
+    created_by: UserId,
+) -> anyhow::Result<DeadlineModel> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO deadlines (chat_id, title, class_name, due_at, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, chat_id, title, class_name, due_at, created_by, created_at
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(title)
+    .bind(class_name)
+    .bind(due_at)
+    .bind(created_by.0 as i64)
+    .fetch_one(pool)
+    .await
+    .context(format!("Failed to add deadline to chat_id: {}", chat_id))?;
+
+    Ok(deadline_from_row(&row))
+}
+
+pub async fn get_deadline(
+    pool: &PgPool,
+    deadline_id: i32,
+) -> anyhow::Result<Option<DeadlineModel>> {
+    let deadline = sqlx::query(
+        r#"
+        SELECT id, chat_id, title, class_name, due_at, created_by, created_at
+        FROM deadlines
+        WHERE id = $1
+        "#,
+    )
+    .bind(deadline_id)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to query deadline id: {}", deadline_id))?
+    .map(|row| deadline_from_row(&row));
+
+    Ok(deadline)
+}
+
+/// Deadlines of the chat due after `since`, soonest first, with the number of
+/// users who marked each one as done.
+pub async fn get_upcoming_deadlines(
+    pool: &PgPool,
+    chat_id: ChatId,
+    since: NaiveDateTime,
+) -> anyhow::Result<Vec<(DeadlineModel, i64)>> {
+    let deadlines = sqlx::query(
+        r#"
+        SELECT d.id, d.chat_id, d.title, d.class_name, d.due_at, d.created_by, d.created_at,
+            COUNT(dc.user_id) AS completed
+        FROM deadlines d
+        LEFT JOIN deadline_completions dc ON dc.deadline_id = d.id
+        WHERE d.chat_id = $1 AND d.due_at > $2
+        GROUP BY d.id
+        ORDER BY d.due_at, d.id
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .context(format!(
+        "Failed to query upcoming deadlines of chat_id: {}",
+        chat_id
+    ))?
+    .into_iter()
+    .map(|row| (deadline_from_row(&row), row.get("completed")))
+    .collect();
+
+    Ok(deadlines)
+}
+
+/// Deadlines of all chats that may still need a reminder. Due dates are
+/// chat-local, so the cut-off is generous and callers filter per chat.
+pub async fn get_pending_deadlines(pool: &PgPool) -> anyhow::Result<Vec<DeadlineModel>> {
+    let deadlines = sqlx::query(
+        r#"
+        SELECT id, chat_id, title, class_name, due_at, created_by, created_at
+        FROM deadlines
+        WHERE due_at > NOW() - INTERVAL '1 day'
+        ORDER BY chat_id, due_at
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to query pending deadlines")?
+    .iter()
+    .map(deadline_from_row)
+    .collect();
+
+    Ok(deadlines)
+}
+
+pub async fn remove_deadline(
+    pool: &PgPool,
+    chat_id: ChatId,
+    deadline_id: i32,
+) -> anyhow::Result<Option<DeadlineModel>> {
+    let deadline = sqlx::query(
+        r#"
+        DELETE FROM deadlines
+        WHERE chat_id = $1 AND id = $2
+        RETURNING id, chat_id, title, class_name, due_at, created_by, created_at
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(deadline_id)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to remove deadline id: {}", deadline_id))?
+    .map(|row| deadline_from_row(&row));
+
+    Ok(deadline)
+}
+
+pub async fn count_completions(pool: &PgPool, deadline_id: i32) -> anyhow::Result<i64> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS completed
+        FROM deadline_completions
+        WHERE deadline_id = $1
+        "#,
+    )
+    .bind(deadline_id)
+    .fetch_one(pool)
+    .await
+    .context(format!(
+        "Failed to count completions of deadline id: {}",
+        deadline_id
+    ))?;
+
+    Ok(row.get("completed"))
+}
+
+/// Marks the deadline as done by the user or takes the mark back. Returns
+/// whether it is done now.
+pub async fn toggle_completion(
+    pool: &PgPool,
+    deadline_id: i32,
+    user_id: i32,
+) -> anyhow::Result<bool> {
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM deadline_completions
+        WHERE deadline_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(deadline_id)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to remove completion of deadline id: {}",
+        deadline_id
+    ))?;
+    if removed.rows_affected() > 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO deadline_completions (deadline_id, user_id)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(deadline_id)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .context(format!("Failed to complete deadline id: {}", deadline_id))?;
+
+    Ok(true)
+}
+
+pub async fn get_deadline_settings(
+    pool: &PgPool,
+    chat_id: ChatId,
+) -> anyhow::Result<DeadlineSettingsModel> {
+    let settings = sqlx::query(
+        r#"
+        SELECT chat_id, offsets
+        FROM deadline_settings
+        WHERE chat_id = $1
+        "#,
+    )
+    .bind(chat_id.0)
+    .fetch_optional(pool)
+    .await
+    .context(format!(
+        "Failed to query deadline settings of chat_id: {}",
+        chat_id
+    ))?
+    .map(|row| DeadlineSettingsModel {
+        chat_id: row.get("chat_id"),
+        offsets: row.get("offsets"),
+    })
+    .unwrap_or_else(|| DeadlineSettingsModel::default_for(chat_id.0));
+
+    Ok(settings)
+}
+
+pub async fn set_deadline_offsets(
+    pool: &PgPool,
+    chat_id: ChatId,
+    offsets: &[i32],
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO deadline_settings (chat_id, offsets)
+        VALUES ($1, $2)
+        ON CONFLICT (chat_id) DO UPDATE
+        SET offsets = EXCLUDED.offsets
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(offsets)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to set deadline offsets of chat_id: {}",
+        chat_id
+    ))?;
+
+    Ok(())
+}
+
+/// Records the reminder as sent. Returns `false` if it was already sent.
+pub async fn claim_deadline_reminder(
+    pool: &PgPool,
+    deadline_id: i32,
+    offset_minutes: i32,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO sent_deadline_reminders (deadline_id, offset_minutes)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(deadline_id)
+    .bind(offset_minutes)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to record reminder for deadline id: {}",
+        deadline_id
+    ))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn prune_deadlines(pool: &PgPool) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM deadlines
+        WHERE due_at < NOW() - make_interval(days => $1)
+        "#,
+    )
+    .bind(PAST_RETENTION_DAYS)
+    .execute(pool)
+    .await
+    .context("Failed to prune past deadlines")?;
+
+    Ok(())
+}
@@ -1,14 +1,27 @@
+use std::fmt;
+
 use anyhow::Context;
-use sqlx::{PgPool, Row};
+use sqlx::{Executor, PgPool, Postgres, Row};
+use teloxide::types::{MessageId, UserId};
 
-use crate::models::{gamble::GambleDto, stats::GambleModel};
+use crate::{
+    models::{
+        gamble::{FairGambleDto, GambleDto, WheelSpinDto},
+        stats::GambleModel,
+    },
+    repositories::stats_repository::update_balance,
+};
 
-pub async fn insert_gamble(pool: &PgPool, gamble: GambleDto) -> anyhow::Result<GambleModel> {
+pub async fn insert_gamble<'c, E>(executor: E, gamble: GambleDto) -> anyhow::Result<GambleModel>
+where
+    E: Executor<'c, Database = Postgres>,
+{
     let inserted_gamble = sqlx::query(
         r#"
-        INSERT INTO gambles (user_id, message_id, is_win, change, bet, gamble_type)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        RETURNING id, user_id, message_id, is_win, change, bet, gamble_type, created_at
+        INSERT INTO gambles (user_id, message_id, is_win, change, bet, gamble_type, outcome)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, user_id, message_id, is_win, change, bet, gamble_type, outcome,
+            server_seed, client_seed, created_at
         "#,
     )
     .bind(gamble.user_id)
@@ -17,7 +30,8 @@ pub async fn insert_gamble(pool: &PgPool, gamble: GambleDto) -> anyhow::Result<G
     .bind(gamble.change)
     .bind(gamble.bet)
     .bind(String::from(gamble.gamble_type))
-    .fetch_one(pool)
+    .bind(gamble.outcome)
+    .fetch_one(executor)
     .await
     .context(format!(
         "Failed to insert gamble for user_id: {}",
@@ -32,6 +46,9 @@ pub async fn insert_gamble(pool: &PgPool, gamble: GambleDto) -> anyhow::Result<G
         bet: inserted_gamble.get("bet"),
         change: inserted_gamble.get("change"),
         is_win: inserted_gamble.get("is_win"),
+        outcome: inserted_gamble.get("outcome"),
+        server_seed: inserted_gamble.get("server_seed"),
+        client_seed: inserted_gamble.get("client_seed"),
         created_at: inserted_gamble.get("created_at"),
     };
 
@@ -41,7 +58,8 @@ pub async fn insert_gamble(pool: &PgPool, gamble: GambleDto) -> anyhow::Result<G
 pub async fn get_gamble_by_id(pool: &PgPool, id: i32) -> anyhow::Result<Option<GambleModel>> {
     let gamble = sqlx::query(
         r#"
-        SELECT id, user_id, message_id, gamble_type, bet, change, is_win, created_at
+        SELECT id, user_id, message_id, gamble_type, bet, change, is_win, outcome,
+            server_seed, client_seed, created_at
         FROM gambles
         WHERE id = $1
         "#,
@@ -59,8 +77,416 @@ pub async fn get_gamble_by_id(pool: &PgPool, id: i32) -> anyhow::Result<Option<G
         bet: row.get("bet"),
         change: row.get("change"),
         is_win: row.get("is_win"),
+        outcome: row.get("outcome"),
+        server_seed: row.get("server_seed"),
+        client_seed: row.get("client_seed"),
         created_at: row.get("created_at"),
     });
 
     Ok(gamble)
 }
+
+pub async fn get_gambles_by_account_id(
+    pool: &PgPool,
+    user_id: UserId,
+    offset: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<GambleModel>> {
+    let gambles = sqlx::query(
+        r#"
+        SELECT
+            g.id,
+            g.user_id,
+            g.message_id,
+            g.gamble_type,
+            g.bet,
+            g.change,
+            g.is_win,
+            g.outcome,
+            g.server_seed,
+            g.client_seed,
+            g.created_at
+        FROM gambles g
+        JOIN users u ON g.user_id = u.id
+        WHERE u.account_id = $1
+        ORDER BY g.created_at DESC, g.id DESC
+        OFFSET $2
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id.0 as i64)
+    .bind(offset)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context(format!(
+        "Failed to query gamble history for account_id: {}",
+        user_id
+    ))?
+    .into_iter()
+    .map(|row| GambleModel {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        message_id: row.get("message_id"),
+        gamble_type: row.get("gamble_type"),
+        bet: row.get("bet"),
+        change: row.get("change"),
+        is_win: row.get("is_win"),
+        outcome: row.get("outcome"),
+        server_seed: row.get("server_seed"),
+        client_seed: row.get("client_seed"),
+        created_at: row.get("created_at"),
+    })
+    .collect();
+
+    Ok(gambles)
+}
+
+/// Returns the seed committed for the user's next API game, creating one if needed.
+pub async fn get_or_create_server_seed(
+    pool: &PgPool,
+    user_id: i32,
+    new_seed: &str,
+) -> anyhow::Result<String> {
+    sqlx::query(
+        r#"
+        INSERT INTO game_seeds (user_id, server_seed)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(new_seed)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to create server seed for user_id: {}",
+        user_id
+    ))?;
+
+    let seed = sqlx::query(
+        r#"
+        SELECT server_seed
+        FROM game_seeds
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .context(format!(
+        "Failed to query server seed for user_id: {}",
+        user_id
+    ))?
+    .get("server_seed");
+
+    Ok(seed)
+}
+
+/// Why a fair gamble was not settled, for the caller to tell the player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettleError {
+    SeedAlreadyUsed,
+    InsufficientFunds,
+    FreeSpinUsed,
+}
+
+impl fmt::Display for SettleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettleError::SeedAlreadyUsed => write!(f, "Server seed was already used"),
+            SettleError::InsufficientFunds => write!(f, "Недостатньо коштів"),
+            SettleError::FreeSpinUsed => write!(f, "Безкоштовне обертання вже використано"),
+        }
+    }
+}
+
+impl std::error::Error for SettleError {}
+
+/// Debits or credits the balance, stores the gamble and consumes its seed in one
+/// transaction. Returns the stored gamble and the balance after it.
+pub async fn settle_fair_gamble(
+    pool: &PgPool,
+    gamble: FairGambleDto,
+) -> anyhow::Result<(GambleModel, i32)> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let consumed = sqlx::query(
+        r#"
+        DELETE FROM game_seeds
+        WHERE user_id = $1 AND server_seed = $2
+        "#,
+    )
+    .bind(gamble.user_id)
+    .bind(&gamble.server_seed)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to consume server seed")?;
+
+    if consumed.rows_affected() == 0 {
+        tx.rollback()
+            .await
+            .context("Failed to rollback transaction")?;
+        return Err(SettleError::SeedAlreadyUsed.into());
+    }
+
+    let balance: i32 = sqlx::query(
+        r#"
+        SELECT balance
+        FROM user_stats
+        WHERE user_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(gamble.user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to lock user stats")?
+    .ok_or_else(|| anyhow::anyhow!("User stats not found for user_id: {}", gamble.user_id))?
+    .get("balance");
+
+    if balance < gamble.bet {
+        tx.rollback()
+            .await
+            .context("Failed to rollback transaction")?;
+        return Err(SettleError::InsufficientFunds.into());
+    }
+
+    let balance: i32 = sqlx::query(
+        r#"
+        UPDATE user_stats
+        SET balance = balance + $1
+        WHERE user_id = $2
+        RETURNING balance
+        "#,
+    )
+    .bind(gamble.change)
+    .bind(gamble.user_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to update balance")?
+    .get("balance");
+
+    let inserted_gamble = sqlx::query(
+        r#"
+        INSERT INTO gambles
+            (user_id, message_id, is_win, change, bet, gamble_type, outcome, server_seed, client_seed)
+        VALUES ($1, 0, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, user_id, message_id, is_win, change, bet, gamble_type, outcome,
+            server_seed, client_seed, created_at
+        "#,
+    )
+    .bind(gamble.user_id)
+    .bind(gamble.is_win)
+    .bind(gamble.change)
+    .bind(gamble.bet)
+    .bind(String::from(gamble.gamble_type))
+    .bind(gamble.outcome)
+    .bind(gamble.server_seed)
+    .bind(gamble.client_seed)
+    .fetch_one(&mut *tx)
+    .await
+    .context(format!(
+        "Failed to insert gamble for user_id: {}",
+        gamble.user_id
+    ))?;
+
+    let inserted_gamble = GambleModel {
+        id: inserted_gamble.get("id"),
+        user_id: inserted_gamble.get("user_id"),
+        message_id: inserted_gamble.get("message_id"),
+        gamble_type: inserted_gamble.get("gamble_type"),
+        bet: inserted_gamble.get("bet"),
+        change: inserted_gamble.get("change"),
+        is_win: inserted_gamble.get("is_win"),
+        outcome: inserted_gamble.get("outcome"),
+        server_seed: inserted_gamble.get("server_seed"),
+        client_seed: inserted_gamble.get("client_seed"),
+        created_at: inserted_gamble.get("created_at"),
+    };
+
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    Ok((inserted_gamble, balance))
+}
+
+/// Applies a wheel spin and stores its gamble in one transaction. The spin is
+/// refused if the free spin it uses is gone or the balance no longer covers it.
+pub async fn settle_wheel_spin(pool: &PgPool, spin: WheelSpinDto) -> anyhow::Result<GambleModel> {
+    let user_id = spin.gamble.user_id;
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let stats = sqlx::query(
+        r#"
+        SELECT balance, free_spins
+        FROM user_stats
+        WHERE user_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to lock user stats")?
+    .ok_or_else(|| anyhow::anyhow!("User stats not found for user_id: {}", user_id))?;
+    let balance: i32 = stats.get("balance");
+    let free_spins: i32 = stats.get("free_spins");
+
+    let refused = if spin.used_free_spin && free_spins == 0 {
+        Some(SettleError::FreeSpinUsed)
+    } else if balance + spin.gamble.change < 0 {
+        Some(SettleError::InsufficientFunds)
+    } else {
+        None
+    };
+    if let Some(err) = refused {
+        tx.rollback()
+            .await
+            .context("Failed to rollback transaction")?;
+        return Err(err.into());
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE user_stats
+        SET free_spins = free_spins - $1 + $2,
+            daily_limit = daily_limit + $3
+        WHERE user_id = $4
+        "#,
+    )
+    .bind(spin.used_free_spin as i32)
+    .bind(spin.won_free_spin as i32)
+    .bind(spin.limit_boost)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await
+    .context(format!(
+        "Failed to update free spins for user id: {}",
+        user_id
+    ))?;
+
+    update_balance(&mut *tx, user_id, spin.gamble.change).await?;
+    let gamble = insert_gamble(&mut *tx, spin.gamble).await?;
+
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    Ok(gamble)
+}
+
+pub async fn set_gamble_message_id(
+    pool: &PgPool,
+    gamble_id: i32,
+    message_id: MessageId,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE gambles
+        SET message_id = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(message_id.0)
+    .bind(gamble_id)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to set message id of gamble id: {}",
+        gamble_id
+    ))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::postgres::PgPoolOptions;
+    use teloxide::types::MessageId;
+
+    use super::*;
+    use crate::{models::gamble::GambleType, repositories::setup::MIGRATOR};
+
+    fn spin(user_id: i32, change: i32, used_free_spin: bool) -> WheelSpinDto {
+        WheelSpinDto {
+            gamble: GambleDto {
+                user_id,
+                message_id: MessageId(1),
+                is_win: true,
+                change,
+                bet: 0,
+                gamble_type: GambleType::Wheel,
+                outcome: Some("Буст".to_string()),
+            },
+            used_free_spin,
+            won_free_spin: false,
+            limit_boost: 50,
+        }
+    }
+
+    /// Needs a database: `cargo test -- --ignored` with `DATABASE_URL` set.
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_settle_wheel_spin() {
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+        let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+
+        let chat_id: i64 = -1_000_000_000_103;
+        sqlx::query("DELETE FROM chats WHERE chat_id = $1")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO chats (chat_id) VALUES ($1)")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let user_id: i32 = sqlx::query(
+            "INSERT INTO users (account_id, chat_id, name) VALUES ($1, $1, '') RETURNING id",
+        )
+        .bind(chat_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .get("id");
+        sqlx::query(
+            "INSERT INTO user_stats (user_id, balance, daily_limit, free_spins) VALUES ($1, 100, 100, 1)",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let free = settle_wheel_spin(&pool, spin(user_id, 0, true)).await;
+        let no_free_spin_left = settle_wheel_spin(&pool, spin(user_id, 0, true)).await;
+        let too_expensive = settle_wheel_spin(&pool, spin(user_id, -101, false)).await;
+        let stats = sqlx::query(
+            "SELECT balance, daily_limit, free_spins FROM user_stats WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM chats WHERE chat_id = $1")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(free.unwrap().outcome.as_deref(), Some("Буст"));
+        assert_eq!(
+            no_free_spin_left.unwrap_err().downcast_ref::<SettleError>(),
+            Some(&SettleError::FreeSpinUsed)
+        );
+        assert_eq!(
+            too_expensive.unwrap_err().downcast_ref::<SettleError>(),
+            Some(&SettleError::InsufficientFunds)
+        );
+        let stats: (i32, i32, i32) = (
+            stats.get("balance"),
+            stats.get("daily_limit"),
+            stats.get("free_spins"),
+        );
+        assert_eq!(stats, (100, 150, 0));
+    }
+}
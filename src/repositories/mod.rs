@@ -1,7 +1,12 @@
+pub mod attendance_repository;
 pub mod chat_repository;
+pub mod deadline_repository;
 pub mod gamble_repository;
 pub mod queue_repository;
+pub mod reminder_repository;
 pub mod setup;
 pub mod stats_repository;
+pub mod subgroup_repository;
+pub mod subscription_repository;
 pub mod timetable_repository;
 pub mod user_repository;
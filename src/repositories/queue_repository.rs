@@ -1,10 +1,44 @@
 use anyhow::{bail, Context};
+use chrono::NaiveDateTime;
 use rand::seq::SliceRandom;
 use rand::{rngs::StdRng, SeedableRng};
-use sqlx::{PgPool, Row};
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
 use teloxide::types::{ChatId, MessageId};
 
-use crate::models::queue::{QueueModel, QueueUserModel, QueueUserWithUserModel};
+use crate::{
+    bot::queues::{
+        admin::{move_member, swap_members},
+        lottery::draw_order,
+        priority::{history_weight, rank_by_priority},
+    },
+    models::queue::{
+        QueueAction, QueueEventModel, QueueModel, QueuePrioritySettingsModel, QueueSnapshot,
+        QueueSnapshotUser, QueueUserModel, QueueUserWithUserModel,
+    },
+};
+
+fn queue_from_row(row: &PgRow) -> QueueModel {
+    QueueModel {
+        id: row.get("id"),
+        title: row.get("title"),
+        chat_id: row.get("chat_id"),
+        message_id: row.get("message_id"),
+        is_mixed: row.get("is_mixed"),
+        is_priority: row.get("is_priority"),
+        is_deleted: row.get("is_deleted"),
+        created_at: row.get("created_at"),
+        opens_at: row.get("opens_at"),
+        closes_at: row.get("closes_at"),
+        max_users: row.get("max_users"),
+        entry_id: row.get("entry_id"),
+        archive_at: row.get("archive_at"),
+        is_closed: row.get("is_closed"),
+        is_archived: row.get("is_archived"),
+        lottery_seed: row.get("lottery_seed"),
+        lottery_closes_at: row.get("lottery_closes_at"),
+        subject: row.get("subject"),
+    }
+}
 
 pub async fn create_queue(
     pool: &PgPool,
@@ -13,12 +47,15 @@ pub async fn create_queue(
     message_id: MessageId,
     is_mixed: Option<bool>,
     is_priority: bool,
+    subject: Option<&str>,
 ) -> anyhow::Result<QueueModel> {
     let new_queue = sqlx::query(
         r#"
-        INSERT INTO queues (title, chat_id, message_id, is_mixed, is_priority)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at
+        INSERT INTO queues (title, chat_id, message_id, is_mixed, is_priority, subject)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at,
+            opens_at, closes_at, max_users, entry_id, archive_at, is_closed, is_archived, lottery_seed,
+            lottery_closes_at, subject
         "#,
     )
     .bind(title)
@@ -26,30 +63,22 @@ pub async fn create_queue(
     .bind(message_id.0)
     .bind(is_mixed)
     .bind(is_priority)
+    .bind(subject)
     .fetch_one(pool)
     .await
     .context("Failed to insert new queue")?;
 
-    let new_queue = QueueModel {
-        id: new_queue.get("id"),
-        title: new_queue.get("title"),
-        chat_id: new_queue.get("chat_id"),
-        message_id: new_queue.get("message_id"),
-        is_mixed: new_queue.get("is_mixed"),
-        is_priority: new_queue.get("is_priority"),
-        is_deleted: new_queue.get("is_deleted"),
-        created_at: new_queue.get("created_at"),
-    };
-
-    Ok(new_queue)
+    Ok(queue_from_row(&new_queue))
 }
 
 pub async fn get_all_queues(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<Vec<QueueModel>> {
     let queues = sqlx::query(
         r#"
-        SELECT id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at
+        SELECT id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at,
+            opens_at, closes_at, max_users, entry_id, archive_at, is_closed, is_archived, lottery_seed,
+            lottery_closes_at, subject
         FROM queues
-        WHERE chat_id = $1 AND is_deleted = FALSE
+        WHERE chat_id = $1 AND is_deleted = FALSE AND is_archived = FALSE
         "#,
     )
     .bind(chat_id.0)
@@ -57,22 +86,19 @@ pub async fn get_all_queues(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<Ve
     .await
     .context("Failed to query all queues")?
     .into_iter()
-    .map(|row| QueueModel {
-        id: row.get("id"),
-        title: row.get("title"),
-        chat_id: row.get("chat_id"),
-        message_id: row.get("message_id"),
-        is_mixed: row.get("is_mixed"),
-        is_priority: row.get("is_priority"),
-        is_deleted: row.get("is_deleted"),
-        created_at: row.get("created_at"),
-    })
+    .map(|row| queue_from_row(&row))
     .collect();
 
     Ok(queues)
 }
 
-pub async fn delete_queue(pool: &PgPool, queue_id: i32) -> anyhow::Result<()> {
+pub async fn delete_queue(pool: &PgPool, queue_id: i32, actor_id: i32) -> anyhow::Result<()> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for deleting queue")?;
+
+    let (snapshot, _) = snapshot_queue(&mut tx, queue_id).await?;
     let result = sqlx::query(
         r#"
         UPDATE queues
@@ -81,7 +107,7 @@ pub async fn delete_queue(pool: &PgPool, queue_id: i32) -> anyhow::Result<()> {
         "#,
     )
     .bind(queue_id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await
     .context("Failed to soft delete queue")?;
 
@@ -89,6 +115,21 @@ pub async fn delete_queue(pool: &PgPool, queue_id: i32) -> anyhow::Result<()> {
         bail!("Queue not found or already deleted");
     }
 
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(actor_id),
+        QueueAction::Delete,
+        None,
+        String::new(),
+        &snapshot,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit delete queue transaction")?;
+
     Ok(())
 }
 
@@ -99,7 +140,9 @@ pub async fn get_queue(
 ) -> anyhow::Result<QueueModel> {
     let queue = sqlx::query(
         r#"
-        SELECT id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at
+        SELECT id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at,
+            opens_at, closes_at, max_users, entry_id, archive_at, is_closed, is_archived, lottery_seed,
+            lottery_closes_at, subject
         FROM queues
         WHERE chat_id = $1 AND message_id = $2 AND is_deleted = FALSE
         "#,
@@ -109,16 +152,7 @@ pub async fn get_queue(
     .fetch_optional(pool)
     .await
     .context("Failed to query queue")?
-    .map(|row| QueueModel {
-        id: row.get("id"),
-        title: row.get("title"),
-        chat_id: row.get("chat_id"),
-        message_id: row.get("message_id"),
-        is_mixed: row.get("is_mixed"),
-        is_priority: row.get("is_priority"),
-        is_deleted: row.get("is_deleted"),
-        created_at: row.get("created_at"),
-    })
+    .map(|row| queue_from_row(&row))
     .ok_or_else(|| anyhow::anyhow!("Queue not found"))?;
 
     Ok(queue)
@@ -127,7 +161,9 @@ pub async fn get_queue(
 pub async fn get_queue_by_id(pool: &PgPool, queue_id: i32) -> anyhow::Result<QueueModel> {
     let queue = sqlx::query(
         r#"
-        SELECT id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at
+        SELECT id, title, chat_id, message_id, is_mixed, is_priority, is_deleted, created_at,
+            opens_at, closes_at, max_users, entry_id, archive_at, is_closed, is_archived, lottery_seed,
+            lottery_closes_at, subject
         FROM queues
         WHERE id = $1 AND is_deleted = FALSE
         "#,
@@ -136,89 +172,23 @@ pub async fn get_queue_by_id(pool: &PgPool, queue_id: i32) -> anyhow::Result<Que
     .fetch_optional(pool)
     .await
     .context("Failed to query queue by id")?
-    .map(|row| QueueModel {
-        id: row.get("id"),
-        title: row.get("title"),
-        chat_id: row.get("chat_id"),
-        message_id: row.get("message_id"),
-        is_mixed: row.get("is_mixed"),
-        is_priority: row.get("is_priority"),
-        is_deleted: row.get("is_deleted"),
-        created_at: row.get("created_at"),
-    })
+    .map(|row| queue_from_row(&row))
     .ok_or_else(|| anyhow::anyhow!("Queue not found"))?;
 
     Ok(queue)
 }
 
-pub async fn shuffle_queue(pool: &PgPool, queue_id: i32) -> anyhow::Result<()> {
+pub async fn shuffle_queue(pool: &PgPool, queue_id: i32, actor_id: i32) -> anyhow::Result<()> {
     let mut tx = pool
         .begin()
         .await
         .context("Failed to begin transaction for shuffle")?;
 
-    // Fetch all users in the queue
-    let mut all_users = sqlx::query(
-        r#"
-        SELECT id, position, priority, is_frozen, queue_id, user_id
-        FROM queue_users
-        WHERE queue_id = $1
-        ORDER BY position
-        "#,
-    )
-    .bind(queue_id)
-    .fetch_all(&mut *tx)
-    .await
-    .context("Failed to fetch queue users for shuffle")?
-    .into_iter()
-    .map(|row| QueueUserModel {
-        id: row.get("id"),
-        position: row.get("position"),
-        priority: row.get("priority"),
-        is_frozen: row.get("is_frozen"),
-        queue_id: row.get("queue_id"),
-        user_id: row.get("user_id"),
-    })
-    .collect::<Vec<_>>();
-
-    // If queue is empty, just set is_mixed to true and return
-    if all_users.is_empty() {
-        sqlx::query(
-            r#"
-            UPDATE queues
-            SET is_mixed = true
-            WHERE id = $1
-            "#,
-        )
-        .bind(queue_id)
-        .execute(&mut *tx)
-        .await
-        .context("Failed to update queue is_mixed flag")?;
-
-        tx.commit()
-            .await
-            .context("Failed to commit empty shuffle transaction")?;
-        return Ok(());
-    }
+    let (snapshot, mut all_users) = snapshot_queue(&mut tx, queue_id).await?;
 
     let mut rng = StdRng::from_entropy();
     all_users.shuffle(&mut rng);
-
-    for (i, user) in all_users.iter().enumerate() {
-        let new_position = i as i32 + 1;
-        sqlx::query(
-            r#"
-            UPDATE queue_users
-            SET position = $1
-            WHERE id = $2
-            "#,
-        )
-        .bind(new_position)
-        .bind(user.id)
-        .execute(&mut *tx)
-        .await
-        .context("Failed to update queue user position")?;
-    }
+    write_positions(&mut tx, &all_users).await?;
 
     sqlx::query(
         r#"
@@ -232,6 +202,17 @@ pub async fn shuffle_queue(pool: &PgPool, queue_id: i32) -> anyhow::Result<()> {
     .await
     .context("Failed to update queue is_mixed flag")?;
 
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(actor_id),
+        QueueAction::Shuffle,
+        None,
+        String::new(),
+        &snapshot,
+    )
+    .await?;
+
     tx.commit()
         .await
         .context("Failed to commit shuffle transaction")?;
@@ -283,7 +264,10 @@ pub async fn get_users(
             u.username,
             u.account_id,
             u.chat_id as chat_id_user,
-            u.name
+            u.name,
+            qu.weight,
+            qu.defences,
+            qu.last_defended_at
         FROM queue_users qu
         JOIN users u ON qu.user_id = u.id
         WHERE qu.queue_id = $1
@@ -307,6 +291,9 @@ pub async fn get_users(
         account_id: row.get("account_id"),
         chat_id_user: row.get("chat_id_user"),
         name: row.get("name"),
+        weight: row.get("weight"),
+        defences: row.get("defences"),
+        last_defended_at: row.get("last_defended_at"),
     })
     .collect();
 
@@ -351,22 +338,26 @@ pub async fn add_user_to_queue(
         .await
         .context("Failed to begin transaction for add user to queue")?;
 
-    let max_position_row = sqlx::query(
+    let (snapshot, users) = snapshot_queue(&mut tx, queue_id).await?;
+    let max_users: Option<i32> = sqlx::query(
         r#"
-        SELECT MAX(position) as max_pos
-        FROM queue_users
-        WHERE queue_id = $1
+        SELECT max_users
+        FROM queues
+        WHERE id = $1
         "#,
     )
     .bind(queue_id)
     .fetch_one(&mut *tx)
     .await
-    .context("Failed to get max position for queue")?;
-
-    let next_position = max_position_row
-        .get::<Option<i32>, _>("max_pos")
-        .unwrap_or(0)
-        + 1;
+    .context("Failed to query queue limit")?
+    .get("max_users");
+    if snapshot.is_closed {
+        bail!("Queue {} is closed", queue_id);
+    }
+    let next_position = users.len() as i32 + 1;
+    if max_users.is_some_and(|max_users| next_position > max_users) {
+        bail!("Queue {} is full", queue_id);
+    }
 
     let new_user = sqlx::query(
         r#"
@@ -393,6 +384,32 @@ pub async fn add_user_to_queue(
         is_frozen: new_user.get("is_frozen"),
     };
 
+    // The last place closes the queue.
+    if max_users == Some(next_position) {
+        sqlx::query(
+            r#"
+            UPDATE queues
+            SET is_closed = TRUE
+            WHERE id = $1
+            "#,
+        )
+        .bind(queue_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to close full queue")?;
+    }
+
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(user_id),
+        QueueAction::Join,
+        None,
+        next_position.to_string(),
+        &snapshot,
+    )
+    .await?;
+
     tx.commit()
         .await
         .context("Failed to commit add user to queue transaction")?;
@@ -410,69 +427,37 @@ pub async fn remove_user_from_queue(
         .await
         .context("Failed to begin transaction for removing user")?;
 
-    let queue_user_to_remove = sqlx::query(
-        r#"
-        SELECT id, position, priority, is_frozen, queue_id, user_id
-        FROM queue_users
-        WHERE queue_id = $1 AND user_id = $2
-        "#,
-    )
-    .bind(queue_id)
-    .bind(user_id)
-    .fetch_optional(&mut *tx)
-    .await
-    .context("Failed to query user to remove")?;
-
-    let queue_user_to_remove = match queue_user_to_remove {
-        Some(row) => QueueUserModel {
-            id: row.get("id"),
-            position: row.get("position"),
-            priority: row.get("priority"),
-            is_frozen: row.get("is_frozen"),
-            queue_id: row.get("queue_id"),
-            user_id: row.get("user_id"),
-        },
-        None => {
-            tx.rollback()
-                .await
-                .context("Failed to rollback remove user transaction (not found)")?;
-            return Ok(());
-        }
+    let (snapshot, mut users) = snapshot_queue(&mut tx, queue_id).await?;
+    let Some(index) = users.iter().position(|user| user.user_id == user_id) else {
+        tx.rollback()
+            .await
+            .context("Failed to rollback remove user transaction (not found)")?;
+        return Ok(());
     };
+    let removed = users.remove(index);
 
-    let user_position = queue_user_to_remove.position;
-
-    let delete_result = sqlx::query(
+    sqlx::query(
         r#"
         DELETE FROM queue_users
-        WHERE queue_id = $1 AND user_id = $2
+        WHERE id = $1
         "#,
     )
-    .bind(queue_id)
-    .bind(user_id)
+    .bind(removed.id)
     .execute(&mut *tx)
     .await
     .context("Failed to delete queue user")?;
-
-    if delete_result.rows_affected() == 0 {
-        tx.rollback()
-            .await
-            .context("Failed to rollback remove user transaction (delete failed)")?;
-        bail!("Failed to delete user from queue");
-    }
-
-    sqlx::query(
-        r#"
-        UPDATE queue_users
-        SET position = position - 1
-        WHERE queue_id = $1 AND position > $2
-        "#,
+    write_positions(&mut tx, &users).await?;
+
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(user_id),
+        QueueAction::Leave,
+        None,
+        removed.position.to_string(),
+        &snapshot,
     )
-    .bind(queue_id)
-    .bind(user_position)
-    .execute(&mut *tx)
-    .await
-    .context("Failed to decrement queue positions")?;
+    .await?;
 
     tx.commit()
         .await
@@ -481,35 +466,17 @@ pub async fn remove_user_from_queue(
     Ok(())
 }
 
+/// Orders a priority queue: first by answers given in it, then by the history
+/// weight of defences of the same subject in the chat's earlier priority
+/// queues, then by the current order. The weight is kept with each member so
+/// the queue can show it.
 pub async fn order_by_priority(pool: &PgPool, queue_id: i32) -> anyhow::Result<()> {
     let mut tx = pool
         .begin()
         .await
         .context("Failed to begin transaction for priority order")?;
 
-    let all_users = sqlx::query(
-        r#"
-        SELECT id, position, priority, is_frozen, queue_id, user_id
-        FROM queue_users
-        WHERE queue_id = $1
-        ORDER BY priority NULLS LAST, position ASC
-        "#,
-    )
-    .bind(queue_id)
-    .fetch_all(&mut *tx)
-    .await
-    .context("Failed to fetch queue users for priority order")?
-    .into_iter()
-    .map(|row| QueueUserModel {
-        id: row.get("id"),
-        position: row.get("position"),
-        priority: row.get("priority"),
-        is_frozen: row.get("is_frozen"),
-        queue_id: row.get("queue_id"),
-        user_id: row.get("user_id"),
-    })
-    .collect::<Vec<_>>();
-
+    let (_, mut all_users) = snapshot_queue(&mut tx, queue_id).await?;
     if all_users.is_empty() {
         tx.rollback()
             .await
@@ -517,22 +484,99 @@ pub async fn order_by_priority(pool: &PgPool, queue_id: i32) -> anyhow::Result<(
         return Ok(());
     }
 
-    for (i, user) in all_users.iter().enumerate() {
-        let new_position = i as i32 + 1;
+    let settings = sqlx::query(
+        r#"
+        SELECT q.chat_id, s.window_days, s.half_life_days
+        FROM queues q
+        LEFT JOIN queue_priority_settings s ON s.chat_id = q.chat_id
+        WHERE q.id = $1
+        "#,
+    )
+    .bind(queue_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context(format!(
+        "Failed to query priority settings of queue {}",
+        queue_id
+    ))?;
+    let defaults = QueuePrioritySettingsModel::default_for(settings.get("chat_id"));
+    let window_days: i32 = settings
+        .get::<Option<i32>, _>("window_days")
+        .unwrap_or(defaults.window_days);
+    let half_life_days: i32 = settings
+        .get::<Option<i32>, _>("half_life_days")
+        .unwrap_or(defaults.half_life_days);
+
+    let defences = sqlx::query(
+        r#"
+        SELECT
+            l.actor_id AS user_id,
+            EXTRACT(EPOCH FROM NOW() - l.created_at)::FLOAT8 / 86400 AS age_days,
+            (l.created_at AT TIME ZONE current_setting('TimeZone')) AT TIME ZONE c.timezone AS defended_at
+        FROM queues cur
+        JOIN chats c ON c.chat_id = cur.chat_id
+        JOIN queues q ON q.chat_id = cur.chat_id
+            AND q.id <> cur.id
+            AND q.is_priority = TRUE
+            AND q.subject = cur.subject
+        JOIN queue_audit_log l ON l.queue_id = q.id
+        WHERE cur.id = $1
+            AND l.action = 'done'
+            AND l.undone_at IS NULL
+            AND l.created_at > NOW() - make_interval(days => $2)
+            AND l.actor_id IN (SELECT user_id FROM queue_users WHERE queue_id = $1)
+        "#,
+    )
+    .bind(queue_id)
+    .bind(window_days)
+    .fetch_all(&mut *tx)
+    .await
+    .context(format!("Failed to query defences for queue {}", queue_id))?;
+
+    let standings: Vec<(f64, i32, Option<NaiveDateTime>)> = all_users
+        .iter()
+        .map(|user| {
+            let user_defences: Vec<&PgRow> = defences
+                .iter()
+                .filter(|row| row.get::<i32, _>("user_id") == user.user_id)
+                .collect();
+            let ages: Vec<f64> = user_defences
+                .iter()
+                .map(|row| row.get("age_days"))
+                .collect();
+            let last_defended_at = user_defences
+                .iter()
+                .map(|row| row.get::<NaiveDateTime, _>("defended_at"))
+                .max();
+            (
+                history_weight(&ages, half_life_days),
+                ages.len() as i32,
+                last_defended_at,
+            )
+        })
+        .collect();
+
+    for (user, (weight, defences, last_defended_at)) in all_users.iter().zip(&standings) {
         sqlx::query(
             r#"
             UPDATE queue_users
-            SET position = $1
-            WHERE id = $2
+            SET weight = $2, defences = $3, last_defended_at = $4
+            WHERE id = $1
             "#,
         )
-        .bind(new_position)
         .bind(user.id)
+        .bind(weight)
+        .bind(defences)
+        .bind(last_defended_at)
         .execute(&mut *tx)
         .await
-        .context("Failed to update queue user position")?;
+        .context("Failed to update queue user weight")?;
     }
 
+    let weights: Vec<f64> = standings.iter().map(|(weight, _, _)| *weight).collect();
+    rank_by_priority(&mut all_users, &weights);
+    write_positions(&mut tx, &all_users).await?;
+
     tx.commit()
         .await
         .context("Failed to commit priority order transaction")?;
@@ -540,111 +584,144 @@ pub async fn order_by_priority(pool: &PgPool, queue_id: i32) -> anyhow::Result<(
     Ok(())
 }
 
-pub async fn skip_priority_queue(
-    pool: &PgPool,
-    queue_id: i32,
-    user_id: i32,
-    done: bool,
-) -> anyhow::Result<()> {
-    let mut tx = pool
-        .begin()
-        .await
-        .context("Failed to begin transaction for skipping in priority queue")?;
-
-    let queue_user = sqlx::query(
+/// Subjects of the chat's priority queues.
+pub async fn get_queue_subjects(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<Vec<String>> {
+    let subjects = sqlx::query(
         r#"
-        SELECT id, position, priority, is_frozen, queue_id, user_id
-        FROM queue_users
-        WHERE queue_id = $1 AND user_id = $2
+        SELECT DISTINCT subject
+        FROM queues
+        WHERE chat_id = $1 AND subject IS NOT NULL
         "#,
     )
-    .bind(queue_id)
-    .bind(user_id)
-    .fetch_optional(&mut *tx)
+    .bind(chat_id.0)
+    .fetch_all(pool)
     .await
-    .context("Failed to query user to skip in priority queue")?;
-
-    let queue_user = match queue_user {
-        Some(row) => QueueUserModel {
-            id: row.get("id"),
-            position: row.get("position"),
-            priority: row.get("priority"),
-            is_frozen: row.get("is_frozen"),
-            queue_id: row.get("queue_id"),
-            user_id: row.get("user_id"),
-        },
-        None => {
-            tx.rollback().await.context(
-                "Failed to rollback skip priority queue transaction (user not in queue)",
-            )?;
-            return Ok(());
-        }
-    };
+    .context(format!(
+        "Failed to query queue subjects of chat_id: {}",
+        chat_id
+    ))?
+    .into_iter()
+    .map(|row| row.get("subject"))
+    .collect();
 
-    let user_position = queue_user.position;
+    Ok(subjects)
+}
 
-    let max_position_row = sqlx::query(
+/// The priority settings of the chat, or the defaults.
+pub async fn get_queue_priority_settings(
+    pool: &PgPool,
+    chat_id: ChatId,
+) -> anyhow::Result<QueuePrioritySettingsModel> {
+    let settings = sqlx::query(
         r#"
-        SELECT MAX(position) as max_pos
-        FROM queue_users
-        WHERE queue_id = $1
+        SELECT chat_id, window_days, half_life_days
+        FROM queue_priority_settings
+        WHERE chat_id = $1
         "#,
     )
-    .bind(queue_id)
-    .fetch_one(&mut *tx)
+    .bind(chat_id.0)
+    .fetch_optional(pool)
     .await
-    .context("Failed to get max position for skip operation")?;
-
-    let max_position = max_position_row
-        .get::<Option<i32>, _>("max_pos")
-        .unwrap_or(0);
+    .context(format!(
+        "Failed to query queue priority settings of chat_id: {}",
+        chat_id
+    ))?
+    .map(|row| QueuePrioritySettingsModel {
+        chat_id: row.get("chat_id"),
+        window_days: row.get("window_days"),
+        half_life_days: row.get("half_life_days"),
+    })
+    .unwrap_or_else(|| QueuePrioritySettingsModel::default_for(chat_id.0));
 
-    if user_position == max_position {
-        tx.commit()
-            .await
-            .context("Failed to commit skip priority queue transaction (no change needed)")?;
-        return Ok(());
-    }
+    Ok(settings)
+}
 
+pub async fn set_queue_priority_settings(
+    pool: &PgPool,
+    settings: &QueuePrioritySettingsModel,
+) -> anyhow::Result<()> {
     sqlx::query(
         r#"
-        UPDATE queue_users
-        SET position = position - 1
-        WHERE queue_id = $1 AND position > $2
+        INSERT INTO queue_priority_settings (chat_id, window_days, half_life_days)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (chat_id) DO UPDATE
+        SET window_days = EXCLUDED.window_days, half_life_days = EXCLUDED.half_life_days
         "#,
     )
-    .bind(queue_id)
-    .bind(user_position)
-    .execute(&mut *tx)
+    .bind(settings.chat_id)
+    .bind(settings.window_days)
+    .bind(settings.half_life_days)
+    .execute(pool)
     .await
-    .context("Failed to shift queue positions during skip operation")?;
+    .context(format!(
+        "Failed to set queue priority settings of chat_id: {}",
+        settings.chat_id
+    ))?;
 
-    let update_query = if done {
-        sqlx::query(
-            r#"
-            UPDATE queue_users
-            SET position = $1, priority = COALESCE(priority, 0) + 1
-            WHERE id = $2
-            "#,
-        )
-        .bind(max_position)
-        .bind(queue_user.id)
-    } else {
-        sqlx::query(
-            r#"
-            UPDATE queue_users
-            SET position = $1
-            WHERE id = $2
-            "#,
-        )
-        .bind(max_position)
-        .bind(queue_user.id)
+    Ok(())
+}
+
+pub async fn skip_priority_queue(
+    pool: &PgPool,
+    queue_id: i32,
+    user_id: i32,
+    done: bool,
+) -> anyhow::Result<()> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for skipping in priority queue")?;
+
+    let (snapshot, mut users) = snapshot_queue(&mut tx, queue_id).await?;
+    let Some(index) = users.iter().position(|user| user.user_id == user_id) else {
+        tx.rollback()
+            .await
+            .context("Failed to rollback skip priority queue transaction (user not in queue)")?;
+        return Ok(());
     };
 
-    update_query
+    if index == users.len() - 1 {
+        tx.commit()
+            .await
+            .context("Failed to commit skip priority queue transaction (no change needed)")?;
+        return Ok(());
+    }
+
+    let queue_user = users.remove(index);
+    let user_position = queue_user.position;
+    users.push(queue_user);
+    write_positions(&mut tx, &users).await?;
+
+    if done {
+        sqlx::query(
+            r#"
+            UPDATE queue_users
+            SET priority = COALESCE(priority, 0) + 1
+            WHERE queue_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(queue_id)
+        .bind(user_id)
         .execute(&mut *tx)
         .await
-        .context("Failed to move user to last position during skip operation")?;
+        .context("Failed to raise priority during done operation")?;
+    }
+
+    let action = if done {
+        QueueAction::Done
+    } else {
+        QueueAction::Skip
+    };
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(user_id),
+        action,
+        None,
+        user_position.to_string(),
+        &snapshot,
+    )
+    .await?;
 
     tx.commit()
         .await
@@ -654,6 +731,12 @@ pub async fn skip_priority_queue(
 }
 
 pub async fn freeze_user(pool: &PgPool, queue_id: i32, user_id: i32) -> anyhow::Result<()> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for freezing user")?;
+
+    let (snapshot, _) = snapshot_queue(&mut tx, queue_id).await?;
     let result = sqlx::query(
         r#"
         UPDATE queue_users
@@ -663,7 +746,7 @@ pub async fn freeze_user(pool: &PgPool, queue_id: i32, user_id: i32) -> anyhow::
     )
     .bind(queue_id)
     .bind(user_id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await
     .context("Failed to freeze user in queue")?;
 
@@ -671,5 +754,791 @@ pub async fn freeze_user(pool: &PgPool, queue_id: i32, user_id: i32) -> anyhow::
         bail!("User not found in queue");
     }
 
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(user_id),
+        QueueAction::Freeze,
+        None,
+        String::new(),
+        &snapshot,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit freeze user transaction")?;
+
+    Ok(())
+}
+
+/// Members of the queue in order together with the snapshot logged with the
+/// change. The queue stays locked until the transaction ends.
+async fn snapshot_queue(
+    tx: &mut Transaction<'_, Postgres>,
+    queue_id: i32,
+) -> anyhow::Result<(QueueSnapshot, Vec<QueueUserModel>)> {
+    let queue = sqlx::query(
+        r#"
+        SELECT is_mixed, is_closed
+        FROM queues
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(queue_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .context(format!("Failed to lock queue {}", queue_id))?
+    .ok_or_else(|| anyhow::anyhow!("Queue not found"))?;
+
+    let users: Vec<QueueUserModel> = sqlx::query(
+        r#"
+        SELECT id, position, priority, is_frozen, queue_id, user_id
+        FROM queue_users
+        WHERE queue_id = $1
+        ORDER BY position
+        FOR UPDATE
+        "#,
+    )
+    .bind(queue_id)
+    .fetch_all(&mut **tx)
+    .await
+    .context(format!("Failed to lock users of queue {}", queue_id))?
+    .into_iter()
+    .map(|row| QueueUserModel {
+        id: row.get("id"),
+        position: row.get("position"),
+        priority: row.get("priority"),
+        is_frozen: row.get("is_frozen"),
+        queue_id: row.get("queue_id"),
+        user_id: row.get("user_id"),
+    })
+    .collect();
+
+    let snapshot = QueueSnapshot {
+        is_mixed: queue.get("is_mixed"),
+        is_closed: queue.get("is_closed"),
+        users: users
+            .iter()
+            .map(|user| QueueSnapshotUser {
+                user_id: user.user_id,
+                priority: user.priority,
+                is_frozen: user.is_frozen,
+            })
+            .collect(),
+    };
+
+    Ok((snapshot, users))
+}
+
+/// Numbers the members from 1 in the given order, skipping those already in place.
+async fn write_positions(
+    tx: &mut Transaction<'_, Postgres>,
+    users: &[QueueUserModel],
+) -> anyhow::Result<()> {
+    for (i, user) in users.iter().enumerate() {
+        let new_position = i as i32 + 1;
+        if user.position == new_position {
+            continue;
+        }
+        sqlx::query(
+            r#"
+            UPDATE queue_users
+            SET position = $1
+            WHERE id = $2
+            "#,
+        )
+        .bind(new_position)
+        .bind(user.id)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to update queue user position")?;
+    }
+
+    Ok(())
+}
+
+async fn log_queue_action(
+    tx: &mut Transaction<'_, Postgres>,
+    queue_id: i32,
+    actor_id: Option<i32>,
+    action: QueueAction,
+    target_id: Option<i32>,
+    details: String,
+    snapshot: &QueueSnapshot,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO queue_audit_log (queue_id, actor_id, action, target_id, details, snapshot)
+        VALUES ($1, $2, $3, $4, $5, $6::JSONB)
+        "#,
+    )
+    .bind(queue_id)
+    .bind(actor_id)
+    .bind(String::from(action))
+    .bind(target_id)
+    .bind(details)
+    .bind(serde_json::to_string(snapshot)?)
+    .execute(&mut **tx)
+    .await
+    .context(format!("Failed to log action on queue {}", queue_id))?;
+
+    Ok(())
+}
+
+/// Moves the member at position `from` to `to`, shifting the ones in between.
+/// Returns the moved member, or `None` if a position is out of range.
+pub async fn move_queue_user(
+    pool: &PgPool,
+    queue_id: i32,
+    actor_id: i32,
+    from: i32,
+    to: i32,
+) -> anyhow::Result<Option<QueueUserModel>> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for moving queue user")?;
+
+    let (snapshot, mut users) = snapshot_queue(&mut tx, queue_id).await?;
+    if !move_member(&mut users, from as usize, to as usize) {
+        return Ok(None);
+    }
+    write_positions(&mut tx, &users).await?;
+
+    let moved = users[to as usize - 1].clone();
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(actor_id),
+        QueueAction::Move,
+        Some(moved.user_id),
+        format!("{} → {}", from, to),
+        &snapshot,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit move queue user transaction")?;
+
+    Ok(Some(moved))
+}
+
+/// Swaps the members at positions `a` and `b`. Returns `false` if a position is
+/// out of range.
+pub async fn swap_queue_users(
+    pool: &PgPool,
+    queue_id: i32,
+    actor_id: i32,
+    a: i32,
+    b: i32,
+) -> anyhow::Result<bool> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for swapping queue users")?;
+
+    let (snapshot, mut users) = snapshot_queue(&mut tx, queue_id).await?;
+    if !swap_members(&mut users, a as usize, b as usize) {
+        return Ok(false);
+    }
+    write_positions(&mut tx, &users).await?;
+
+    let target_id = users[b as usize - 1].user_id;
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(actor_id),
+        QueueAction::Swap,
+        Some(target_id),
+        format!("{} ⇄ {}", a, b),
+        &snapshot,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit swap queue users transaction")?;
+
+    Ok(true)
+}
+
+/// Removes the member at `position` and closes the gap. Returns the removed
+/// member, or `None` if the position is out of range.
+pub async fn kick_queue_user(
+    pool: &PgPool,
+    queue_id: i32,
+    actor_id: i32,
+    position: i32,
+) -> anyhow::Result<Option<QueueUserModel>> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for kicking queue user")?;
+
+    let (snapshot, mut users) = snapshot_queue(&mut tx, queue_id).await?;
+    if !(1..=users.len() as i32).contains(&position) {
+        return Ok(None);
+    }
+    let kicked = users.remove(position as usize - 1);
+
+    sqlx::query(
+        r#"
+        DELETE FROM queue_users
+        WHERE id = $1
+        "#,
+    )
+    .bind(kicked.id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to delete kicked queue user")?;
+    write_positions(&mut tx, &users).await?;
+
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(actor_id),
+        QueueAction::Kick,
+        Some(kicked.user_id),
+        position.to_string(),
+        &snapshot,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit kick queue user transaction")?;
+
+    Ok(Some(kicked))
+}
+
+/// Adds the user to the end of the queue on an admin's behalf. Returns `None`
+/// if the user is already in the queue.
+pub async fn add_queue_user_by_admin(
+    pool: &PgPool,
+    queue_id: i32,
+    actor_id: i32,
+    user_id: i32,
+) -> anyhow::Result<Option<QueueUserModel>> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for adding queue user")?;
+
+    let (snapshot, users) = snapshot_queue(&mut tx, queue_id).await?;
+    if users.iter().any(|user| user.user_id == user_id) {
+        return Ok(None);
+    }
+    let position = users.len() as i32 + 1;
+
+    let new_user = sqlx::query(
+        r#"
+        INSERT INTO queue_users (queue_id, user_id, position, is_frozen)
+        VALUES ($1, $2, $3, FALSE)
+        RETURNING id, queue_id, user_id, priority, position, is_frozen
+        "#,
+    )
+    .bind(queue_id)
+    .bind(user_id)
+    .bind(position)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to insert queue user added by admin")?;
+
+    let new_user = QueueUserModel {
+        id: new_user.get("id"),
+        queue_id: new_user.get("queue_id"),
+        user_id: new_user.get("user_id"),
+        priority: new_user.get("priority"),
+        position: new_user.get("position"),
+        is_frozen: new_user.get("is_frozen"),
+    };
+
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        Some(actor_id),
+        QueueAction::Add,
+        Some(user_id),
+        position.to_string(),
+        &snapshot,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit add queue user transaction")?;
+
+    Ok(Some(new_user))
+}
+
+/// A member of a queue by the id of their `queue_users` row.
+pub async fn get_queue_member(
+    pool: &PgPool,
+    queue_user_id: i32,
+) -> anyhow::Result<Option<QueueUserWithUserModel>> {
+    let member = sqlx::query(
+        r#"
+        SELECT
+            qu.id,
+            qu.position,
+            qu.priority,
+            qu.is_frozen,
+            qu.queue_id,
+            qu.user_id,
+            u.id as user_id_user,
+            u.username,
+            u.account_id,
+            u.chat_id as chat_id_user,
+            u.name,
+            qu.weight,
+            qu.defences,
+            qu.last_defended_at
+        FROM queue_users qu
+        JOIN users u ON qu.user_id = u.id
+        WHERE qu.id = $1
+        "#,
+    )
+    .bind(queue_user_id)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to query queue member {}", queue_user_id))?
+    .map(|row| QueueUserWithUserModel {
+        id: row.get("id"),
+        position: row.get("position"),
+        priority: row.get("priority"),
+        is_frozen: row.get("is_frozen"),
+        queue_id: row.get("queue_id"),
+        user_id: row.get("user_id"),
+        user_id_user: row.get("user_id_user"),
+        username: row.get("username"),
+        account_id: row.get("account_id"),
+        chat_id_user: row.get("chat_id_user"),
+        name: row.get("name"),
+        weight: row.get("weight"),
+        defences: row.get("defences"),
+        last_defended_at: row.get("last_defended_at"),
+    });
+
+    Ok(member)
+}
+
+/// `None` for actions this version doesn't know.
+fn queue_event_from_row(row: &PgRow) -> Option<QueueEventModel> {
+    let action: String = row.get("action");
+    let snapshot: Option<String> = row.get("snapshot");
+    Some(QueueEventModel {
+        id: row.get("id"),
+        queue_id: row.get("queue_id"),
+        chat_id: row.get("chat_id"),
+        actor_id: row.get("actor_id"),
+        actor_name: row.get("actor_name"),
+        action: QueueAction::try_from(action.as_str()).ok()?,
+        target_name: row.get("target_name"),
+        details: row.get("details"),
+        created_at: row.get("created_at"),
+        undone_at: row.get("undone_at"),
+        snapshot: snapshot.and_then(|snapshot| serde_json::from_str(&snapshot).ok()),
+    })
+}
+
+/// The latest changes of the queue, oldest first.
+pub async fn get_queue_history(
+    pool: &PgPool,
+    queue_id: i32,
+    limit: i64,
+) -> anyhow::Result<Vec<QueueEventModel>> {
+    let mut events: Vec<QueueEventModel> = sqlx::query(
+        r#"
+        SELECT
+            l.id,
+            l.queue_id,
+            q.chat_id,
+            l.actor_id,
+            a.name AS actor_name,
+            l.action,
+            t.name AS target_name,
+            l.details,
+            (l.created_at AT TIME ZONE current_setting('TimeZone')) AT TIME ZONE c.timezone AS created_at,
+            l.undone_at,
+            l.snapshot::TEXT AS snapshot
+        FROM queue_audit_log l
+        JOIN queues q ON q.id = l.queue_id
+        JOIN chats c ON c.chat_id = q.chat_id
+        LEFT JOIN users a ON a.id = l.actor_id
+        LEFT JOIN users t ON t.id = l.target_id
+        WHERE l.queue_id = $1
+        ORDER BY l.id DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(queue_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context(format!("Failed to query history of queue {}", queue_id))?
+    .into_iter()
+    .filter_map(|row| queue_event_from_row(&row))
+    .collect();
+    events.reverse();
+
+    Ok(events)
+}
+
+/// The last change of the queue that is not undone yet, if it was made in the
+/// last `window_minutes` and can be undone.
+pub async fn get_undoable_event(
+    pool: &PgPool,
+    queue_id: i32,
+    window_minutes: i32,
+) -> anyhow::Result<Option<QueueEventModel>> {
+    let event = sqlx::query(
+        r#"
+        SELECT
+            l.id,
+            l.queue_id,
+            q.chat_id,
+            l.actor_id,
+            a.name AS actor_name,
+            l.action,
+            t.name AS target_name,
+            l.details,
+            (l.created_at AT TIME ZONE current_setting('TimeZone')) AT TIME ZONE c.timezone AS created_at,
+            l.undone_at,
+            l.snapshot::TEXT AS snapshot
+        FROM queue_audit_log l
+        JOIN queues q ON q.id = l.queue_id
+        JOIN chats c ON c.chat_id = q.chat_id
+        LEFT JOIN users a ON a.id = l.actor_id
+        LEFT JOIN users t ON t.id = l.target_id
+        WHERE l.id = (
+            SELECT MAX(id)
+            FROM queue_audit_log
+            WHERE queue_id = $1 AND undone_at IS NULL
+        )
+            AND l.snapshot IS NOT NULL
+            AND l.created_at > NOW() - make_interval(mins => $2)
+        "#,
+    )
+    .bind(queue_id)
+    .bind(window_minutes)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to query last change of queue {}", queue_id))?
+    .and_then(|row| queue_event_from_row(&row));
+
+    Ok(event)
+}
+
+/// Restores the queue to its state before the change. Returns `false` if the
+/// queue changed again in the meantime.
+pub async fn undo_queue_event(pool: &PgPool, event: &QueueEventModel) -> anyhow::Result<bool> {
+    let Some(snapshot) = event.snapshot.as_ref() else {
+        return Ok(false);
+    };
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for undoing queue change")?;
+
+    snapshot_queue(&mut tx, event.queue_id).await?;
+    let last_id: Option<i32> = sqlx::query(
+        r#"
+        SELECT MAX(id) AS last_id
+        FROM queue_audit_log
+        WHERE queue_id = $1 AND undone_at IS NULL
+        "#,
+    )
+    .bind(event.queue_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to query last queue change")?
+    .get("last_id");
+    if last_id != Some(event.id) {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        DELETE FROM queue_users
+        WHERE queue_id = $1
+        "#,
+    )
+    .bind(event.queue_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to clear queue users for undo")?;
+
+    for (i, user) in snapshot.users.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO queue_users (queue_id, user_id, position, priority, is_frozen)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(event.queue_id)
+        .bind(user.user_id)
+        .bind(i as i32 + 1)
+        .bind(user.priority)
+        .bind(user.is_frozen)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to restore queue user")?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE queues
+        SET is_mixed = $2, is_closed = $3, is_deleted = FALSE
+        WHERE id = $1
+        "#,
+    )
+    .bind(event.queue_id)
+    .bind(snapshot.is_mixed)
+    .bind(snapshot.is_closed)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to restore queue")?;
+
+    // An undone draw leaves an ordinary mixed queue behind.
+    if event.action == QueueAction::Draw {
+        sqlx::query(
+            r#"
+            UPDATE queues
+            SET lottery_seed = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(event.queue_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear lottery seed")?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE queue_audit_log
+        SET undone_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(event.id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to mark queue change as undone")?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit undo queue change transaction")?;
+
+    Ok(true)
+}
+
+/// Points the queue to a new message, e.g. after it was restored.
+pub async fn set_queue_message(
+    pool: &PgPool,
+    queue_id: i32,
+    message_id: MessageId,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE queues
+        SET message_id = $2
+        WHERE id = $1
+        "#,
+    )
+    .bind(queue_id)
+    .bind(message_id.0)
+    .execute(pool)
+    .await
+    .context(format!("Failed to update message of queue {}", queue_id))?;
+
     Ok(())
 }
+
+/// Saves the opening and closing times, the limit and the lesson of the queue.
+pub async fn set_queue_schedule(pool: &PgPool, queue: &QueueModel) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE queues
+        SET opens_at = $2, closes_at = $3, max_users = $4, entry_id = $5, archive_at = $6,
+            is_closed = $7
+        WHERE id = $1
+        "#,
+    )
+    .bind(queue.id)
+    .bind(queue.opens_at)
+    .bind(queue.closes_at)
+    .bind(queue.max_users)
+    .bind(queue.entry_id)
+    .bind(queue.archive_at)
+    .bind(queue.is_closed)
+    .execute(pool)
+    .await
+    .context(format!("Failed to update schedule of queue {}", queue.id))?;
+
+    Ok(())
+}
+
+/// Opens, closes and archives the queues whose chat-local time has come.
+/// Returns the ids of the queues that changed.
+pub async fn apply_queue_schedules(pool: &PgPool) -> anyhow::Result<Vec<i32>> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for queue schedules")?;
+
+    let opened = sqlx::query(
+        r#"
+        UPDATE queues q
+        SET opens_at = NULL
+        FROM chats c
+        WHERE c.chat_id = q.chat_id
+            AND q.opens_at <= NOW() AT TIME ZONE c.timezone
+            AND q.is_deleted = FALSE
+        RETURNING q.id
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to open scheduled queues")?;
+
+    let closed = sqlx::query(
+        r#"
+        UPDATE queues q
+        SET closes_at = NULL, is_closed = TRUE
+        FROM chats c
+        WHERE c.chat_id = q.chat_id
+            AND q.closes_at <= NOW() AT TIME ZONE c.timezone
+            AND q.is_deleted = FALSE
+        RETURNING q.id
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to close scheduled queues")?;
+
+    let archived = sqlx::query(
+        r#"
+        UPDATE queues q
+        SET archive_at = NULL, is_closed = TRUE, is_archived = TRUE
+        FROM chats c
+        WHERE c.chat_id = q.chat_id
+            AND q.archive_at <= NOW() AT TIME ZONE c.timezone
+            AND q.is_deleted = FALSE
+        RETURNING q.id
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to archive queues after their lessons")?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit queue schedules transaction")?;
+
+    let mut queue_ids: Vec<i32> = opened
+        .iter()
+        .chain(closed.iter())
+        .chain(archived.iter())
+        .map(|row| row.get("id"))
+        .collect();
+    queue_ids.sort_unstable();
+    queue_ids.dedup();
+
+    Ok(queue_ids)
+}
+
+/// Starts the join window of a lottery queue; its members are drawn at `closes_at`.
+pub async fn start_lottery(
+    pool: &PgPool,
+    queue_id: i32,
+    seed: &str,
+    closes_at: NaiveDateTime,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE queues
+        SET lottery_seed = $2, lottery_closes_at = $3
+        WHERE id = $1
+        "#,
+    )
+    .bind(queue_id)
+    .bind(seed)
+    .bind(closes_at)
+    .execute(pool)
+    .await
+    .context(format!("Failed to start lottery of queue {}", queue_id))?;
+
+    Ok(())
+}
+
+/// Lottery queues whose join window is over in chat-local time.
+pub async fn get_due_lotteries(pool: &PgPool) -> anyhow::Result<Vec<i32>> {
+    let queue_ids = sqlx::query(
+        r#"
+        SELECT q.id
+        FROM queues q
+        JOIN chats c ON c.chat_id = q.chat_id
+        WHERE q.lottery_closes_at <= NOW() AT TIME ZONE c.timezone
+            AND q.is_deleted = FALSE
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to query due lotteries")?
+    .into_iter()
+    .map(|row| row.get("id"))
+    .collect();
+
+    Ok(queue_ids)
+}
+
+/// Orders the members who joined during the window with the queue's seed and
+/// ends the window. Returns `false` if the draw was already done.
+pub async fn draw_lottery(pool: &PgPool, queue_id: i32) -> anyhow::Result<bool> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to begin transaction for lottery draw")?;
+
+    let (snapshot, mut all_users) = snapshot_queue(&mut tx, queue_id).await?;
+    let seed: Option<String> = sqlx::query(
+        r#"
+        UPDATE queues
+        SET lottery_closes_at = NULL, is_mixed = TRUE
+        WHERE id = $1 AND lottery_closes_at IS NOT NULL
+        RETURNING lottery_seed
+        "#,
+    )
+    .bind(queue_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context(format!("Failed to close lottery of queue {}", queue_id))?
+    .and_then(|row| row.get("lottery_seed"));
+    let Some(seed) = seed else {
+        return Ok(false);
+    };
+
+    draw_order(&mut all_users, &seed, queue_id);
+    write_positions(&mut tx, &all_users).await?;
+
+    log_queue_action(
+        &mut tx,
+        queue_id,
+        None,
+        QueueAction::Draw,
+        None,
+        seed,
+        &snapshot,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit lottery draw transaction")?;
+
+    Ok(true)
+}
@@ -0,0 +1,215 @@
+use anyhow::Context;
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::{PgPool, Row};
+use teloxide::types::ChatId;
+
+use crate::models::timetable::ReminderSettingsModel;
+
+/// How long sent reminders are remembered for deduplication.
+const SENT_RETENTION_DAYS: i32 = 7;
+
+pub async fn get_reminder_settings(
+    pool: &PgPool,
+    chat_id: ChatId,
+) -> anyhow::Result<ReminderSettingsModel> {
+    let settings = sqlx::query(
+        r#"
+        SELECT chat_id, offsets, muted_class_types, digest_time, attendance
+        FROM reminder_settings
+        WHERE chat_id = $1
+        "#,
+    )
+    .bind(chat_id.0)
+    .fetch_optional(pool)
+    .await
+    .context(format!(
+        "Failed to query reminder settings of chat_id: {}",
+        chat_id
+    ))?
+    .map(|row| ReminderSettingsModel {
+        chat_id: row.get("chat_id"),
+        offsets: row.get("offsets"),
+        muted_class_types: row.get("muted_class_types"),
+        digest_time: row.get("digest_time"),
+        attendance: row.get("attendance"),
+    })
+    .unwrap_or_else(|| ReminderSettingsModel::default_for(chat_id.0));
+
+    Ok(settings)
+}
+
+pub async fn set_reminder_offsets(
+    pool: &PgPool,
+    chat_id: ChatId,
+    offsets: &[i32],
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO reminder_settings (chat_id, offsets)
+        VALUES ($1, $2)
+        ON CONFLICT (chat_id) DO UPDATE
+        SET offsets = EXCLUDED.offsets
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(offsets)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to set reminder offsets of chat_id: {}",
+        chat_id
+    ))?;
+
+    Ok(())
+}
+
+/// Mutes or unmutes reminders for a class type. Returns whether it is muted now.
+pub async fn toggle_muted_class_type(
+    pool: &PgPool,
+    chat_id: ChatId,
+    class_type: &str,
+) -> anyhow::Result<bool> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO reminder_settings (chat_id, muted_class_types)
+        VALUES ($1, ARRAY[$2])
+        ON CONFLICT (chat_id) DO UPDATE
+        SET muted_class_types = CASE
+            WHEN $2 = ANY(reminder_settings.muted_class_types)
+                THEN array_remove(reminder_settings.muted_class_types, $2)
+            ELSE array_append(reminder_settings.muted_class_types, $2)
+        END
+        RETURNING $2 = ANY(muted_class_types) AS muted
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(class_type)
+    .fetch_one(pool)
+    .await
+    .context(format!(
+        "Failed to toggle muted class type of chat_id: {}",
+        chat_id
+    ))?;
+
+    Ok(row.get("muted"))
+}
+
+pub async fn set_digest_time(
+    pool: &PgPool,
+    chat_id: ChatId,
+    digest_time: Option<NaiveTime>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO reminder_settings (chat_id, digest_time)
+        VALUES ($1, $2)
+        ON CONFLICT (chat_id) DO UPDATE
+        SET digest_time = EXCLUDED.digest_time
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(digest_time)
+    .execute(pool)
+    .await
+    .context(format!("Failed to set digest time of chat_id: {}", chat_id))?;
+
+    Ok(())
+}
+
+pub async fn set_attendance(pool: &PgPool, chat_id: ChatId, enabled: bool) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO reminder_settings (chat_id, attendance)
+        VALUES ($1, $2)
+        ON CONFLICT (chat_id) DO UPDATE
+        SET attendance = EXCLUDED.attendance
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(enabled)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to set attendance check-ins of chat_id: {}",
+        chat_id
+    ))?;
+
+    Ok(())
+}
+
+/// Records the reminder as sent. Returns `false` if it was already sent.
+pub async fn claim_reminder(
+    pool: &PgPool,
+    chat_id: ChatId,
+    entry_id: i32,
+    class_date: NaiveDate,
+    offset_minutes: i32,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO sent_reminders (chat_id, entry_id, class_date, offset_minutes)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(entry_id)
+    .bind(class_date)
+    .bind(offset_minutes)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to record reminder for entry id: {}",
+        entry_id
+    ))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records the morning digest as sent. Returns `false` if it was already sent.
+pub async fn claim_digest(
+    pool: &PgPool,
+    chat_id: ChatId,
+    digest_date: NaiveDate,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO sent_digests (chat_id, digest_date)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(digest_date)
+    .execute(pool)
+    .await
+    .context(format!("Failed to record digest of chat_id: {}", chat_id))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn prune_sent_notifications(pool: &PgPool) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM sent_reminders
+        WHERE sent_at < NOW() - make_interval(days => $1)
+        "#,
+    )
+    .bind(SENT_RETENTION_DAYS)
+    .execute(pool)
+    .await
+    .context("Failed to prune sent reminders")?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM sent_digests
+        WHERE sent_at < NOW() - make_interval(days => $1)
+        "#,
+    )
+    .bind(SENT_RETENTION_DAYS)
+    .execute(pool)
+    .await
+    .context("Failed to prune sent digests")?;
+
+    Ok(())
+}
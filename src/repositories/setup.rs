@@ -1,16 +1,31 @@
 use std::{env, time::Duration};
 
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, PgPool};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 pub async fn connect_db() -> PgPool {
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
 
-    PgPoolOptions::new()
+    let pool = PgPoolOptions::new()
         .max_connections(10)
         .max_lifetime(Duration::from_secs(30 * 60))
         .idle_timeout(Duration::from_secs(5 * 60))
         .test_before_acquire(true)
         .connect(&database_url)
         .await
-        .expect("Failed to connect to database")
+        .expect("Failed to connect to database");
+
+    run_migrations(&pool).await;
+
+    pool
+}
+
+async fn run_migrations(pool: &PgPool) {
+    tracing::info!("Applying database migrations");
+    if let Err(e) = MIGRATOR.run(pool).await {
+        tracing::error!("Failed to apply database migrations: {:?}", e);
+        std::process::exit(1);
+    }
+    tracing::info!("Database schema is up to date");
 }
@@ -1,7 +1,9 @@
 use anyhow::Context;
-use sqlx::{PgPool, Row};
+use chrono::NaiveDate;
+use sqlx::{Executor, PgPool, Postgres, Row};
 use teloxide::types::{ChatId, UserId};
 
+use crate::config::daily_limits::DailyLimitConfig;
 use crate::models::stats::{FullStats, GambleModel, GroupMemberStat, GroupStats};
 use crate::models::user::UserStatsModel;
 
@@ -33,7 +35,8 @@ pub async fn get_user_stats(pool: &PgPool, user_id: UserId) -> anyhow::Result<Us
     Ok(stats)
 }
 
-pub async fn get_full_me(pool: &PgPool, user_id: UserId) -> anyhow::Result<FullStats> {
+/// `None` if the account has no stats.
+pub async fn get_full_me(pool: &PgPool, user_id: UserId) -> anyhow::Result<Option<FullStats>> {
     let stats_row = sqlx::query(
         r#"
         SELECT us.id, us.user_id, us.balance, us.daily_limit, us.daily_used
@@ -47,15 +50,15 @@ pub async fn get_full_me(pool: &PgPool, user_id: UserId) -> anyhow::Result<FullS
     .await
     .context("Failed to query user stats")?;
 
-    let stats = stats_row
-        .map(|row| UserStatsModel {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            balance: row.get("balance"),
-            daily_limit: row.get("daily_limit"),
-            daily_used: row.get("daily_used"),
-        })
-        .ok_or_else(|| anyhow::anyhow!("User stats not found for account_id: {}", user_id))?;
+    let Some(stats) = stats_row.map(|row| UserStatsModel {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        balance: row.get("balance"),
+        daily_limit: row.get("daily_limit"),
+        daily_used: row.get("daily_used"),
+    }) else {
+        return Ok(None);
+    };
 
     let gamble_rows = sqlx::query(
         r#"
@@ -67,6 +70,9 @@ pub async fn get_full_me(pool: &PgPool, user_id: UserId) -> anyhow::Result<FullS
             g.bet,
             g.change,
             g.is_win,
+            g.outcome,
+            g.server_seed,
+            g.client_seed,
             g.created_at
         FROM gambles g
         JOIN users u ON g.user_id = u.id
@@ -89,6 +95,9 @@ pub async fn get_full_me(pool: &PgPool, user_id: UserId) -> anyhow::Result<FullS
             bet: row.get("bet"),
             change: row.get("change"),
             is_win: row.get("is_win"),
+            outcome: row.get("outcome"),
+            server_seed: row.get("server_seed"),
+            client_seed: row.get("client_seed"),
             created_at: row.get("created_at"),
         })
         .collect::<Vec<_>>();
@@ -153,7 +162,7 @@ pub async fn get_full_me(pool: &PgPool, user_id: UserId) -> anyhow::Result<FullS
         average_bet,
     };
 
-    Ok(full_stats)
+    Ok(Some(full_stats))
 }
 
 pub async fn transfer_reaction_points(
@@ -249,53 +258,60 @@ pub async fn transfer_reaction_points(
     Ok(())
 }
 
-pub async fn update_balance(pool: &PgPool, user_db_id: i32, change: i32) -> anyhow::Result<()> {
-    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
-
-    let stats = sqlx::query(
-        r#"
-        SELECT id, user_id, balance, daily_limit, daily_used
-        FROM user_stats
-        WHERE user_id = $1
-        "#,
-    )
-    .bind(user_db_id)
-    .fetch_optional(&mut *tx)
-    .await
-    .context("Failed to query user stats for balance update")?
-    .map(|row| UserStatsModel {
-        id: row.get("id"),
-        user_id: row.get("user_id"),
-        balance: row.get("balance"),
-        daily_limit: row.get("daily_limit"),
-        daily_used: row.get("daily_used"),
-    })
-    .ok_or_else(|| anyhow::anyhow!("User stats not found for user_id: {}", user_db_id))?;
-
-    sqlx::query(
+pub async fn update_balance<'c, E>(executor: E, user_db_id: i32, change: i32) -> anyhow::Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    let result = sqlx::query(
         r#"
         UPDATE user_stats
         SET balance = balance + $1
-        WHERE id = $2
+        WHERE user_id = $2
         "#,
     )
     .bind(change)
-    .bind(stats.id)
-    .execute(&mut *tx)
+    .bind(user_db_id)
+    .execute(executor)
     .await
     .context(format!(
         "Failed to update balance for user id: {}",
         user_db_id
     ))?;
 
-    tx.commit().await.context("Failed to commit transaction")?;
+    if result.rows_affected() == 0 {
+        anyhow::bail!("User stats not found for user_id: {}", user_db_id);
+    }
+
     Ok(())
 }
 
-pub async fn get_group_stats(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<GroupStats> {
+pub async fn get_free_spins(pool: &PgPool, user_db_id: i32) -> anyhow::Result<i32> {
+    let free_spins = sqlx::query(
+        r#"
+        SELECT free_spins
+        FROM user_stats
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_db_id)
+    .fetch_optional(pool)
+    .await
+    .context(format!(
+        "Failed to query free spins for user id: {}",
+        user_db_id
+    ))?
+    .map(|row| row.get("free_spins"))
+    .unwrap_or(0);
+
+    Ok(free_spins)
+}
+
+/// `None` if the chat is unknown.
+pub async fn get_group_stats(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<Option<GroupStats>> {
     let group_stats = sqlx::query(
         r#"
         SELECT
+            u.id as user_id,
             u.username as username,
             us.balance as balance
         FROM user_stats us
@@ -313,6 +329,7 @@ pub async fn get_group_stats(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<G
     ))?
     .into_iter()
     .map(|row| GroupMemberStat {
+        user_id: row.get("user_id"),
         username: row.get("username"),
         balance: row.get("balance"),
     })
@@ -330,11 +347,163 @@ pub async fn get_group_stats(pool: &PgPool, chat_id: ChatId) -> anyhow::Result<G
         "Failed to query chat title for chat_id: {}",
         chat_id
     ))?
-    .and_then(|row| row.try_get("title").ok())
-    .ok_or_else(|| anyhow::anyhow!("Chat not found for chat_id: {}", chat_id))?;
+    .and_then(|row| row.try_get("title").ok());
 
-    Ok(GroupStats {
-        group_name: group,
+    Ok(group.map(|group_name| GroupStats {
+        group_name,
         stats: group_stats,
-    })
+    }))
+}
+
+pub async fn record_message_activity(pool: &PgPool, user_id: UserId) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE user_stats us
+        SET messages_today = us.messages_today + 1
+        FROM users u
+        WHERE us.user_id = u.id AND u.account_id = $1
+        "#,
+    )
+    .bind(user_id.0 as i64)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to record message activity for account_id: {}",
+        user_id
+    ))?;
+
+    Ok(())
+}
+
+/// Resets the reaction allowance of every member of the chat for `local_date`.
+///
+/// Returns `false` without touching anything when the chat was already reset
+/// for that date, so the job can safely run as often as needed.
+pub async fn reset_daily_limits(
+    pool: &PgPool,
+    chat_id: ChatId,
+    local_date: NaiveDate,
+    config: &DailyLimitConfig,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let claimed = sqlx::query(
+        r#"
+        INSERT INTO daily_resets (chat_id, last_reset_date, last_run_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (chat_id) DO UPDATE
+        SET last_reset_date = EXCLUDED.last_reset_date, last_run_at = NOW()
+        WHERE daily_resets.last_reset_date < EXCLUDED.last_reset_date
+        RETURNING chat_id
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(local_date)
+    .fetch_optional(&mut *tx)
+    .await
+    .context(format!(
+        "Failed to claim daily reset for chat_id: {}",
+        chat_id
+    ))?;
+
+    if claimed.is_none() {
+        tx.rollback()
+            .await
+            .context("Failed to rollback transaction")?;
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE user_stats us
+        SET
+            active_streak = CASE WHEN us.messages_today > 0 THEN us.active_streak + 1 ELSE 0 END,
+            daily_limit = $2
+                + LEAST(us.messages_today * $3, $4)
+                + CASE
+                    WHEN us.messages_today > 0 THEN LEAST((us.active_streak + 1) * $5, $6)
+                    ELSE 0
+                  END,
+            daily_used = 0,
+            messages_today = 0
+        FROM users u
+        WHERE us.user_id = u.id AND u.chat_id = $1
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(config.base_limit)
+    .bind(config.message_bonus)
+    .bind(config.message_bonus_cap)
+    .bind(config.streak_bonus)
+    .bind(config.streak_bonus_cap)
+    .execute(&mut *tx)
+    .await
+    .context(format!(
+        "Failed to reset daily limits for chat_id: {}",
+        chat_id
+    ))?;
+
+    tx.commit().await.context("Failed to commit transaction")?;
+    Ok(true)
+}
+
+/// Adds flushed clicker clicks and credits one coin for every `clicks_per_coin`
+/// clicks, carrying the remainder over to the next batch. Returns `false`
+/// without crediting anything if the batch was already credited.
+pub async fn credit_clicks(
+    pool: &PgPool,
+    batch_id: &str,
+    clicks: &[(i32, i64)],
+    clicks_per_coin: i64,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let recorded = sqlx::query(
+        r#"
+        INSERT INTO clicker_batches (id)
+        VALUES ($1)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(batch_id)
+    .execute(&mut *tx)
+    .await
+    .context(format!("Failed to record click batch: {}", batch_id))?;
+    if recorded.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    for (user_db_id, count) in clicks {
+        sqlx::query(
+            r#"
+            UPDATE user_stats
+            SET
+                balance = balance + ((clicks + $1) / $3 - clicks / $3)::INTEGER,
+                clicks = clicks + $1
+            WHERE user_id = $2
+            "#,
+        )
+        .bind(count)
+        .bind(user_db_id)
+        .bind(clicks_per_coin)
+        .execute(&mut *tx)
+        .await
+        .context(format!(
+            "Failed to credit clicks for user id: {}",
+            user_db_id
+        ))?;
+    }
+
+    sqlx::query(
+        r#"
+        DELETE FROM clicker_batches
+        WHERE flushed_at < NOW() - INTERVAL '7 days'
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to prune old click batches")?;
+
+    tx.commit().await.context("Failed to commit transaction")?;
+    Ok(true)
 }
@@ -0,0 +1,191 @@
+use anyhow::Context;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use teloxide::types::ChatId;
+
+use crate::models::{timetable::TimetableModel, user::UserModel};
+
+fn timetable_from_row(row: &PgRow) -> TimetableModel {
+    TimetableModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        name: row.get("name"),
+    }
+}
+
+/// Subgroups of the chat by name, with the number of members in each.
+pub async fn get_subgroups(
+    pool: &PgPool,
+    chat_id: ChatId,
+) -> anyhow::Result<Vec<(TimetableModel, i64)>> {
+    let subgroups = sqlx::query(
+        r#"
+        SELECT tt.id, tt.chat_id, tt.name, COUNT(sm.user_id) AS members
+        FROM timetables tt
+        LEFT JOIN subgroup_members sm ON sm.timetable_id = tt.id
+        WHERE tt.chat_id = $1 AND tt.name <> ''
+        GROUP BY tt.id
+        ORDER BY tt.name
+        "#,
+    )
+    .bind(chat_id.0)
+    .fetch_all(pool)
+    .await
+    .context(format!("Failed to query subgroups of chat_id: {}", chat_id))?
+    .into_iter()
+    .map(|row| (timetable_from_row(&row), row.get("members")))
+    .collect();
+
+    Ok(subgroups)
+}
+
+pub async fn get_subgroup(
+    pool: &PgPool,
+    chat_id: ChatId,
+    name: &str,
+) -> anyhow::Result<Option<TimetableModel>> {
+    let subgroup = sqlx::query(
+        r#"
+        SELECT id, chat_id, name
+        FROM timetables
+        WHERE chat_id = $1 AND name = $2 AND name <> ''
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .context(format!(
+        "Failed to query subgroup {} of chat_id: {}",
+        name, chat_id
+    ))?
+    .map(|row| timetable_from_row(&row));
+
+    Ok(subgroup)
+}
+
+/// The subgroup the user belongs to, if any.
+pub async fn get_user_subgroup(
+    pool: &PgPool,
+    user_id: i32,
+) -> anyhow::Result<Option<TimetableModel>> {
+    let subgroup = sqlx::query(
+        r#"
+        SELECT tt.id, tt.chat_id, tt.name
+        FROM subgroup_members sm
+        JOIN timetables tt ON tt.id = sm.timetable_id
+        WHERE sm.user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to query subgroup of user id: {}", user_id))?
+    .map(|row| timetable_from_row(&row));
+
+    Ok(subgroup)
+}
+
+/// Puts the user into the subgroup, moving them out of their previous one.
+pub async fn join_subgroup(pool: &PgPool, user_id: i32, timetable_id: i32) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO subgroup_members (user_id, timetable_id)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET timetable_id = EXCLUDED.timetable_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(timetable_id)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to add user id: {} to subgroup id: {}",
+        user_id, timetable_id
+    ))?;
+
+    Ok(())
+}
+
+/// Returns `false` if the user was not in a subgroup.
+pub async fn leave_subgroup(pool: &PgPool, user_id: i32) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM subgroup_members
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to remove user id: {} from subgroup",
+        user_id
+    ))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_subgroup_members(
+    pool: &PgPool,
+    timetable_id: i32,
+) -> anyhow::Result<Vec<UserModel>> {
+    let members = sqlx::query_as::<_, UserModel>(
+        r#"
+        SELECT u.id, u.username, u.account_id, u.chat_id, u.name
+        FROM subgroup_members sm
+        JOIN users u ON u.id = sm.user_id
+        WHERE sm.timetable_id = $1
+        ORDER BY u.name
+        "#,
+    )
+    .bind(timetable_id)
+    .fetch_all(pool)
+    .await
+    .context(format!(
+        "Failed to query members of subgroup id: {}",
+        timetable_id
+    ))?;
+
+    Ok(members)
+}
+
+/// Deletes the subgroup with its lessons, members and import source. Returns
+/// `false` if there is no such subgroup.
+pub async fn remove_subgroup(pool: &PgPool, chat_id: ChatId, name: &str) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM timetables
+        WHERE chat_id = $1 AND name = $2 AND name <> ''
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(name)
+    .execute(&mut *tx)
+    .await
+    .context(format!(
+        "Failed to delete subgroup {} of chat_id: {}",
+        name, chat_id
+    ))?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM timetable_sources
+        WHERE chat_id = $1 AND subgroup = $2
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(name)
+    .execute(&mut *tx)
+    .await
+    .context(format!(
+        "Failed to delete timetable source of subgroup {}",
+        name
+    ))?;
+
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    Ok(removed.rows_affected() > 0)
+}
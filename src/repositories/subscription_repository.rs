@@ -0,0 +1,204 @@
+use anyhow::Context;
+use chrono::NaiveTime;
+use sqlx::{PgPool, Row};
+use teloxide::types::{ChatId, UserId};
+
+use crate::models::subscription::{
+    QuietHoursModel, SubscriberModel, SubscriptionKind, SubscriptionModel,
+};
+
+pub async fn subscribe(
+    pool: &PgPool,
+    user_id: UserId,
+    chat_id: ChatId,
+    kind: SubscriptionKind,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO subscriptions (account_id, chat_id, kind)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(user_id.0 as i64)
+    .bind(chat_id.0)
+    .bind(String::from(kind))
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to subscribe user {} to chat_id: {}",
+        user_id, chat_id
+    ))?;
+
+    Ok(())
+}
+
+/// Removes the user's subscriptions, limited to a chat and a kind when given.
+/// Returns how many were removed.
+pub async fn unsubscribe(
+    pool: &PgPool,
+    user_id: UserId,
+    chat_id: Option<ChatId>,
+    kind: Option<SubscriptionKind>,
+) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM subscriptions
+        WHERE account_id = $1
+            AND ($2::BIGINT IS NULL OR chat_id = $2)
+            AND ($3::TEXT IS NULL OR kind = $3)
+        "#,
+    )
+    .bind(user_id.0 as i64)
+    .bind(chat_id.map(|chat_id| chat_id.0))
+    .bind(kind.map(String::from))
+    .execute(pool)
+    .await
+    .context(format!("Failed to unsubscribe user {}", user_id))?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn get_subscriptions(
+    pool: &PgPool,
+    user_id: UserId,
+) -> anyhow::Result<Vec<SubscriptionModel>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT s.account_id, s.chat_id, c.title AS chat_title, s.kind
+        FROM subscriptions s
+        JOIN chats c ON c.chat_id = s.chat_id
+        WHERE s.account_id = $1
+        ORDER BY c.title, s.chat_id, s.kind
+        "#,
+    )
+    .bind(user_id.0 as i64)
+    .fetch_all(pool)
+    .await
+    .context(format!("Failed to query subscriptions of user {}", user_id))?;
+
+    let subscriptions = rows
+        .into_iter()
+        .filter_map(|row| {
+            let kind: String = row.get("kind");
+            Some(SubscriptionModel {
+                account_id: row.get("account_id"),
+                chat_id: row.get("chat_id"),
+                chat_title: row.get("chat_title"),
+                kind: SubscriptionKind::try_from(kind.as_str()).ok()?,
+            })
+        })
+        .collect();
+
+    Ok(subscriptions)
+}
+
+/// Subscribers of the chat's notifications of `kind`. Pass `account_id` to
+/// check a single user.
+pub async fn get_subscribers(
+    pool: &PgPool,
+    chat_id: ChatId,
+    kind: SubscriptionKind,
+    account_id: Option<i64>,
+) -> anyhow::Result<Vec<SubscriberModel>> {
+    let subscribers = sqlx::query(
+        r#"
+        SELECT s.account_id, c.title AS chat_title, q.starts_at, q.ends_at
+        FROM subscriptions s
+        JOIN chats c ON c.chat_id = s.chat_id
+        LEFT JOIN quiet_hours q ON q.account_id = s.account_id
+        WHERE s.chat_id = $1 AND s.kind = $2 AND ($3::BIGINT IS NULL OR s.account_id = $3)
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(String::from(kind))
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+    .context(format!(
+        "Failed to query subscribers of chat_id: {}",
+        chat_id
+    ))?
+    .into_iter()
+    .map(|row| {
+        let account_id: i64 = row.get("account_id");
+        let starts_at: Option<NaiveTime> = row.get("starts_at");
+        let ends_at: Option<NaiveTime> = row.get("ends_at");
+        SubscriberModel {
+            account_id,
+            chat_title: row.get("chat_title"),
+            quiet_hours: starts_at
+                .zip(ends_at)
+                .map(|(starts_at, ends_at)| QuietHoursModel {
+                    account_id,
+                    starts_at,
+                    ends_at,
+                }),
+        }
+    })
+    .collect();
+
+    Ok(subscribers)
+}
+
+pub async fn get_quiet_hours(
+    pool: &PgPool,
+    user_id: UserId,
+) -> anyhow::Result<Option<QuietHoursModel>> {
+    let quiet_hours = sqlx::query(
+        r#"
+        SELECT account_id, starts_at, ends_at
+        FROM quiet_hours
+        WHERE account_id = $1
+        "#,
+    )
+    .bind(user_id.0 as i64)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to query quiet hours of user {}", user_id))?
+    .map(|row| QuietHoursModel {
+        account_id: row.get("account_id"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.get("ends_at"),
+    });
+
+    Ok(quiet_hours)
+}
+
+/// Sets the user's quiet hours, or removes them when `hours` is `None`.
+pub async fn set_quiet_hours(
+    pool: &PgPool,
+    user_id: UserId,
+    hours: Option<(NaiveTime, NaiveTime)>,
+) -> anyhow::Result<()> {
+    let Some((starts_at, ends_at)) = hours else {
+        sqlx::query(
+            r#"
+            DELETE FROM quiet_hours
+            WHERE account_id = $1
+            "#,
+        )
+        .bind(user_id.0 as i64)
+        .execute(pool)
+        .await
+        .context(format!("Failed to remove quiet hours of user {}", user_id))?;
+        return Ok(());
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO quiet_hours (account_id, starts_at, ends_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (account_id) DO UPDATE
+        SET starts_at = EXCLUDED.starts_at, ends_at = EXCLUDED.ends_at
+        "#,
+    )
+    .bind(user_id.0 as i64)
+    .bind(starts_at)
+    .bind(ends_at)
+    .execute(pool)
+    .await
+    .context(format!("Failed to set quiet hours of user {}", user_id))?;
+
+    Ok(())
+}
@@ -1,224 +1,179 @@
 use anyhow::Context;
-use chrono::NaiveTime;
-use serde_json::Value;
-use sqlx::{PgPool, Row};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use teloxide::types::ChatId;
 
 use crate::{
-    bot::{
-        timetable::{Day, Week},
-        utils::time::get_current_time,
+    bot::timetable::{
+        calendar::AcademicCalendar,
+        sources::ImportedEntry,
+        sync::{diff_timetable, TimetableDiff},
+        Day,
     },
-    models::timetable::{TimetableEntryModel, TimetableModel},
+    models::timetable::{TimetableEntryModel, TimetableOverrideModel, TimetableSourceModel},
+    repositories::chat_repository::get_calendar,
 };
 
 const OFFSET: chrono::Duration = chrono::Duration::minutes(5);
 
-pub async fn import_timetable(pool: &PgPool, chat_id: i64, timetable: Value) -> anyhow::Result<()> {
-    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+/// Brings the timetable of the subgroup in line with `entries`; an empty
+/// `subgroup` is the timetable of the whole chat. Entries that still match keep
+/// their id, so links and one-off changes survive a re-import.
+pub async fn import_timetable(
+    pool: &PgPool,
+    chat_id: i64,
+    subgroup: &str,
+    entries: &[ImportedEntry],
+) -> anyhow::Result<TimetableDiff> {
+    let stored: Vec<_> = get_full_timetable(pool, ChatId(chat_id))
+        .await?
+        .into_iter()
+        .filter(|entry| entry.subgroup == subgroup)
+        .collect();
+    let diff = diff_timetable(&stored, entries);
 
-    let existing_timetable = sqlx::query(
-        r#"
-        SELECT id, chat_id
-        FROM timetables
-        WHERE chat_id = $1
-        "#,
-    )
-    .bind(chat_id)
-    .fetch_optional(&mut *tx)
-    .await
-    .context("Failed to query existing timetable")?
-    .map(|row| TimetableModel {
-        id: row.get("id"),
-        chat_id: row.get("chat_id"),
-    });
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let timetable_id = get_or_create_timetable(&mut tx, chat_id, subgroup).await?;
 
-    if let Some(existing_timetable) = existing_timetable {
+    for entry in &diff.removed {
         sqlx::query(
             r#"
             DELETE FROM timetable_entries
-            WHERE timetable_id = $1
+            WHERE id = $1
             "#,
         )
-        .bind(existing_timetable.id)
+        .bind(entry.id)
         .execute(&mut *tx)
         .await
-        .context("Failed to delete existing timetable entries")?;
+        .context(format!("Failed to delete timetable entry id: {}", entry.id))?;
+    }
 
+    for (entry, moved) in &diff.moved {
         sqlx::query(
             r#"
-            DELETE FROM timetables
-            WHERE id = $1
+            UPDATE timetable_entries
+            SET week = $1, day = $2, class_time = $3, link = COALESCE(link, $4)
+            WHERE id = $5
             "#,
         )
-        .bind(existing_timetable.id)
+        .bind(moved.week as i32)
+        .bind(u8::from(moved.day) as i32)
+        .bind(moved.class_time)
+        .bind(&moved.link)
+        .bind(entry.id)
         .execute(&mut *tx)
         .await
-        .context("Failed to delete existing timetable")?;
+        .context(format!("Failed to move timetable entry id: {}", entry.id))?;
+    }
+
+    for entry in &diff.added {
+        insert_entry(&mut tx, timetable_id, entry).await?;
     }
 
-    let created_timetable = sqlx::query(
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    Ok(diff)
+}
+
+async fn get_or_create_timetable(
+    tx: &mut Transaction<'_, Postgres>,
+    chat_id: i64,
+    subgroup: &str,
+) -> anyhow::Result<i32> {
+    let existing = sqlx::query(
         r#"
-        INSERT INTO timetables (chat_id)
-        VALUES ($1)
-        RETURNING id, chat_id
+        SELECT id
+        FROM timetables
+        WHERE chat_id = $1 AND name = $2
         "#,
     )
     .bind(chat_id)
-    .fetch_one(&mut *tx)
+    .bind(subgroup)
+    .fetch_optional(&mut **tx)
     .await
-    .context("Failed to insert new timetable")?;
-
-    let created_timetable = TimetableModel {
-        id: created_timetable.get("id"),
-        chat_id: created_timetable.get("chat_id"),
-    };
+    .context("Failed to query existing timetable")?;
 
-    for (week, schedule_key) in [
-        (Week::First, "scheduleFirstWeek"),
-        (Week::Second, "scheduleSecondWeek"),
-    ] {
-        if let Some(days) = timetable["data"][schedule_key].as_array() {
-            for (index, day) in days.iter().enumerate() {
-                if let Some(pairs) = day["pairs"].as_array() {
-                    for entry in pairs {
-                        let class_time_str = entry["time"].as_str().ok_or_else(|| {
-                            anyhow::anyhow!("Missing 'time' field in timetable entry")
-                        })?;
-                        let class_time = NaiveTime::parse_from_str(class_time_str, "%H:%M")
-                            .context(format!("Failed to parse time '{}'", class_time_str))?;
-
-                        sqlx::query(
-                            r#"
-                            INSERT INTO timetable_entries (timetable_id, week, day, class_name, class_type, class_time, link)
-                            VALUES ($1, $2, $3, $4, $5, $6, $7)
-                            "#,
-                        )
-                        .bind(created_timetable.id)
-                        .bind(week as i32)
-                        .bind(index as i32)
-                        .bind(entry["name"].as_str().unwrap_or_default())
-                        .bind(entry["tag"].as_str().unwrap_or_default())
-                        .bind(class_time)
-                        .bind(None::<String>) // Link is initially null
-                        .execute(&mut *tx)
-                        .await
-                        .context("Failed to insert timetable entry")?;
-                    }
-                }
-            }
-        }
+    if let Some(row) = existing {
+        return Ok(row.get("id"));
     }
 
-    tx.commit().await.context("Failed to commit transaction")?;
+    let created = sqlx::query(
+        r#"
+        INSERT INTO timetables (chat_id, name)
+        VALUES ($1, $2)
+        RETURNING id
+        "#,
+    )
+    .bind(chat_id)
+    .bind(subgroup)
+    .fetch_one(&mut **tx)
+    .await
+    .context("Failed to insert new timetable")?;
 
-    Ok(())
+    Ok(created.get("id"))
 }
 
-pub async fn get_today_timetable(
-    pool: &PgPool,
-    chat_id: &str,
-) -> anyhow::Result<Vec<TimetableEntryModel>> {
-    let week = Week::current();
-    let day = Day::current();
-
-    let entries = sqlx::query(
+async fn insert_entry(
+    tx: &mut Transaction<'_, Postgres>,
+    timetable_id: i32,
+    entry: &ImportedEntry,
+) -> anyhow::Result<()> {
+    sqlx::query(
         r#"
-        SELECT
-            te.id,
-            te.week,
-            te.day,
-            te.timetable_id,
-            te.class_name,
-            te.class_type,
-            te.class_time,
-            te.link
-        FROM timetable_entries te
-        JOIN timetables tt ON te.timetable_id = tt.id
-        WHERE tt.chat_id = $1
-          AND te.week = $2
-          AND te.day = $3
-        ORDER BY te.class_time
+        INSERT INTO timetable_entries (timetable_id, week, day, class_name, class_type, class_time, link)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
         "#,
     )
-    .bind(chat_id)
-    .bind(week as i32)
-    .bind(day as i32)
-    .fetch_all(pool)
+    .bind(timetable_id)
+    .bind(entry.week as i32)
+    .bind(u8::from(entry.day) as i32)
+    .bind(&entry.class_name)
+    .bind(&entry.class_type)
+    .bind(entry.class_time)
+    .bind(&entry.link)
+    .execute(&mut **tx)
     .await
-    .context("Failed to query today's timetable")?
-    .into_iter()
-    .map(|row| TimetableEntryModel {
-        id: row.get("id"),
-        week: row.get("week"),
-        day: row.get("day"),
-        timetable_id: row.get("timetable_id"),
-        class_name: row.get("class_name"),
-        class_type: row.get("class_type"),
-        class_time: row.get("class_time"),
-        link: row.get("link"),
-    })
-    .collect();
+    .context("Failed to insert timetable entry")?;
 
-    Ok(entries)
+    Ok(())
 }
 
-pub async fn get_tomorrow_timetable(
+pub async fn get_today_timetable(
     pool: &PgPool,
-    chat_id: &str,
+    chat_id: ChatId,
 ) -> anyhow::Result<Vec<TimetableEntryModel>> {
-    let mut week = Week::current();
-    let day = Day::current();
-    if day == Day::Sat {
-        week = week.next();
-    }
-    let next_day = day.next();
+    let calendar = get_calendar(pool, chat_id).await?;
+    get_lessons_on(pool, chat_id, &calendar, calendar.today()).await
+}
 
-    let entries = sqlx::query(
-        r#"
-        SELECT
-            te.id,
-            te.week,
-            te.day,
-            te.timetable_id,
-            te.class_name,
-            te.class_type,
-            te.class_time,
-            te.link
-        FROM timetable_entries te
-        JOIN timetables tt ON te.timetable_id = tt.id
-        WHERE tt.chat_id = $1
-          AND te.week = $2
-          AND te.day = $3
-        ORDER BY te.class_time
-        "#,
-    )
-    .bind(chat_id)
-    .bind(week as i32)
-    .bind(next_day as i32)
-    .fetch_all(pool)
-    .await
-    .context("Failed to query tomorrow's timetable")?
-    .into_iter()
-    .map(|row| TimetableEntryModel {
-        id: row.get("id"),
-        week: row.get("week"),
-        day: row.get("day"),
-        timetable_id: row.get("timetable_id"),
-        class_name: row.get("class_name"),
-        class_type: row.get("class_type"),
-        class_time: row.get("class_time"),
-        link: row.get("link"),
-    })
-    .collect();
+pub async fn get_tomorrow_timetable(
+    pool: &PgPool,
+    chat_id: ChatId,
+) -> anyhow::Result<Vec<TimetableEntryModel>> {
+    let calendar = get_calendar(pool, chat_id).await?;
+    let tomorrow = calendar.today() + chrono::Duration::days(1);
+    get_lessons_on(pool, chat_id, &calendar, tomorrow).await
+}
 
-    Ok(entries)
+/// Lessons of the chat on `date` with one-off overrides applied.
+pub async fn get_lessons_on(
+    pool: &PgPool,
+    chat_id: ChatId,
+    calendar: &AcademicCalendar,
+    date: NaiveDate,
+) -> anyhow::Result<Vec<TimetableEntryModel>> {
+    let entries = get_full_timetable(pool, chat_id).await?;
+    let overrides = get_overrides(pool, chat_id, date, date).await?;
+    Ok(calendar.lessons_on(&entries, &overrides, date))
 }
 
 pub async fn get_week_timetable(
     pool: &PgPool,
-    chat_id: &str,
+    chat_id: ChatId,
 ) -> anyhow::Result<Vec<TimetableEntryModel>> {
-    let week = Week::current();
+    let calendar = get_calendar(pool, chat_id).await?;
+    let today = calendar.today();
+    let week = calendar.week_of(today);
 
     let entries = sqlx::query(
         r#"
@@ -230,7 +185,8 @@ pub async fn get_week_timetable(
             te.class_name,
             te.class_type,
             te.class_time,
-            te.link
+            te.link,
+            tt.name AS subgroup
         FROM timetable_entries te
         JOIN timetables tt ON te.timetable_id = tt.id
         WHERE tt.chat_id = $1
@@ -238,7 +194,7 @@ pub async fn get_week_timetable(
         ORDER BY te.day, te.class_time
         "#,
     )
-    .bind(chat_id)
+    .bind(chat_id.0)
     .bind(week as i32)
     .fetch_all(pool)
     .await
@@ -253,6 +209,10 @@ pub async fn get_week_timetable(
         class_type: row.get("class_type"),
         class_time: row.get("class_time"),
         link: row.get("link"),
+        subgroup: row.get("subgroup"),
+    })
+    .filter(|entry| {
+        !calendar.is_holiday(AcademicCalendar::date_in_week(today, Day::from(entry.day)))
     })
     .collect();
 
@@ -261,7 +221,7 @@ pub async fn get_week_timetable(
 
 pub async fn get_full_timetable(
     pool: &PgPool,
-    chat_id: &str,
+    chat_id: ChatId,
 ) -> anyhow::Result<Vec<TimetableEntryModel>> {
     let entries = sqlx::query(
         r#"
@@ -273,14 +233,15 @@ pub async fn get_full_timetable(
             te.class_name,
             te.class_type,
             te.class_time,
-            te.link
+            te.link,
+            tt.name AS subgroup
         FROM timetable_entries te
         JOIN timetables tt ON te.timetable_id = tt.id
         WHERE tt.chat_id = $1
         ORDER BY te.week, te.day, te.class_time
         "#,
     )
-    .bind(chat_id)
+    .bind(chat_id.0)
     .fetch_all(pool)
     .await
     .context("Failed to query full timetable")?
@@ -294,23 +255,55 @@ pub async fn get_full_timetable(
         class_type: row.get("class_type"),
         class_time: row.get("class_time"),
         link: row.get("link"),
+        subgroup: row.get("subgroup"),
     })
     .collect();
 
     Ok(entries)
 }
 
-pub async fn get_current_entry(
+/// Today's lessons that have started, of every subgroup; the last one is on now.
+pub async fn get_started_entries(
     pool: &PgPool,
-    chat_id: &str,
-) -> anyhow::Result<Option<TimetableEntryModel>> {
-    let week = Week::current();
-    let day = Day::current();
-    let now = get_current_time() - OFFSET;
-    let now_time = now.time();
+    chat_id: ChatId,
+) -> anyhow::Result<Vec<TimetableEntryModel>> {
+    let calendar = get_calendar(pool, chat_id).await?;
+    let now = calendar.now();
+    let now_time = (now - OFFSET).time();
 
     tracing::info!("Current time for timetable lookup: {}", now_time);
 
+    let entries = get_lessons_on(pool, chat_id, &calendar, now.date_naive())
+        .await?
+        .into_iter()
+        .filter(|entry| entry.class_time <= now_time)
+        .collect();
+
+    Ok(entries)
+}
+
+/// Today's lessons that have not started yet, of every subgroup.
+pub async fn get_upcoming_entries(
+    pool: &PgPool,
+    chat_id: ChatId,
+) -> anyhow::Result<Vec<TimetableEntryModel>> {
+    let calendar = get_calendar(pool, chat_id).await?;
+    let now = calendar.now();
+    let now_time = (now - OFFSET).time();
+
+    let entries = get_lessons_on(pool, chat_id, &calendar, now.date_naive())
+        .await?
+        .into_iter()
+        .filter(|entry| entry.class_time >= now_time)
+        .collect();
+
+    Ok(entries)
+}
+
+pub async fn get_entry_by_id(
+    pool: &PgPool,
+    entry_id: i32,
+) -> anyhow::Result<Option<TimetableEntryModel>> {
     let entry = sqlx::query(
         r#"
         SELECT
@@ -321,24 +314,20 @@ pub async fn get_current_entry(
             te.class_name,
             te.class_type,
             te.class_time,
-            te.link
+            te.link,
+            tt.name AS subgroup
         FROM timetable_entries te
         JOIN timetables tt ON te.timetable_id = tt.id
-        WHERE tt.chat_id = $1
-          AND te.week = $2
-          AND te.day = $3
-          AND te.class_time <= $4
-        ORDER BY te.class_time DESC
-        LIMIT 1
+        WHERE te.id = $1
         "#,
     )
-    .bind(chat_id)
-    .bind(week as i32)
-    .bind(day as i32)
-    .bind(now_time)
+    .bind(entry_id)
     .fetch_optional(pool)
     .await
-    .context("Failed to query current timetable entry")?
+    .context(format!(
+        "Failed to query timetable entry by id: {}",
+        entry_id
+    ))?
     .map(|row| TimetableEntryModel {
         id: row.get("id"),
         week: row.get("week"),
@@ -348,20 +337,35 @@ pub async fn get_current_entry(
         class_type: row.get("class_type"),
         class_time: row.get("class_time"),
         link: row.get("link"),
+        subgroup: row.get("subgroup"),
     });
 
     Ok(entry)
 }
 
-pub async fn get_next_entry(
+pub async fn update_link(pool: &PgPool, entry_id: i32, link: &str) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE timetable_entries
+        SET link = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(link)
+    .bind(entry_id)
+    .execute(pool)
+    .await
+    .context(format!("Failed to update link for entry id: {}", entry_id))?;
+
+    Ok(())
+}
+
+/// The entry, if it belongs to the chat's timetable.
+pub async fn get_chat_entry(
     pool: &PgPool,
-    chat_id: &str,
+    chat_id: ChatId,
+    entry_id: i32,
 ) -> anyhow::Result<Option<TimetableEntryModel>> {
-    let week = Week::current();
-    let day = Day::current();
-    let now = get_current_time() - OFFSET;
-    let now_time = now.time();
-
     let entry = sqlx::query(
         r#"
         SELECT
@@ -372,24 +376,22 @@ pub async fn get_next_entry(
             te.class_name,
             te.class_type,
             te.class_time,
-            te.link
+            te.link,
+            tt.name AS subgroup
         FROM timetable_entries te
         JOIN timetables tt ON te.timetable_id = tt.id
         WHERE tt.chat_id = $1
-          AND te.week = $2
-          AND te.day = $3
-          AND te.class_time >= $4
-        ORDER BY te.class_time ASC
-        LIMIT 1
+          AND te.id = $2
         "#,
     )
-    .bind(chat_id)
-    .bind(week as i32)
-    .bind(day as i32)
-    .bind(now_time)
+    .bind(chat_id.0)
+    .bind(entry_id)
     .fetch_optional(pool)
     .await
-    .context("Failed to query next timetable entry")?
+    .context(format!(
+        "Failed to query timetable entry by id: {}",
+        entry_id
+    ))?
     .map(|row| TimetableEntryModel {
         id: row.get("id"),
         week: row.get("week"),
@@ -399,64 +401,350 @@ pub async fn get_next_entry(
         class_type: row.get("class_type"),
         class_time: row.get("class_time"),
         link: row.get("link"),
+        subgroup: row.get("subgroup"),
     });
 
     Ok(entry)
 }
 
-pub async fn get_entry_by_id(
+/// Adds recurring entries for the whole chat, creating its timetable if there
+/// is none yet.
+pub async fn add_entries(
     pool: &PgPool,
-    entry_id: i32,
-) -> anyhow::Result<Option<TimetableEntryModel>> {
-    let entry = sqlx::query(
+    chat_id: ChatId,
+    entries: &[ImportedEntry],
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let timetable_id = get_or_create_timetable(&mut tx, chat_id.0, "").await?;
+
+    for entry in entries {
+        insert_entry(&mut tx, timetable_id, entry).await?;
+    }
+
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    Ok(())
+}
+
+pub async fn delete_entry(pool: &PgPool, entry_id: i32) -> anyhow::Result<()> {
+    sqlx::query(
         r#"
-        SELECT
-            id,
-            week,
-            day,
-            timetable_id,
-            class_name,
-            class_type,
-            class_time,
-            link
-        FROM timetable_entries
+        DELETE FROM timetable_entries
         WHERE id = $1
         "#,
     )
     .bind(entry_id)
-    .fetch_optional(pool)
+    .execute(pool)
+    .await
+    .context(format!("Failed to delete timetable entry id: {}", entry_id))?;
+
+    Ok(())
+}
+
+pub async fn update_class_name(pool: &PgPool, entry_id: i32, name: &str) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE timetable_entries
+        SET class_name = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(name)
+    .bind(entry_id)
+    .execute(pool)
+    .await
+    .context(format!("Failed to update name for entry id: {}", entry_id))?;
+
+    Ok(())
+}
+
+pub async fn update_class_time(
+    pool: &PgPool,
+    entry_id: i32,
+    class_time: NaiveTime,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE timetable_entries
+        SET class_time = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(class_time)
+    .bind(entry_id)
+    .execute(pool)
+    .await
+    .context(format!("Failed to update time for entry id: {}", entry_id))?;
+
+    Ok(())
+}
+
+/// Cancels (`moved_to` is `None`) or moves the lesson of `class_date`,
+/// replacing an earlier override of the same lesson.
+pub async fn set_override(
+    pool: &PgPool,
+    chat_id: ChatId,
+    entry_id: i32,
+    class_date: NaiveDate,
+    moved_to: Option<NaiveDateTime>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO timetable_overrides (chat_id, entry_id, class_date, moved_to)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (entry_id, class_date) DO UPDATE
+        SET moved_to = EXCLUDED.moved_to
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(entry_id)
+    .bind(class_date)
+    .bind(moved_to)
+    .execute(pool)
+    .await
+    .context(format!("Failed to set override for entry id: {}", entry_id))?;
+
+    Ok(())
+}
+
+/// Overrides of lessons originally on, or moved to, a date between `from` and `to`.
+pub async fn get_overrides(
+    pool: &PgPool,
+    chat_id: ChatId,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> anyhow::Result<Vec<TimetableOverrideModel>> {
+    let overrides = sqlx::query(
+        r#"
+        SELECT id, chat_id, entry_id, class_date, moved_to
+        FROM timetable_overrides
+        WHERE chat_id = $1
+          AND (class_date BETWEEN $2 AND $3 OR moved_to::DATE BETWEEN $2 AND $3)
+        ORDER BY class_date
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .context(format!("Failed to query overrides of chat_id: {}", chat_id))?
+    .into_iter()
+    .map(|row| TimetableOverrideModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        entry_id: row.get("entry_id"),
+        class_date: row.get("class_date"),
+        moved_to: row.get("moved_to"),
+    })
+    .collect();
+
+    Ok(overrides)
+}
+
+/// Overrides of the entry that have not passed yet.
+pub async fn get_upcoming_entry_overrides(
+    pool: &PgPool,
+    entry_id: i32,
+    today: NaiveDate,
+) -> anyhow::Result<Vec<TimetableOverrideModel>> {
+    let overrides = sqlx::query(
+        r#"
+        SELECT id, chat_id, entry_id, class_date, moved_to
+        FROM timetable_overrides
+        WHERE entry_id = $1
+          AND GREATEST(class_date, moved_to::DATE) >= $2
+        ORDER BY class_date
+        "#,
+    )
+    .bind(entry_id)
+    .bind(today)
+    .fetch_all(pool)
     .await
     .context(format!(
-        "Failed to query timetable entry by id: {}",
+        "Failed to query overrides of entry id: {}",
         entry_id
     ))?
-    .map(|row| TimetableEntryModel {
+    .into_iter()
+    .map(|row| TimetableOverrideModel {
         id: row.get("id"),
-        week: row.get("week"),
-        day: row.get("day"),
-        timetable_id: row.get("timetable_id"),
-        class_name: row.get("class_name"),
-        class_type: row.get("class_type"),
-        class_time: row.get("class_time"),
-        link: row.get("link"),
+        chat_id: row.get("chat_id"),
+        entry_id: row.get("entry_id"),
+        class_date: row.get("class_date"),
+        moved_to: row.get("moved_to"),
+    })
+    .collect();
+
+    Ok(overrides)
+}
+
+pub async fn remove_override(
+    pool: &PgPool,
+    chat_id: ChatId,
+    override_id: i32,
+) -> anyhow::Result<Option<TimetableOverrideModel>> {
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM timetable_overrides
+        WHERE chat_id = $1 AND id = $2
+        RETURNING id, chat_id, entry_id, class_date, moved_to
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(override_id)
+    .fetch_optional(pool)
+    .await
+    .context(format!("Failed to remove override id: {}", override_id))?
+    .map(|row| TimetableOverrideModel {
+        id: row.get("id"),
+        chat_id: row.get("chat_id"),
+        entry_id: row.get("entry_id"),
+        class_date: row.get("class_date"),
+        moved_to: row.get("moved_to"),
     });
 
-    Ok(entry)
+    Ok(removed)
 }
 
-pub async fn update_link(pool: &PgPool, entry_id: i32, link: &str) -> anyhow::Result<()> {
+/// Remembers the group to re-sync the subgroup's timetable from, or forgets it
+/// when `group_name` is `None`.
+pub async fn set_timetable_source(
+    pool: &PgPool,
+    chat_id: ChatId,
+    subgroup: &str,
+    source: &str,
+    group_name: Option<&str>,
+) -> anyhow::Result<()> {
+    let Some(group_name) = group_name else {
+        sqlx::query(
+            r#"
+            DELETE FROM timetable_sources
+            WHERE chat_id = $1 AND subgroup = $2
+            "#,
+        )
+        .bind(chat_id.0)
+        .bind(subgroup)
+        .execute(pool)
+        .await
+        .context(format!(
+            "Failed to delete timetable source for chat {}",
+            chat_id
+        ))?;
+        return Ok(());
+    };
+
     sqlx::query(
         r#"
-        UPDATE timetable_entries
-        SET link = $1
-        WHERE id = $2
+        INSERT INTO timetable_sources (chat_id, subgroup, source, group_name, synced_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (chat_id, subgroup)
+        DO UPDATE SET source = EXCLUDED.source, group_name = EXCLUDED.group_name, synced_at = NOW()
         "#,
     )
-    .bind(link)
-    .bind(entry_id)
+    .bind(chat_id.0)
+    .bind(subgroup)
+    .bind(source)
+    .bind(group_name)
     .execute(pool)
     .await
-    .context(format!("Failed to update link for entry id: {}", entry_id))?;
+    .context(format!(
+        "Failed to set timetable source for chat {}",
+        chat_id
+    ))?;
+
+    Ok(())
+}
+
+pub async fn get_timetable_sources(pool: &PgPool) -> anyhow::Result<Vec<TimetableSourceModel>> {
+    let sources = sqlx::query(
+        r#"
+        SELECT chat_id, source, group_name, subgroup, synced_at
+        FROM timetable_sources
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to query timetable sources")?
+    .into_iter()
+    .map(|row| TimetableSourceModel {
+        chat_id: row.get("chat_id"),
+        source: row.get("source"),
+        group_name: row.get("group_name"),
+        subgroup: row.get("subgroup"),
+        synced_at: row.get("synced_at"),
+    })
+    .collect();
+
+    Ok(sources)
+}
+
+pub async fn mark_synced(pool: &PgPool, chat_id: ChatId, subgroup: &str) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE timetable_sources
+        SET synced_at = NOW()
+        WHERE chat_id = $1 AND subgroup = $2
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(subgroup)
+    .execute(pool)
+    .await
+    .context(format!(
+        "Failed to mark timetable of chat {} synced",
+        chat_id
+    ))?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::postgres::PgPoolOptions;
+
+    use super::*;
+    use crate::{bot::timetable::Week, repositories::setup::MIGRATOR};
+
+    /// Needs a database: `cargo test -- --ignored` with `DATABASE_URL` set.
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_import_first_week_entry() {
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+        let pool = PgPoolOptions::new().connect(&database_url).await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+
+        let chat_id = -1_000_000_000_101;
+        sqlx::query("DELETE FROM chats WHERE chat_id = $1")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO chats (chat_id) VALUES ($1)")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let entry = ImportedEntry {
+            week: Week::First,
+            day: Day::Mon,
+            class_name: "Бази даних".to_string(),
+            class_type: "Лек".to_string(),
+            class_time: NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+            link: None,
+        };
+        import_timetable(&pool, chat_id, "", &[entry])
+            .await
+            .unwrap();
+        let entries = get_full_timetable(&pool, ChatId(chat_id)).await.unwrap();
+
+        sqlx::query("DELETE FROM chats WHERE chat_id = $1")
+            .bind(chat_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].week, Week::First as i32);
+        assert_eq!(entries[0].class_name, "Бази даних");
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::Context;
 use sqlx::PgPool;
-use teloxide::types::UserId;
+use teloxide::types::{ChatId, UserId};
 
 use crate::models::user::{UserModel, UserStatsModel};
 use crate::redis::RedisCache;
@@ -122,3 +122,46 @@ pub async fn get_user_by_id(state: &State, user_id: i32) -> anyhow::Result<UserM
     .context(format!("Failed to query user by id: {}", user_id))?
     .ok_or_else(|| anyhow::anyhow!("User with id {} not found", user_id))
 }
+
+/// A user by `@username`, or by name among the users of the chat. A name is only
+/// matched when no one else in the chat has it.
+pub async fn find_chat_user(
+    state: &State,
+    chat_id: ChatId,
+    query: &str,
+) -> anyhow::Result<Option<UserModel>> {
+    let pool: &PgPool = &state.db;
+
+    if let Some(username) = query.strip_prefix('@').filter(|name| !name.is_empty()) {
+        return sqlx::query_as::<_, UserModel>(
+            r#"
+            SELECT id, username, account_id, chat_id, name
+            FROM users
+            WHERE LOWER(username) = LOWER($1)
+            "#,
+        )
+        .bind(username)
+        .fetch_optional(pool)
+        .await
+        .context(format!("Failed to query user by username: {}", username));
+    }
+
+    let mut users = sqlx::query_as::<_, UserModel>(
+        r#"
+        SELECT id, username, account_id, chat_id, name
+        FROM users
+        WHERE chat_id = $1 AND LOWER(name) = LOWER($2)
+        LIMIT 2
+        "#,
+    )
+    .bind(chat_id.0)
+    .bind(query)
+    .fetch_all(pool)
+    .await
+    .context(format!(
+        "Failed to query users of chat_id {} by name",
+        chat_id
+    ))?;
+
+    Ok(if users.len() == 1 { users.pop() } else { None })
+}
@@ -1,49 +1,158 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
 
+use chrono::{NaiveDate, NaiveTime};
+use serde::Serialize;
 use sqlx::PgPool;
-use teloxide::types::{ChatId, MessageId};
+use teloxide::types::{ChatId, MessageId, UserId};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
-use crate::redis::setup::RedisStore;
+use crate::{models::cleanup::CleanupKind, redis::setup::RedisStore};
 
 pub struct AppState {
     pub db: PgPool,
     pub redis: RedisStore,
     pub http_client: reqwest::Client,
-    pub sender: tokio::sync::broadcast::Sender<Event>,
-    pub receiver: tokio::sync::broadcast::Receiver<Event>,
+    pub events: EventBus,
 }
 
 pub type State = Arc<AppState>;
 
-const MAX_CHANNEL_CAPACITY: usize = 100;
-
 impl AppState {
     pub fn new(pool: PgPool, redis: RedisStore) -> Arc<Self> {
-        let (event_tx, event_rx) = tokio::sync::broadcast::channel::<Event>(MAX_CHANNEL_CAPACITY);
-
         Arc::new(Self {
             db: pool,
             redis,
             http_client: reqwest::Client::new(),
-            sender: event_tx,
-            receiver: event_rx,
+            events: EventBus::new(),
         })
     }
 }
 
-#[derive(Clone, Debug)]
+/// Unbounded queue of [`Event`]s with a single consumer, so a slow handler
+/// delays events instead of dropping them.
+pub struct EventBus {
+    sender: UnboundedSender<Event>,
+    receiver: Mutex<Option<UnboundedReceiver<Event>>>,
+    pub stats: EventStats,
+}
+
+#[derive(Default)]
+pub struct EventStats {
+    published: AtomicU64,
+    handled: AtomicU64,
+    retried: AtomicU64,
+    dead_lettered: AtomicU64,
+    dropped: AtomicU64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct EventStatsSnapshot {
+    pub published: u64,
+    pub handled: u64,
+    pub retried: u64,
+    pub dead_lettered: u64,
+    pub dropped: u64,
+    /// Events published but neither handled nor given up on yet.
+    pub lag: u64,
+}
+
+impl EventBus {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+            stats: EventStats::default(),
+        }
+    }
+
+    pub fn send(&self, event: Event) -> anyhow::Result<()> {
+        self.stats.published.fetch_add(1, Ordering::Relaxed);
+        self.sender.send(event).map_err(|err| {
+            self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+            anyhow::anyhow!("Event bus is closed, dropped {:?}", err.0)
+        })
+    }
+
+    /// Hands the receiving end to the event loop; there is only one.
+    pub fn take_receiver(&self) -> Option<UnboundedReceiver<Event>> {
+        self.receiver.lock().ok()?.take()
+    }
+}
+
+impl EventStats {
+    pub fn record_handled(&self) {
+        self.handled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_retry(&self) {
+        self.retried.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_dead_letter(&self) {
+        self.dead_lettered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> EventStatsSnapshot {
+        let published = self.published.load(Ordering::Relaxed);
+        let handled = self.handled.load(Ordering::Relaxed);
+        let retried = self.retried.load(Ordering::Relaxed);
+        let dead_lettered = self.dead_lettered.load(Ordering::Relaxed);
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        EventStatsSnapshot {
+            published,
+            handled,
+            retried,
+            dead_lettered,
+            dropped,
+            lag: published.saturating_sub(handled + dead_lettered + dropped),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
     DeleteMessage {
         chat_id: ChatId,
         message_id: MessageId,
+        kind: CleanupKind,
     },
     NotifyTimetable {
         chat_id: ChatId,
         entry_id: i32,
+        class_date: NaiveDate,
+        /// Start of this occurrence, which differs from the entry's if it was moved.
+        class_time: NaiveTime,
+    },
+    TimetableDigest {
+        chat_id: ChatId,
+    },
+    TimetableChanged {
+        chat_id: ChatId,
+        summary: String,
+    },
+    DeadlineReminder {
+        chat_id: ChatId,
+        deadline_id: i32,
+        offset_minutes: i32,
+    },
+    PrivateNotification {
+        user_id: UserId,
+        text: String,
+        /// Delivered without sound, during the user's quiet hours.
+        silent: bool,
     },
     GambleResult {
         chat_id: ChatId,
         gamble_id: i32,
     },
+    /// Redraws the queue message after its schedule opened, closed or archived it.
+    RefreshQueue {
+        queue_id: i32,
+    },
     Exit,
 }